@@ -1,12 +1,13 @@
 use crate::config::Config;
 use crate::manager::{
-    handle_config_command, handle_delete_command, handle_edit_command, handle_exec_command,
-    handle_export_command, handle_list_command, handle_new_command, handle_push_command,
-    handle_search_command, handle_show_command, handle_sync_command,
+    handle_config_command, handle_delete_command, handle_diff_command, handle_edit_command,
+    handle_exec_command, handle_export_command, handle_history_command, handle_import_command,
+    handle_list_command, handle_migrate_command, handle_new_command, handle_push_command,
+    handle_revert_command, handle_search_command, handle_show_command, handle_sync_command,
+    handle_trash_command, handle_undo_command,
 };
 use crate::utils::error::AppResult;
-use crate::utils::print_warning;
-use clap::{Args, Parser, Subcommand};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use std::path::PathBuf;
 
 #[derive(Parser)]
@@ -20,15 +21,27 @@ pub struct Cli {
     #[arg(short, long)]
     pub debug: bool,
 
+    /// How errors are reported on exit
+    #[arg(long, value_enum, global = true, default_value_t = ErrorFormat::Text)]
+    pub error_format: ErrorFormat,
+
     #[command(subcommand)]
     pub command: Commands,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum ErrorFormat {
+    /// Human-readable message
+    Text,
+    /// `{"error": {...}}` object on stderr
+    Json,
+}
+
 impl Commands {
     pub async fn execute(self, config: Config) -> AppResult<()> {
         match self {
             Commands::New(args) => {
-                handle_new_command(config, &args).await?;
+                handle_new_command(config, &args).await?.into_result()?;
             }
             Commands::List(args) => {
                 handle_list_command(config, &args)?;
@@ -40,16 +53,16 @@ impl Commands {
                 handle_exec_command(config, &args)?;
             }
             Commands::Edit(args) => {
-                handle_edit_command(config, &args).await?;
+                handle_edit_command(config, &args).await?.into_result()?;
             }
             Commands::Config(args) => {
                 handle_config_command(config, args.command.clone())?;
             }
             Commands::Show(args) => {
-                handle_show_command(config, &args)?;
+                handle_show_command(config, &args)?.into_result()?;
             }
             Commands::Delete(args) => {
-                handle_delete_command(config, &args)?;
+                handle_delete_command(config, &args)?.into_result()?;
             }
             Commands::Sync(args) => {
                 handle_sync_command(config, &args).await?;
@@ -57,12 +70,30 @@ impl Commands {
             Commands::Push => {
                 handle_push_command(config).await?;
             }
-            Commands::Import(_) => {
-                print_warning("Import command not yet implemented");
+            Commands::Import(args) => {
+                handle_import_command(config, &args).await?;
             }
             Commands::Export(args) => {
                 handle_export_command(config, &args)?;
             }
+            Commands::Migrate(args) => {
+                handle_migrate_command(config, &args)?;
+            }
+            Commands::History(args) => {
+                handle_history_command(config, &args)?;
+            }
+            Commands::Diff(args) => {
+                handle_diff_command(config, &args)?;
+            }
+            Commands::Revert(args) => {
+                handle_revert_command(config, &args)?;
+            }
+            Commands::Trash(args) => {
+                handle_trash_command(config, &args)?;
+            }
+            Commands::Undo => {
+                handle_undo_command(config)?;
+            }
         }
         Ok(())
     }
@@ -105,6 +136,24 @@ pub enum Commands {
 
     /// Export prompts to file
     Export(ExportArgs),
+
+    /// Copy prompts between the TOML, SQLite and Markdown stores
+    Migrate(MigrateArgs),
+
+    /// List earlier revisions of a prompt
+    History(HistoryArgs),
+
+    /// Show how a prompt changed between revisions
+    Diff(DiffArgs),
+
+    /// Restore a prompt to an earlier revision
+    Revert(RevertArgs),
+
+    /// List, restore or purge deleted prompts
+    Trash(TrashArgs),
+
+    /// Undo the last destructive operation (delete, import, sync, revert, emptying the trash)
+    Undo,
 }
 
 #[derive(Args)]
@@ -193,11 +242,17 @@ pub struct ExecArgs {
     #[arg(help = "Prompt ID or description")]
     pub identifier: Option<String>,
 
+    /// Print the rendered prompt to stdout instead of copying it
     #[arg(long)]
     pub output: bool,
 
-    #[arg(long)]
+    /// Variable value as name=value (repeatable)
+    #[arg(long, value_name = "NAME=VALUE")]
     pub vars: Vec<String>,
+
+    /// JSON or TOML file with variable values
+    #[arg(long, value_name = "FILE")]
+    pub vars_file: Option<PathBuf>,
 }
 
 #[derive(Args)]
@@ -214,12 +269,21 @@ pub struct ShowArgs {
     #[arg(help = "Prompt ID or title")]
     pub identifier: String,
 
-    #[arg(long)]
+    /// Render the content with a variable value as name=value (repeatable)
+    #[arg(long, value_name = "NAME=VALUE")]
     pub vars: Vec<String>,
+
+    /// Render the content with values from a JSON or TOML file
+    #[arg(long, value_name = "FILE")]
+    pub vars_file: Option<PathBuf>,
 }
 
 #[derive(Args)]
+#[command(args_conflicts_with_subcommands = true)]
 pub struct SyncArgs {
+    #[command(subcommand)]
+    pub command: Option<SyncCommands>,
+
     #[arg(short, long)]
     pub upload: bool,
 
@@ -228,6 +292,41 @@ pub struct SyncArgs {
 
     #[arg(short, long)]
     pub force: bool,
+
+    #[arg(
+        long,
+        conflicts_with_all = ["theirs", "keep_both"],
+        help = "Resolve conflicts by keeping local prompts"
+    )]
+    pub ours: bool,
+
+    #[arg(
+        long,
+        conflicts_with = "keep_both",
+        help = "Resolve conflicts by keeping remote prompts"
+    )]
+    pub theirs: bool,
+
+    #[arg(long, help = "Resolve conflicts by keeping both versions")]
+    pub keep_both: bool,
+
+    #[arg(long, help = "Show what sync would change without writing anything")]
+    pub dry_run: bool,
+}
+
+#[derive(Subcommand, Clone)]
+pub enum SyncCommands {
+    /// List backups taken before sync replaced local or remote prompts
+    History,
+
+    /// Restore prompts from a sync backup
+    Rollback {
+        #[arg(help = "Snapshot name or unique prefix, as shown by 'sync history'")]
+        snapshot: String,
+
+        #[arg(long, help = "Also upload the restored prompts to the remote")]
+        push: bool,
+    },
 }
 
 #[derive(Args)]
@@ -238,8 +337,15 @@ pub struct ImportArgs {
     #[arg(short, long)]
     pub format: Option<ImportFormat>,
 
-    #[arg(long)]
+    #[arg(long, help = "Merge into existing prompts instead of replacing them")]
     pub merge: bool,
+
+    #[arg(
+        short = 'y',
+        long,
+        help = "Replace existing prompts without confirmation"
+    )]
+    pub force: bool,
 }
 
 #[derive(Args)]
@@ -259,6 +365,84 @@ pub enum ListFormat {
     Json,
 }
 
+#[derive(Args)]
+pub struct MigrateArgs {
+    #[arg(long, value_enum, help = "Store to copy the prompts into")]
+    pub to: MigrateTarget,
+
+    #[arg(
+        long,
+        value_enum,
+        help = "Store to copy the prompts from [default: the configured storage]"
+    )]
+    pub from: Option<MigrateTarget>,
+
+    #[arg(
+        short = 'y',
+        long,
+        help = "Overwrite prompts already in the target store"
+    )]
+    pub force: bool,
+}
+
+#[derive(Args)]
+pub struct HistoryArgs {
+    #[arg(help = "Prompt ID, unique ID prefix or title")]
+    pub identifier: String,
+}
+
+#[derive(Args)]
+pub struct DiffArgs {
+    #[arg(help = "Prompt ID, unique ID prefix or title")]
+    pub identifier: String,
+
+    #[arg(help = "Revision to compare from [default: the latest revision]")]
+    pub from: Option<usize>,
+
+    #[arg(help = "Revision to compare to [default: the current prompt]")]
+    pub to: Option<usize>,
+}
+
+#[derive(Args)]
+pub struct RevertArgs {
+    #[arg(help = "Prompt ID, unique ID prefix or title")]
+    pub identifier: String,
+
+    #[arg(help = "Revision to restore, as shown by 'history'")]
+    pub revision: usize,
+}
+
+#[derive(Args)]
+pub struct TrashArgs {
+    #[command(subcommand)]
+    pub command: Option<TrashCommands>,
+}
+
+#[derive(Subcommand)]
+pub enum TrashCommands {
+    /// List deleted prompts
+    List,
+
+    /// Move a deleted prompt back into the store
+    Restore {
+        #[arg(help = "Prompt ID, unique ID prefix or title")]
+        identifier: String,
+    },
+
+    /// Permanently remove every prompt in the trash
+    Empty {
+        #[arg(short, long, help = "Do not ask for confirmation")]
+        force: bool,
+    },
+}
+
+#[derive(clap::ValueEnum, Clone, Copy)]
+pub enum MigrateTarget {
+    Toml,
+    Sqlite,
+    Markdown,
+}
+
 #[derive(clap::ValueEnum, Clone)]
 pub enum ImportFormat {
     Toml,
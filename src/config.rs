@@ -6,8 +6,18 @@ use std::path::PathBuf;
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     pub general: GeneralConfig,
+    #[serde(default)]
+    pub network: NetworkConfig,
     pub gist: Option<GistConfig>,
     pub gitlab: Option<GitLabConfig>,
+    #[serde(default)]
+    pub webdav: Option<WebDavConfig>,
+    #[serde(default)]
+    pub git: Option<GitConfig>,
+    #[serde(default)]
+    pub directory: Option<DirectoryConfig>,
+    #[serde(default)]
+    pub encryption: Option<EncryptionConfig>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -15,6 +25,20 @@ pub struct GeneralConfig {
     pub prompt_file: PathBuf,
     #[serde(default)]
     pub prompt_dirs: Vec<PathBuf>,
+    /// Where prompts are kept: the TOML `prompt_file`, the SQLite
+    /// `database_file` or one Markdown file per prompt in `markdown_dir`
+    #[serde(default)]
+    pub storage: StorageBackend,
+    #[serde(default = "default_database_file")]
+    pub database_file: PathBuf,
+    #[serde(default = "default_markdown_dir")]
+    pub markdown_dir: PathBuf,
+    /// Days deleted prompts stay in the trash; 0 keeps them until emptied
+    #[serde(default = "default_trash_retention_days")]
+    pub trash_retention_days: u32,
+    /// Allow `@cmd:` variable values, which run a shell command after confirmation
+    #[serde(default)]
+    pub allow_command_vars: bool,
     pub editor: String,
     pub select_cmd: String,
     pub default_tags: Vec<String>,
@@ -29,6 +53,36 @@ pub struct GeneralConfig {
     pub format: Option<String>,
 }
 
+/// HTTP settings shared by the sync backends
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NetworkConfig {
+    /// Total time allowed for a single request
+    pub timeout_secs: u64,
+    pub connect_timeout_secs: u64,
+    /// Retries after connection errors, server errors and short rate limits
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled for every further one
+    pub retry_delay_ms: u64,
+    /// Longest rate-limit wait to sit out instead of failing
+    pub max_rate_limit_wait_secs: u64,
+    /// Proxy URL for all requests, e.g. `http://proxy.example.com:8080`
+    pub proxy: Option<String>,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            timeout_secs: 30,
+            connect_timeout_secs: 10,
+            max_retries: 3,
+            retry_delay_ms: 500,
+            max_rate_limit_wait_secs: 60,
+            proxy: None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GistConfig {
     pub file_name: String,
@@ -46,6 +100,21 @@ pub struct GistConfig {
     pub gist_id: Option<String>,
     pub public: bool,
     pub auto_sync: bool,
+    /// How prompts are spread over files in the gist
+    #[serde(default)]
+    pub layout: GistLayout,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GistLayout {
+    /// All prompts in `file_name`
+    #[default]
+    Single,
+    /// One file per category, named after `file_name`
+    Category,
+    /// One file per prompt, named after `file_name`
+    Prompt,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -54,11 +123,94 @@ pub struct GitLabConfig {
     pub access_token: Option<String>,
     pub url: String,
     pub id: Option<i32>,
+    /// Project ID or path for project snippets; personal snippets when unset
+    #[serde(default)]
+    pub project: Option<String>,
     pub visibility: String,
     pub auto_sync: bool,
     pub skip_ssl: bool,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebDavConfig {
+    /// Collection URL the prompt file lives in, e.g. a Nextcloud files URL
+    pub url: String,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    #[serde(default = "default_sync_file_name")]
+    pub file_name: String,
+    #[serde(default)]
+    pub auto_sync: bool,
+    #[serde(default)]
+    pub skip_ssl: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitConfig {
+    /// Repository URL or local path used as the sync remote
+    pub repository: String,
+    #[serde(default = "default_git_branch")]
+    pub branch: String,
+    #[serde(default = "default_sync_file_name")]
+    pub file_name: String,
+    #[serde(default)]
+    pub auto_sync: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageBackend {
+    #[default]
+    Toml,
+    Sqlite,
+    Markdown,
+}
+
+fn default_database_file() -> PathBuf {
+    Config::config_dir().join("prompts.db")
+}
+
+fn default_markdown_dir() -> PathBuf {
+    Config::config_dir().join("prompts")
+}
+
+fn default_trash_retention_days() -> u32 {
+    30
+}
+
+fn default_git_branch() -> String {
+    "main".to_string()
+}
+
+fn default_sync_file_name() -> String {
+    "prompts.toml".to_string()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DirectoryConfig {
+    /// Shared folder holding the synced file, e.g. a Dropbox or NFS mount
+    pub path: PathBuf,
+    #[serde(default = "default_sync_file_name")]
+    pub file_name: String,
+    #[serde(default)]
+    pub auto_sync: bool,
+}
+
+/// Encryption of synced content; local prompt files stay plaintext
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncryptionConfig {
+    #[serde(default)]
+    pub passphrase: Option<String>,
+    /// File whose contents are used as the key; takes precedence over the passphrase
+    #[serde(default)]
+    pub key_file: Option<PathBuf>,
+    /// Accept a remote that is not encrypted yet, to migrate it once
+    #[serde(default)]
+    pub allow_plaintext_remote: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum SortBy {
@@ -70,14 +222,17 @@ pub enum SortBy {
 
 impl Default for Config {
     fn default() -> Self {
-        let config_dir = dirs::config_dir()
-            .unwrap_or_else(|| PathBuf::from("."))
-            .join("promptheus");
+        let config_dir = Config::config_dir();
 
         Self {
             general: GeneralConfig {
                 prompt_file: config_dir.join("prompts.toml"),
                 prompt_dirs: Vec::new(),
+                storage: StorageBackend::Toml,
+                database_file: default_database_file(),
+                markdown_dir: default_markdown_dir(),
+                trash_retention_days: default_trash_retention_days(),
+                allow_command_vars: false,
                 editor: detect_editor(None),
                 select_cmd: detect_best_select_command(),
                 default_tags: Vec::new(),
@@ -88,14 +243,20 @@ impl Default for Config {
                 search_case_sensitive: false,
                 format: None,
             },
+            network: NetworkConfig::default(),
             gist: Some(GistConfig {
                 file_name: String::new(),
                 access_token: None,
                 gist_id: None,
                 public: false,
                 auto_sync: false,
+                layout: GistLayout::Single,
             }),
             gitlab: None,
+            webdav: None,
+            git: None,
+            directory: None,
+            encryption: None,
         }
     }
 }
@@ -149,11 +310,11 @@ impl Config {
             return Ok(default_config);
         }
 
-        let content =
-            std::fs::read_to_string(config_path).map_err(|e| AppError::Io(e.to_string()))?;
+        let content = std::fs::read_to_string(config_path)
+            .map_err(|e| AppError::io(e.to_string()).with_source(e))?;
 
-        let config: Config = toml::from_str(&content)
-            .map_err(|e| AppError::System(format!("Failed to parse config file: {}", e)))?;
+        let config: Config =
+            toml::from_str(&content).map_err(|e| AppError::toml_parse(config_path, &content, e))?;
 
         config.validate()?;
         Ok(config)
@@ -161,31 +322,77 @@ impl Config {
 
     pub fn validate(&self) -> AppResult<()> {
         if self.general.editor.is_empty() {
-            return Err(AppError::System("Editor cannot be empty".to_string()));
+            return Err(AppError::Config("Editor cannot be empty".to_string()));
         }
 
         if self.general.select_cmd.is_empty() {
-            return Err(AppError::System(
+            return Err(AppError::Config(
                 "Select command cannot be empty".to_string(),
             ));
         }
 
         if let Some(gitlab) = &self.gitlab {
             if gitlab.url.is_empty() {
-                return Err(AppError::System("GitLab URL cannot be empty".to_string()));
+                return Err(AppError::Config("GitLab URL cannot be empty".to_string()));
             }
             if gitlab.file_name.is_empty() {
-                return Err(AppError::System(
+                return Err(AppError::Config(
                     "GitLab file name cannot be empty".to_string(),
                 ));
             }
         }
 
+        if let Some(webdav) = &self.webdav {
+            if webdav.url.is_empty() {
+                return Err(AppError::Config("WebDAV URL cannot be empty".to_string()));
+            }
+            if webdav.file_name.is_empty() {
+                return Err(AppError::Config(
+                    "WebDAV file name cannot be empty".to_string(),
+                ));
+            }
+        }
+
+        if let Some(git) = &self.git {
+            if git.repository.is_empty() {
+                return Err(AppError::Config(
+                    "Git repository cannot be empty".to_string(),
+                ));
+            }
+            if git.file_name.is_empty() {
+                return Err(AppError::Config(
+                    "Git file name cannot be empty".to_string(),
+                ));
+            }
+        }
+
+        if let Some(directory) = &self.directory {
+            if directory.path.as_os_str().is_empty() {
+                return Err(AppError::Config(
+                    "Sync directory path cannot be empty".to_string(),
+                ));
+            }
+            if directory.file_name.is_empty() {
+                return Err(AppError::Config(
+                    "Sync directory file name cannot be empty".to_string(),
+                ));
+            }
+        }
+
+        if self.encryption.is_some()
+            && let Some(gist) = &self.gist
+            && gist.layout != GistLayout::Single
+        {
+            return Err(AppError::Config(
+                "Encrypted sync requires the single-file gist layout".to_string(),
+            ));
+        }
+
         if let Some(gist) = &self.gist {
             // Only validate gist configuration if it's actually being used (has gist_id or non-empty file_name)
             if gist.gist_id.is_some() || !gist.file_name.is_empty() {
                 if gist.file_name.is_empty() {
-                    return Err(AppError::System(
+                    return Err(AppError::Config(
                         "Gist file name cannot be empty when gist sync is configured".to_string(),
                     ));
                 }
@@ -194,7 +401,7 @@ impl Config {
                 if gist.gist_id.is_some() && gist.access_token.is_none() {
                     // Check environment variables
                     if std::env::var("PROMPTHEUS_GITHUB_ACCESS_TOKEN").is_err() {
-                        return Err(AppError::System(
+                        return Err(AppError::Config(
                             "GitHub access token is required for gist sync. Set it in config or use PROMPTHEUS_GITHUB_ACCESS_TOKEN environment variable".to_string()
                         ));
                     }
@@ -205,7 +412,7 @@ impl Config {
                     .extension()
                     .is_some_and(|ext| ext.eq_ignore_ascii_case("toml"))
                 {
-                    return Err(AppError::System(
+                    return Err(AppError::Config(
                         "Gist file name should have .toml extension for proper prompt storage"
                             .to_string(),
                     ));
@@ -220,21 +427,27 @@ impl Config {
         let config_path = Self::config_file_path();
 
         if let Some(parent) = config_path.parent() {
-            std::fs::create_dir_all(parent).map_err(|e| AppError::Io(e.to_string()))?;
+            std::fs::create_dir_all(parent)
+                .map_err(|e| AppError::io(e.to_string()).with_source(e))?;
         }
 
-        let content = toml::to_string_pretty(self)
-            .map_err(|e| AppError::System(format!("Failed to serialize config: {}", e)))?;
+        let content = toml::to_string_pretty(self).map_err(|e| {
+            AppError::system(format!("Failed to serialize config: {}", e)).with_source(e)
+        })?;
 
-        std::fs::write(&config_path, content).map_err(|e| AppError::Io(e.to_string()))?;
+        std::fs::write(&config_path, content)
+            .map_err(|e| AppError::io(e.to_string()).with_source(e))?;
 
         Ok(())
     }
 
     pub fn config_file_path() -> PathBuf {
+        Self::config_dir().join("config.toml")
+    }
+
+    pub fn config_dir() -> PathBuf {
         dirs::config_dir()
             .unwrap_or_else(|| PathBuf::from("."))
             .join("promptheus")
-            .join("config.toml")
     }
 }
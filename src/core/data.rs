@@ -8,12 +8,28 @@ use crate::utils::format;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+use std::sync::{LazyLock, Mutex};
 use uuid::Uuid;
 
+/// Shortest ID prefix accepted in place of a full ID
+pub const MIN_ID_PREFIX: usize = 4;
+
+/// Shortest ID prefix shown in listings
+const SHORT_ID_LEN: usize = 8;
+
+/// Description and creation time of a prompt saved without an ID
+type PromptKey = (String, DateTime<Utc>);
+
+/// IDs given to prompts saved without one
+static ASSIGNED_IDS: LazyLock<Mutex<HashMap<PromptKey, String>>> = LazyLock::new(Default::default);
+
 /// A single prompt with metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Prompt {
-    #[serde(skip)]
+    #[serde(rename = "Id", default, skip_serializing_if = "Option::is_none")]
     pub id: Option<String>,
     #[serde(rename = "Description")]
     pub description: String,
@@ -28,16 +44,31 @@ pub struct Prompt {
     #[serde(rename = "Created_at")]
     #[serde(with = "format")]
     pub created_at: DateTime<Utc>,
-    #[serde(skip)]
+    #[serde(rename = "Updated_at", default)]
+    #[serde(with = "format")]
     pub updated_at: DateTime<Utc>,
+    /// File in `prompt_dirs` the prompt was loaded from; `None` for `prompt_file`
+    #[serde(skip)]
+    pub source: Option<PathBuf>,
 }
 
 /// Collection of prompts with metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PromptCollection {
+    #[serde(deserialize_with = "deserialize_prompts")]
     pub prompts: Vec<Prompt>,
 }
 
+/// Deserialize prompts, backfilling update times missing from older files
+fn deserialize_prompts<'de, D>(deserializer: D) -> Result<Vec<Prompt>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let mut prompts = Vec::<Prompt>::deserialize(deserializer)?;
+    prompts.iter_mut().for_each(Prompt::backfill_updated_at);
+    Ok(prompts)
+}
+
 /// Statistics about the prompt collection
 #[derive(Debug)]
 pub struct PromptStats {
@@ -48,6 +79,14 @@ pub struct PromptStats {
     pub category_counts: HashMap<String, usize>,
 }
 
+/// Outcome of merging one prompt collection into another
+#[derive(Debug, Default)]
+pub struct MergeReport {
+    pub added: Vec<String>,
+    pub updated: Vec<String>,
+    pub skipped: Vec<String>,
+}
+
 impl Prompt {
     /// Create a new prompt with the given description and content
     pub fn new(description: String, content: String) -> Self {
@@ -61,6 +100,7 @@ impl Prompt {
             created_at: now,
             updated_at: now,
             category: None,
+            source: None,
         }
     }
 
@@ -72,8 +112,63 @@ impl Prompt {
             && !tags.contains(&tag)
         {
             tags.push(tag);
-            self.updated_at = Utc::now();
+        } else {
+            return;
         }
+        self.touch();
+    }
+
+    /// Mark the prompt as modified now
+    pub fn touch(&mut self) {
+        self.updated_at = Utc::now();
+    }
+
+    /// ID for a prompt saved without one, until that ID is persisted
+    ///
+    /// Every copy with the same description and creation time loaded in
+    /// this process (local, remote, sync base) gets the same new UUID, so
+    /// copies still match and a prompt looked up twice keeps its ID.
+    fn assigned_id(&self) -> String {
+        let mut ids = ASSIGNED_IDS
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        ids.entry((self.description.clone(), self.created_at))
+            .or_insert_with(|| Uuid::new_v4().to_string())
+            .clone()
+    }
+
+    /// The ID older versions derived on every load, from a hash of the
+    /// description and creation time
+    ///
+    /// The hash differs between Rust versions, so it is never stored; it is
+    /// only accepted as an alias in [`PromptCollection::find_prompt`].
+    fn legacy_id(&self) -> String {
+        let input = format!("{}{}", self.description, self.created_at.to_rfc3339());
+        let mut hasher = DefaultHasher::new();
+        input.hash(&mut hasher);
+        format!("{:x}", hasher.finish())
+    }
+
+    /// The first `len` characters of the ID
+    pub fn short_id(&self, len: usize) -> &str {
+        let id = self.id.as_deref().unwrap_or_default();
+        id.get(..len).unwrap_or(id)
+    }
+
+    /// Use the creation time for prompts saved without an update time
+    fn backfill_updated_at(&mut self) {
+        if self.updated_at < self.created_at {
+            self.updated_at = self.created_at;
+        }
+    }
+
+    /// Check whether two prompts carry the same user-visible data
+    pub fn same_content(&self, other: &Prompt) -> bool {
+        self.description == other.description
+            && self.content == other.content
+            && self.category == other.category
+            && self.tag == other.tag
+            && self.output == other.output
     }
 }
 
@@ -86,10 +181,53 @@ impl PromptCollection {
     }
 
     /// Add a new prompt to the collection
-    pub fn add_prompt(&mut self, prompt: Prompt) {
+    pub fn add_prompt(&mut self, mut prompt: Prompt) {
+        prompt.touch();
         self.prompts.push(prompt);
     }
 
+    /// Give prompts saved without an ID a new one, written with the next save
+    pub fn ensure_ids(&mut self) {
+        for prompt in &mut self.prompts {
+            if prompt.id.is_none() {
+                prompt.id = Some(prompt.assigned_id());
+            }
+        }
+    }
+
+    /// Give prompts saved without an ID the ID of their copy in `other`
+    ///
+    /// Copies are matched by description and creation time, so a remote or
+    /// sync base written before IDs existed lines up with a store whose IDs
+    /// were already persisted.
+    pub fn adopt_ids_from(&mut self, other: &PromptCollection) {
+        for prompt in self.prompts.iter_mut().filter(|p| p.id.is_none()) {
+            prompt.id = other
+                .prompts
+                .iter()
+                .find(|p| p.description == prompt.description && p.created_at == prompt.created_at)
+                .and_then(|p| p.id.clone());
+        }
+    }
+
+    /// Whether some prompt was saved without an ID
+    pub fn has_missing_ids(&self) -> bool {
+        self.prompts.iter().any(|p| p.id.is_none())
+    }
+
+    /// Length of the shortest ID prefix that tells all prompts apart
+    pub fn short_id_len(&self) -> usize {
+        let mut ids: Vec<&str> = self
+            .prompts
+            .iter()
+            .filter_map(|p| p.id.as_deref())
+            .collect();
+        ids.sort_unstable();
+        ids.windows(2)
+            .map(|pair| common_prefix_len(pair[0], pair[1]) + 1)
+            .fold(SHORT_ID_LEN, usize::max)
+    }
+
     /// Delete a prompt by ID or description (smart delete)
     pub fn delete_prompt(&mut self, identifier: &str) -> Option<Prompt> {
         if let Some(prompt) = self.find_prompt(identifier) {
@@ -105,6 +243,42 @@ impl PromptCollection {
         None
     }
 
+    /// Merge another collection into this one
+    ///
+    /// Incoming prompts are matched against existing ones by ID, then by
+    /// description. Matches with identical data are skipped, other matches
+    /// replace the existing prompt while keeping its ID and creation time.
+    pub fn merge(&mut self, incoming: PromptCollection) -> MergeReport {
+        let mut report = MergeReport::default();
+
+        for mut prompt in incoming.prompts {
+            let existing = self.prompts.iter().position(|p| {
+                (p.id.is_some() && p.id == prompt.id) || p.description == prompt.description
+            });
+
+            match existing {
+                Some(index) if self.prompts[index].same_content(&prompt) => {
+                    report.skipped.push(prompt.description);
+                }
+                Some(index) => {
+                    let current = &self.prompts[index];
+                    prompt.id = current.id.clone().or(prompt.id);
+                    prompt.created_at = current.created_at;
+                    prompt.source = current.source.clone();
+                    prompt.touch();
+                    report.updated.push(prompt.description.clone());
+                    self.prompts[index] = prompt;
+                }
+                None => {
+                    report.added.push(prompt.description.clone());
+                    self.prompts.push(prompt);
+                }
+            }
+        }
+
+        report
+    }
+
     /// Find a prompt by ID
     pub fn find_by_id(&self, id: &str) -> Option<&Prompt> {
         self.prompts
@@ -112,17 +286,33 @@ impl PromptCollection {
             .find(|p| p.id.as_ref() == Some(&id.to_string()))
     }
 
+    /// Find the only prompt whose ID starts with `prefix`, like git's short hashes
+    pub fn find_by_id_prefix(&self, prefix: &str) -> Option<&Prompt> {
+        if prefix.len() < MIN_ID_PREFIX {
+            return None;
+        }
+        let mut matches = self
+            .prompts
+            .iter()
+            .filter(|p| p.id.as_deref().is_some_and(|id| id.starts_with(prefix)));
+        let found = matches.next()?;
+        matches.next().is_none().then_some(found)
+    }
+
     /// Find a prompt by description
     pub fn find_by_description(&self, description: &str) -> Option<&Prompt> {
         self.prompts.iter().find(|p| p.description == description)
     }
 
-    /// Find a prompt by ID or description
+    /// Find a prompt by ID, description or unique ID prefix
+    ///
+    /// The hash-based ID older versions showed is accepted as well, so
+    /// scripts written against it keep working.
     pub fn find_prompt(&self, identifier: &str) -> Option<&Prompt> {
-        if let Some(prompt) = self.find_by_id(identifier) {
-            return Some(prompt);
-        }
-        self.find_by_description(identifier)
+        self.find_by_id(identifier)
+            .or_else(|| self.find_by_description(identifier))
+            .or_else(|| self.find_by_id_prefix(identifier))
+            .or_else(|| self.prompts.iter().find(|p| p.legacy_id() == identifier))
     }
 
     /// Search prompts with query and tag filtering
@@ -234,12 +424,23 @@ impl PromptCollection {
     }
 }
 
+fn common_prefix_len(a: &str, b: &str) -> usize {
+    a.chars().zip(b.chars()).take_while(|(x, y)| x == y).count()
+}
+
 impl Default for PromptCollection {
     fn default() -> Self {
         Self::new()
     }
 }
 
+impl From<Vec<Prompt>> for PromptCollection {
+    fn from(mut prompts: Vec<Prompt>) -> Self {
+        prompts.iter_mut().for_each(Prompt::backfill_updated_at);
+        Self { prompts }
+    }
+}
+
 impl std::fmt::Display for Prompt {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         if let Some(category) = &self.category {
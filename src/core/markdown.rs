@@ -0,0 +1,330 @@
+//! Markdown prompt store
+//!
+//! Keeps every prompt in its own `.md` file: metadata goes into a
+//! frontmatter block and the Markdown body is the prompt content. Files are
+//! written with YAML frontmatter (`---`); TOML frontmatter (`+++`) is read too.
+
+use crate::core::data::{Prompt, PromptCollection};
+use crate::utils::error::{AppError, AppResult};
+use crate::utils::file::write_atomic;
+use crate::utils::format::{self, slugify};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Metadata stored in the frontmatter of a prompt file
+#[derive(Debug, Serialize, Deserialize)]
+struct Frontmatter {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    category: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    output: Option<String>,
+    #[serde(with = "format")]
+    created_at: DateTime<Utc>,
+    #[serde(default, with = "format")]
+    updated_at: DateTime<Utc>,
+}
+
+pub struct MarkdownStore {
+    dir: PathBuf,
+}
+
+impl MarkdownStore {
+    pub fn new(dir: &Path) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+        }
+    }
+
+    pub fn ensure_exists(&self) -> AppResult<()> {
+        fs::create_dir_all(&self.dir).map_err(|e| {
+            AppError::io(format!(
+                "Failed to create prompt directory: {}: {}",
+                self.dir.display(),
+                e
+            ))
+            .with_source(e)
+        })
+    }
+
+    /// Load every prompt file, ordered by file name
+    pub fn load_collection(&self) -> AppResult<PromptCollection> {
+        let prompts = self
+            .prompt_files()?
+            .iter()
+            .map(|path| read_prompt(path))
+            .collect::<AppResult<Vec<_>>>()?;
+        Ok(PromptCollection { prompts })
+    }
+
+    /// Make the directory hold exactly the prompts in `collection`
+    ///
+    /// Prompts keep the file they were loaded from, new prompts get a file
+    /// named after their description, unchanged files are not rewritten and
+    /// files of removed prompts are deleted.
+    pub fn replace_all(&self, collection: &PromptCollection) -> AppResult<()> {
+        self.ensure_exists()?;
+        let mut collection = collection.clone();
+        collection.ensure_ids();
+
+        let existing = self.files_by_id()?;
+        let mut taken: HashSet<PathBuf> = HashSet::new();
+        let mut paths: Vec<Option<PathBuf>> = collection
+            .prompts
+            .iter()
+            .map(|prompt| {
+                prompt
+                    .id
+                    .as_ref()
+                    .and_then(|id| existing.get(id))
+                    .filter(|path| taken.insert((*path).clone()))
+                    .cloned()
+            })
+            .collect();
+
+        // New prompts are named only after existing ones kept their files
+        for (path, prompt) in paths.iter_mut().zip(&collection.prompts) {
+            if path.is_none() {
+                let free = self.free_path(&prompt.description, &taken);
+                taken.insert(free.clone());
+                *path = Some(free);
+            }
+        }
+        let planned = paths.into_iter().flatten().zip(&collection.prompts);
+
+        for (path, prompt) in planned {
+            let content = render_prompt(prompt)?;
+            if fs::read_to_string(&path).is_ok_and(|current| current == content) {
+                continue;
+            }
+            write_atomic(&path, &content).map_err(|e| {
+                AppError::io(format!(
+                    "Failed to write prompt file: {}: {}",
+                    path.display(),
+                    e
+                ))
+                .with_source(e)
+            })?;
+        }
+
+        for path in existing.values().filter(|path| !taken.contains(*path)) {
+            fs::remove_file(path).map_err(|e| {
+                AppError::io(format!(
+                    "Failed to remove prompt file: {}: {}",
+                    path.display(),
+                    e
+                ))
+                .with_source(e)
+            })?;
+        }
+
+        Ok(())
+    }
+
+    /// File holding the prompt with the given ID
+    pub fn path_of(&self, id: &str) -> AppResult<Option<PathBuf>> {
+        Ok(self.files_by_id()?.remove(id))
+    }
+
+    pub fn is_empty(&self) -> AppResult<bool> {
+        Ok(self.prompt_files()?.is_empty())
+    }
+
+    fn prompt_files(&self) -> AppResult<Vec<PathBuf>> {
+        if !self.dir.is_dir() {
+            return Ok(Vec::new());
+        }
+
+        let entries = fs::read_dir(&self.dir).map_err(|e| {
+            AppError::io(format!(
+                "Failed to read prompt directory: {}: {}",
+                self.dir.display(),
+                e
+            ))
+            .with_source(e)
+        })?;
+        let mut files: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "md"))
+            .collect();
+        files.sort();
+        Ok(files)
+    }
+
+    fn files_by_id(&self) -> AppResult<BTreeMap<String, PathBuf>> {
+        let mut files = BTreeMap::new();
+        for path in self.prompt_files()? {
+            let mut collection = PromptCollection {
+                prompts: vec![read_prompt(&path)?],
+            };
+            collection.ensure_ids();
+            if let Some(id) = collection.prompts.remove(0).id {
+                files.entry(id).or_insert(path);
+            }
+        }
+        Ok(files)
+    }
+
+    fn free_path(&self, description: &str, taken: &HashSet<PathBuf>) -> PathBuf {
+        let slug = slugify(description);
+        let mut path = self.dir.join(format!("{}.md", slug));
+        let mut counter = 1;
+        while taken.contains(&path) || path.exists() {
+            counter += 1;
+            path = self.dir.join(format!("{}-{}.md", slug, counter));
+        }
+        path
+    }
+}
+
+fn read_prompt(path: &Path) -> AppResult<Prompt> {
+    let text = fs::read_to_string(path).map_err(|e| {
+        AppError::io(format!(
+            "Failed to read prompt file: {}: {}",
+            path.display(),
+            e
+        ))
+        .with_source(e)
+    })?;
+    parse_prompt(path, &text)
+}
+
+/// Split a prompt file into its frontmatter and body
+fn parse_prompt(path: &Path, text: &str) -> AppResult<Prompt> {
+    let text = text
+        .strip_prefix('\u{feff}')
+        .unwrap_or(text)
+        .replace("\r\n", "\n");
+    let missing = || AppError::Parse {
+        path: path.to_path_buf(),
+        line: Some(1),
+        message: "Missing frontmatter: the file must start with '---' or '+++'".to_string(),
+        source: None,
+    };
+
+    let delimiter = match text.lines().next() {
+        Some("---") => "---",
+        Some("+++") => "+++",
+        _ => return Err(missing()),
+    };
+    let rest = text.get(delimiter.len() + 1..).unwrap_or_default();
+    let closing = format!("\n{}", delimiter);
+    let (header, body) = if let Some(body) = rest.strip_prefix(delimiter) {
+        ("", body)
+    } else {
+        let end = rest.find(&closing).ok_or_else(missing)?;
+        (&rest[..end + 1], &rest[end + closing.len()..])
+    };
+
+    let frontmatter: Frontmatter = if delimiter == "---" {
+        serde_yaml::from_str(header).map_err(|e| {
+            let line = e.location().map(|location| location.line() + 1);
+            AppError::parse(path, line, e.to_string(), e)
+        })?
+    } else {
+        toml::from_str(header).map_err(|e| {
+            let line = e
+                .span()
+                .map(|span| header[..span.start].matches('\n').count() + 2);
+            AppError::parse(path, line, e.message().to_string(), e)
+        })?
+    };
+
+    // The body starts after the delimiter line and one separating blank line
+    let body = body.strip_prefix('\n').unwrap_or(body);
+    let body = body.strip_prefix('\n').unwrap_or(body);
+    let content = body.strip_suffix('\n').unwrap_or(body);
+
+    let mut prompt = Prompt::new(frontmatter.description, content.to_string());
+    prompt.id = frontmatter.id;
+    // Same shape as a TOML round trip, which always yields a category and tags
+    prompt.category = Some(frontmatter.category.unwrap_or_default());
+    prompt.tag = Some(frontmatter.tags);
+    prompt.output = frontmatter.output;
+    prompt.created_at = frontmatter.created_at;
+    prompt.updated_at = frontmatter.updated_at.max(frontmatter.created_at);
+    Ok(prompt)
+}
+
+fn render_prompt(prompt: &Prompt) -> AppResult<String> {
+    let frontmatter = Frontmatter {
+        id: prompt.id.clone(),
+        description: prompt.description.clone(),
+        category: prompt.category.clone().filter(|c| !c.is_empty()),
+        tags: prompt.tag.clone().unwrap_or_default(),
+        output: prompt.output.clone(),
+        created_at: prompt.created_at,
+        updated_at: prompt.updated_at,
+    };
+    let header = serde_yaml::to_string(&frontmatter).map_err(|e| {
+        AppError::system(format!("Failed to serialize frontmatter: {}", e)).with_source(e)
+    })?;
+
+    Ok(format!("---\n{}---\n\n{}\n", header, prompt.content))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_keeps_content_and_file_names() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = MarkdownStore::new(dir.path());
+
+        let mut review = Prompt::new(
+            "Code review".to_string(),
+            "\nReview:\n\n```diff\n<diff>\n```\n".to_string(),
+        );
+        review.tag = Some(vec!["code".to_string()]);
+        review.category = Some("dev".to_string());
+        let mail = Prompt::new("Code review".to_string(), "Second".to_string());
+        store
+            .replace_all(&PromptCollection {
+                prompts: vec![review.clone(), mail.clone()],
+            })
+            .unwrap();
+
+        let path = dir.path().join("code-review.md");
+        assert!(dir.path().join("code-review-2.md").exists());
+        let loaded = store.load_collection().unwrap();
+        let found = loaded.find_by_id(review.id.as_ref().unwrap()).unwrap();
+        assert!(found.same_content(&review));
+        assert_eq!(found.created_at.timestamp(), review.created_at.timestamp());
+
+        // Renaming keeps the file, removing deletes it
+        let mut renamed = found.clone();
+        renamed.description = "Careful review".to_string();
+        store
+            .replace_all(&PromptCollection {
+                prompts: vec![renamed],
+            })
+            .unwrap();
+        assert!(path.exists());
+        assert!(!dir.path().join("code-review-2.md").exists());
+        assert_eq!(
+            store.load_collection().unwrap().prompts[0].description,
+            "Careful review"
+        );
+    }
+
+    #[test]
+    fn test_reads_toml_frontmatter() {
+        let text = "+++\ndescription = \"Summarize\"\ntags = [\"text\"]\ncreated_at = \"2024-01-01 10:00:00\"\n+++\n\nSummarize <text>\n";
+        let prompt = parse_prompt(Path::new("summarize.md"), text).unwrap();
+        assert_eq!(prompt.description, "Summarize");
+        assert_eq!(prompt.content, "Summarize <text>");
+        assert_eq!(prompt.tag, Some(vec!["text".to_string()]));
+        assert_eq!(prompt.updated_at, prompt.created_at);
+
+        let err = parse_prompt(Path::new("bad.md"), "no frontmatter").unwrap_err();
+        assert!(matches!(err, AppError::Parse { .. }));
+    }
+}
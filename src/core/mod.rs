@@ -4,7 +4,11 @@
 //! that form the heart of the Promptheus application.
 
 pub mod data;
+pub mod markdown;
 pub mod operations;
+pub mod revisions;
+pub mod sqlite;
 pub mod traits;
+pub mod trash;
 
 // Re-export for easier access
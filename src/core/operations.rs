@@ -4,20 +4,28 @@
 //! serving as the central hub for prompt management operations.
 
 use crate::cli::ListFormat;
-use crate::config::Config;
+use crate::config::{Config, StorageBackend};
 use crate::core::{
-    data::{Prompt, PromptCollection, PromptStats},
+    data::{MergeReport, Prompt, PromptCollection, PromptStats},
+    markdown::MarkdownStore,
+    revisions::RevisionStore,
+    sqlite::SqliteStore,
     traits::{PromptCrud, PromptDisplay, PromptInteraction, PromptSearch, PromptStorage},
+    trash::{TrashStore, UndoJournal, UndoRecord},
 };
+use crate::sync::merge::base_snapshot_path;
 use crate::utils::error::{AppError, AppResult};
 use crate::utils::{
-    console::{parse_command_variables, prompt_for_variables, replace_command_variables},
-    output::DisplayFormatter,
+    diff::{PromptChange, diff_collections},
+    file::{FileLock, write_atomic, write_new},
+    output::{DisplayFormatter, OutputStyle},
     search::{SearchEngine, interactive_search_with_external_tool},
     stats::StatsCalculator,
+    template::{self, Template},
+    variables::VariableValues,
 };
-use std::collections::hash_map::DefaultHasher;
-use std::hash::{Hash, Hasher};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 
 /// Main operations hub that implements all core traits
 ///
@@ -35,70 +43,430 @@ impl PromptOperations {
         }
     }
 
-    /// Generate deterministic ID based on description and created_at timestamp
-    fn generate_deterministic_id(
-        description: &str,
-        created_at: &chrono::DateTime<chrono::Utc>,
-    ) -> String {
-        let input = format!("{}{}", description, created_at.to_rfc3339());
-        let mut hasher = DefaultHasher::new();
-        input.hash(&mut hasher);
-        format!("{:x}", hasher.finish())
-    }
-
     /// Get reference to the configuration
     pub fn config(&self) -> &Config {
         &self.config
     }
 
-    fn render_prompt_content(&self, prompt: &Prompt) -> AppResult<String> {
-        let variables = parse_command_variables(&prompt.content);
-
-        if variables.is_empty() {
-            return Ok(prompt.content.clone());
+    /// Prompt content with its variables filled in
+    ///
+    /// Prompts in template mode are expanded, with includes looked up in
+    /// this store.
+    pub fn render_prompt_content(
+        &self,
+        prompt: &Prompt,
+        values: &VariableValues,
+    ) -> AppResult<String> {
+        if !template::is_template(&prompt.content) {
+            return values.render(&prompt.content);
         }
 
-        crate::utils::output::OutputStyle::print_variables_list(&variables);
-        let user_values = prompt_for_variables(variables)?;
-        Ok(replace_command_variables(&prompt.content, &user_values))
+        let template =
+            Template::for_prompt(prompt, &mut |identifier| self.find_prompt(identifier))?;
+        values.render_template(&template)
     }
 
-    /// Load prompts with proper error handling and deterministic ID generation
+    /// Load prompts, deriving IDs for prompts saved without one
     fn load_prompts_with_ids(&self) -> AppResult<PromptCollection> {
         self.ensure_storage_exists()?;
 
-        let collection = self.load_prompts()?;
+        let mut collection = self.load_prompts()?;
+        collection.ensure_ids();
+
+        Ok(collection)
+    }
 
-        // Ensure all prompts have deterministic IDs
-        let mut prompts = Vec::new();
-        for mut prompt in collection.prompts {
-            if prompt.id.is_none() {
-                prompt.id = Some(Self::generate_deterministic_id(
-                    &prompt.description,
-                    &prompt.created_at,
-                ));
+    /// Open the SQLite store when it is the configured backend
+    fn database(&self) -> AppResult<Option<SqliteStore>> {
+        match self.config.general.storage {
+            StorageBackend::Sqlite => {
+                SqliteStore::open(&self.config.general.database_file).map(Some)
             }
-            prompts.push(prompt);
+            StorageBackend::Toml | StorageBackend::Markdown => Ok(None),
+        }
+    }
+
+    /// The Markdown directory store when it is the configured backend
+    fn markdown(&self) -> Option<MarkdownStore> {
+        (self.config.general.storage == StorageBackend::Markdown)
+            .then(|| MarkdownStore::new(&self.config.general.markdown_dir))
+    }
+
+    /// File holding `prompt` when every prompt is stored in its own file
+    pub fn markdown_file(&self, prompt: &Prompt) -> AppResult<Option<PathBuf>> {
+        match (self.markdown(), &prompt.id) {
+            (Some(store), Some(id)) => store.path_of(id),
+            _ => Ok(None),
+        }
+    }
+
+    /// Whether prompts live in the SQLite database rather than a TOML file
+    pub fn uses_database(&self) -> bool {
+        self.config.general.storage == StorageBackend::Sqlite
+    }
+
+    /// Path of the file or directory backing the configured store
+    pub fn store_path(&self) -> &Path {
+        match self.config.general.storage {
+            StorageBackend::Sqlite => &self.config.general.database_file,
+            StorageBackend::Markdown => &self.config.general.markdown_dir,
+            StorageBackend::Toml => &self.config.general.prompt_file,
+        }
+    }
+
+    /// Check whether the store holds no prompts yet
+    pub fn is_store_empty(&self) -> AppResult<bool> {
+        if let Some(db) = self.database()? {
+            return db.is_empty();
+        }
+        if let Some(store) = self.markdown() {
+            return store.is_empty();
+        }
+
+        let prompt_file = &self.config.general.prompt_file;
+        Ok(!prompt_file.exists()
+            || std::fs::metadata(prompt_file)
+                .map_err(|e| AppError::io(e.to_string()).with_source(e))?
+                .len()
+                == 0)
+    }
+
+    /// Take the advisory lock guarding read-modify-write of the prompt store
+    ///
+    /// Hold the returned guard from loading the prompts until they are saved,
+    /// so concurrent invocations cannot overwrite each other's changes.
+    pub fn lock_store(&self) -> AppResult<FileLock> {
+        self.ensure_storage_exists()?;
+        FileLock::exclusive(self.store_path())
+    }
+
+    /// Store a prompt as is; the caller holds [`Self::lock_store`]
+    fn insert_prompt(&self, prompt: Prompt) -> AppResult<()> {
+        if let Some(mut db) = self.database()? {
+            return db.insert(&prompt);
         }
 
-        Ok(PromptCollection { prompts })
+        let current = self.load_prompts_with_ids()?;
+        let mut collection = current.clone();
+        collection.prompts.push(prompt);
+        self.save_changes(&current, &collection)
+    }
+
+    /// Revision history of the prompts in this store
+    pub fn revisions(&self) -> RevisionStore {
+        RevisionStore::for_store(self.store_path())
+    }
+
+    /// Trash holding deleted prompts until they are restored or purged
+    pub fn trash(&self) -> TrashStore {
+        TrashStore::for_store(self.store_path(), self.config.general.trash_retention_days)
+    }
+
+    /// Record of the last destructive operation, consumed by [`Self::undo`]
+    pub fn undo_journal(&self) -> UndoJournal {
+        UndoJournal::for_store(self.store_path())
+    }
+
+    /// Replace the stored prompts, keeping what is needed to undo it
+    ///
+    /// The caller holds [`Self::lock_store`].
+    pub fn replace_prompts(&self, operation: &str, collection: &PromptCollection) -> AppResult<()> {
+        let current = self.load_prompts_with_ids()?;
+        self.undo_journal()
+            .record(&UndoRecord::from_changes(operation, &current, collection))?;
+        self.save_changes(&current, collection)
+    }
+
+    /// Move a prompt from the trash back into the store
+    pub fn restore_from_trash(&self, identifier: &str) -> AppResult<Prompt> {
+        let _lock = self.lock_store()?;
+        let trash = self.trash();
+        let entry = trash.take(identifier)?;
+        let prompt = entry.prompt.clone();
+
+        let restored = match prompt.id.as_deref() {
+            Some(id) if self.find_prompt(id)?.is_some() => Err(AppError::Conflict(format!(
+                "A prompt with ID '{}' already exists",
+                id
+            ))),
+            _ => self.insert_prompt(prompt.clone()),
+        };
+        if restored.is_err() {
+            trash.put_back(vec![entry])?;
+        }
+        restored.map(|_| prompt)
+    }
+
+    /// Purge the trash, returning how many prompts it held
+    pub fn empty_trash(&self) -> AppResult<usize> {
+        let _lock = self.lock_store()?;
+        let mut record = UndoRecord::new("empty trash");
+        record.purged = self.trash().empty()?;
+        self.undo_journal().record(&record)?;
+        Ok(record.purged.len())
+    }
+
+    /// Reverse the last destructive operation, returning its record
+    pub fn undo(&self) -> AppResult<Option<UndoRecord>> {
+        let _lock = self.lock_store()?;
+        let journal = self.undo_journal();
+        let Some(record) = journal.last()? else {
+            return Ok(None);
+        };
+
+        if !record.previous.is_empty() || !record.created.is_empty() {
+            let current = self.load_prompts_with_ids()?;
+            let mut collection = current.clone();
+            collection
+                .prompts
+                .retain(|p| !p.id.as_ref().is_some_and(|id| record.created.contains(id)));
+            for previous in &record.previous {
+                match collection.prompts.iter_mut().find(|p| p.id == previous.id) {
+                    Some(current) => {
+                        // Keep packs writing back to the file the prompt came from
+                        let source = current.source.take();
+                        *current = previous.clone();
+                        current.source = source;
+                    }
+                    None => collection.prompts.push(previous.clone()),
+                }
+            }
+            self.save_changes(&current, &collection)?;
+        }
+
+        let trash = self.trash();
+        let restored_ids: Vec<String> = record
+            .previous
+            .iter()
+            .filter_map(|p| p.id.clone())
+            .collect();
+        trash.discard(&restored_ids)?;
+        trash.put_back(record.purged.clone())?;
+        journal.clear()?;
+        Ok(Some(record))
+    }
+
+    /// Keep the previous version of every prompt changed from `before` to `after`
+    fn record_revisions(
+        &self,
+        before: &PromptCollection,
+        after: &PromptCollection,
+    ) -> AppResult<()> {
+        let revisions = self.revisions();
+        for change in diff_collections(before, after) {
+            if let PromptChange::Modified { before, .. } = change {
+                revisions.record(&before)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Replace a stored prompt with an edited version, matched by ID
+    pub fn update_prompt(&self, prompt: &Prompt) -> AppResult<()> {
+        let _lock = self.lock_store()?;
+        if let Some(mut db) = self.database()? {
+            if let Some(previous) = prompt
+                .id
+                .as_deref()
+                .map(|id| db.find(id))
+                .transpose()?
+                .flatten()
+                && !previous.same_content(prompt)
+            {
+                self.revisions().record(&previous)?;
+            }
+            return db.update(prompt);
+        }
+
+        let current = self.load_prompts_with_ids()?;
+        let mut collection = current.clone();
+        let existing = collection
+            .prompts
+            .iter_mut()
+            .find(|p| p.id.is_some() && p.id == prompt.id)
+            .ok_or_else(|| {
+                AppError::NotFound(format!("Prompt '{}' not found", prompt.description))
+            })?;
+        *existing = prompt.clone();
+        self.save_changes(&current, &collection)
+    }
+
+    /// Save `after` in place of `before`, the prompts the caller loaded
+    /// under [`Self::lock_store`]
+    ///
+    /// Prompts changed from `before` keep their previous version as a revision.
+    fn save_changes(&self, before: &PromptCollection, after: &PromptCollection) -> AppResult<()> {
+        self.record_revisions(before, after)?;
+        self.save_prompts_internal(after)
     }
 
     /// Save prompts with error handling
+    ///
+    /// Prompts loaded from `prompt_dirs` go back to the file they came from,
+    /// all others to `prompt_file`. Files whose prompts did not change are
+    /// left untouched, so read-only prompt packs only fail when edited.
+    /// Prompts saved without an ID get it written along with the save.
     fn save_prompts_internal(&self, collection: &PromptCollection) -> AppResult<()> {
+        if let Some(mut db) = self.database()? {
+            return db.replace_all(collection);
+        }
+        if let Some(store) = self.markdown() {
+            return store.replace_all(collection);
+        }
+
+        let mut own = PromptCollection::new();
+        let mut packs: BTreeMap<PathBuf, PromptCollection> = self
+            .prompt_dir_files()?
+            .into_iter()
+            .map(|path| (path, PromptCollection::new()))
+            .collect();
+
+        for prompt in &collection.prompts {
+            match &prompt.source {
+                Some(source) => packs
+                    .entry(source.clone())
+                    .or_default()
+                    .prompts
+                    .push(prompt.clone()),
+                None => own.prompts.push(prompt.clone()),
+            }
+        }
+
+        own.ensure_ids();
+        self.write_prompt_file(&self.config.general.prompt_file, &own)?;
+        for (path, pack) in &mut packs {
+            pack.ensure_ids();
+            // Packs that fail to parse were skipped on load and are left as is
+            match read_prompt_file(path) {
+                Ok(current) if same_file_content(&current, pack) => {
+                    // Only IDs are missing; a read-only pack keeps working without them
+                    if current.has_missing_ids() {
+                        let _ = self.write_prompt_file(path, pack);
+                    }
+                    continue;
+                }
+                Err(AppError::Parse { .. }) => continue,
+                _ => {}
+            }
+            self.write_prompt_file(path, pack)?;
+        }
+
+        Ok(())
+    }
+
+    fn write_prompt_file(&self, path: &Path, collection: &PromptCollection) -> AppResult<()> {
         let content = toml::to_string_pretty(collection).map_err(|e| {
-            AppError::System(format!("Failed to serialize prompt collection: {}", e))
+            AppError::system(format!("Failed to serialize prompt collection: {}", e)).with_source(e)
         })?;
 
-        std::fs::write(&self.config.general.prompt_file, content).map_err(|e| {
-            AppError::Io(format!(
+        write_atomic(path, &content).map_err(|e| {
+            AppError::io(format!(
                 "Failed to write prompt file: {}: {}",
-                self.config.general.prompt_file.display(),
+                path.display(),
                 e
             ))
-        })?;
+            .with_source(e)
+        })
+    }
 
-        Ok(())
+    /// Every `*.toml` file in the configured `prompt_dirs`, in a stable order
+    ///
+    /// Missing directories are skipped so a shared folder that is not mounted
+    /// does not break the command. Hidden files, the config file and the
+    /// files promptheus keeps next to the store are never treated as prompt
+    /// packs.
+    fn prompt_dir_files(&self) -> AppResult<Vec<PathBuf>> {
+        let prompt_file = &self.config.general.prompt_file;
+        let own_files = [
+            canonical(prompt_file),
+            canonical(&base_snapshot_path(prompt_file)),
+            canonical(&Config::config_file_path()),
+            canonical(self.trash().path()),
+            canonical(self.undo_journal().path()),
+        ];
+        let mut files = Vec::new();
+
+        for dir in self
+            .config
+            .general
+            .prompt_dirs
+            .iter()
+            .filter(|d| d.is_dir())
+        {
+            let entries = std::fs::read_dir(dir).map_err(|e| {
+                AppError::io(format!(
+                    "Failed to read prompt directory: {}: {}",
+                    dir.display(),
+                    e
+                ))
+                .with_source(e)
+            })?;
+
+            for path in entries.filter_map(|entry| entry.ok().map(|e| e.path())) {
+                let hidden = path
+                    .file_name()
+                    .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+                let is_toml = path.extension().is_some_and(|ext| ext == "toml");
+                if path.is_file() && is_toml && !hidden && !own_files.contains(&canonical(&path)) {
+                    files.push(path);
+                }
+            }
+        }
+
+        files.sort();
+        files.dedup();
+        Ok(files)
+    }
+
+    /// Import a collection into the store
+    ///
+    /// With `merge` the imported prompts are merged into the existing store,
+    /// otherwise they replace it. Duplicates are resolved the same way in
+    /// both modes, so an import file never produces repeated prompts.
+    pub fn import_collection(
+        &self,
+        imported: PromptCollection,
+        merge: bool,
+    ) -> AppResult<MergeReport> {
+        let _lock = self.lock_store()?;
+        let mut collection = self.load_prompts_with_ids()?;
+        if !merge {
+            // Replacing only affects personal prompts, never the prompt packs
+            collection.prompts.retain(|prompt| prompt.source.is_some());
+        }
+
+        let report = collection.merge(imported);
+        self.replace_prompts("import", &collection)?;
+
+        Ok(report)
+    }
+
+    /// Stamp the update time of prompts changed since the given snapshot
+    ///
+    /// Editing the prompt file directly bypasses every mutation path, so the
+    /// edit command compares the file against its state before the edit.
+    /// The caller holds [`Self::lock_store`] for the whole edit.
+    pub fn stamp_modified_prompts(&self, before: &PromptCollection) -> AppResult<usize> {
+        let mut collection = self.load_prompts()?;
+        let mut stamped = 0;
+
+        for prompt in &mut collection.prompts {
+            let unchanged = before
+                .prompts
+                .iter()
+                .any(|old| old.created_at == prompt.created_at && old.same_content(prompt));
+            if !unchanged {
+                prompt.touch();
+                stamped += 1;
+            }
+        }
+
+        if stamped > 0 {
+            // The file already holds the edit, so it is saved over the state before it
+            self.save_changes(before, &collection)?;
+        }
+
+        Ok(stamped)
     }
 
     /// Format prompts for selection with display strings
@@ -108,7 +476,10 @@ impl PromptOperations {
         tag: Option<&str>,
         category: Option<&str>,
     ) -> AppResult<Vec<(Prompt, String)>> {
-        let collection = self.load_prompts_with_ids()?;
+        let collection = match self.database()? {
+            Some(db) => db.search_candidates(query, tag)?,
+            None => self.load_prompts_with_ids()?,
+        };
         Ok(SearchEngine::format_for_selection(
             &collection,
             query,
@@ -152,57 +523,122 @@ impl PromptOperations {
     }
 }
 
+/// Read a prompt file, treating an empty file as an empty collection
+fn read_prompt_file(path: &Path) -> AppResult<PromptCollection> {
+    let content = std::fs::read_to_string(path).map_err(|e| {
+        AppError::io(format!(
+            "Failed to read prompt file: {}: {}",
+            path.display(),
+            e
+        ))
+        .with_source(e)
+    })?;
+
+    if content.trim().is_empty() {
+        return Ok(PromptCollection::default());
+    }
+    toml::from_str(&content).map_err(|e| AppError::toml_parse(path, &content, e))
+}
+
+fn same_file_content(a: &PromptCollection, b: &PromptCollection) -> bool {
+    a.prompts.len() == b.prompts.len()
+        && a.prompts.iter().zip(&b.prompts).all(|(a, b)| {
+            a.same_content(b) && a.created_at == b.created_at && a.updated_at == b.updated_at
+        })
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
 // Implement PromptStorage trait
 impl PromptStorage for PromptOperations {
     fn load_prompts(&self) -> AppResult<PromptCollection> {
-        let content = std::fs::read_to_string(&self.config.general.prompt_file).map_err(|e| {
-            AppError::Io(format!(
+        if let Some(db) = self.database()? {
+            return db.load_collection();
+        }
+        if let Some(store) = self.markdown() {
+            return store.load_collection();
+        }
+
+        let prompt_file = &self.config.general.prompt_file;
+        let content = std::fs::read_to_string(prompt_file).map_err(|e| {
+            AppError::io(format!(
                 "Failed to read prompt file: {}: {}",
-                self.config.general.prompt_file.display(),
+                prompt_file.display(),
                 e
             ))
+            .with_source(e)
         })?;
 
         // Handle empty or invalid TOML files
-        if content.trim().is_empty() {
+        let mut collection = if content.trim().is_empty() {
             let default_collection = PromptCollection::default();
-            self.save_prompts(&default_collection)?;
-            return Ok(default_collection);
+            self.write_prompt_file(prompt_file, &default_collection)?;
+            default_collection
+        } else {
+            toml::from_str(&content).map_err(|e| AppError::toml_parse(prompt_file, &content, e))?
+        };
+        for path in self.prompt_dir_files()? {
+            // One broken pack in a shared folder should not stop every command
+            let mut pack = match read_prompt_file(&path) {
+                Ok(pack) => pack,
+                Err(e) => {
+                    eprintln!(
+                        "⚠️  {}",
+                        OutputStyle::warning(&format!("Skipping prompt pack: {}", e))
+                    );
+                    continue;
+                }
+            };
+            for prompt in &mut pack.prompts {
+                prompt.source = Some(path.clone());
+            }
+            collection.prompts.extend(pack.prompts);
         }
 
-        let collection: PromptCollection = toml::from_str(&content)
-            .map_err(|e| AppError::System(format!("Failed to parse prompt file: {}", e)))?;
-
         Ok(collection)
     }
 
     fn save_prompts(&self, collection: &PromptCollection) -> AppResult<()> {
-        self.save_prompts_internal(collection)
+        let current = self.load_prompts_with_ids()?;
+        self.save_changes(&current, collection)
     }
 
     fn ensure_storage_exists(&self) -> AppResult<()> {
+        if self.uses_database() {
+            // Opening the database creates it along with its schema
+            return self.database().map(|_| ());
+        }
+        if let Some(store) = self.markdown() {
+            return store.ensure_exists();
+        }
+
         if !self.config.general.prompt_file.exists() {
             if let Some(parent) = self.config.general.prompt_file.parent() {
                 std::fs::create_dir_all(parent).map_err(|e| {
-                    AppError::Io(format!(
+                    AppError::io(format!(
                         "Failed to create prompt directory: {}: {}",
                         parent.display(),
                         e
                     ))
+                    .with_source(e)
                 })?;
             }
 
             let default_collection = PromptCollection::default();
             let content = toml::to_string_pretty(&default_collection).map_err(|e| {
-                AppError::System(format!("Failed to create default prompt collection: {}", e))
+                AppError::system(format!("Failed to create default prompt collection: {}", e))
+                    .with_source(e)
             })?;
 
-            std::fs::write(&self.config.general.prompt_file, content).map_err(|e| {
-                AppError::Io(format!(
+            write_new(&self.config.general.prompt_file, &content).map_err(|e| {
+                AppError::io(format!(
                     "Failed to create prompt file: {}: {}",
                     self.config.general.prompt_file.display(),
                     e
                 ))
+                .with_source(e)
             })?;
         }
 
@@ -213,21 +649,39 @@ impl PromptStorage for PromptOperations {
 // Implement PromptSearch trait
 impl PromptSearch for PromptOperations {
     fn search_prompts(&self, query: Option<&str>, tag: Option<&str>) -> AppResult<Vec<Prompt>> {
+        if let Some(db) = self.database()? {
+            return Ok(db
+                .search_candidates(query, tag)?
+                .search(query, tag, &self.config));
+        }
+
         let collection = self.load_prompts_with_ids()?;
         Ok(collection.search(query, tag, &self.config))
     }
 
     fn find_prompt(&self, identifier: &str) -> AppResult<Option<Prompt>> {
+        if let Some(db) = self.database()? {
+            return db.find(identifier);
+        }
+
         let collection = self.load_prompts_with_ids()?;
         Ok(collection.find_prompt(identifier).cloned())
     }
 
     fn get_all_tags(&self) -> AppResult<Vec<String>> {
+        if let Some(db) = self.database()? {
+            return db.tags();
+        }
+
         let collection = self.load_prompts_with_ids()?;
         Ok(collection.get_all_tags())
     }
 
     fn get_categories(&self) -> AppResult<Vec<String>> {
+        if let Some(db) = self.database()? {
+            return db.categories();
+        }
+
         let collection = self.load_prompts_with_ids()?;
         Ok(collection.get_categories())
     }
@@ -241,7 +695,8 @@ impl PromptSearch for PromptOperations {
 // Implement PromptDisplay trait
 impl PromptDisplay for PromptOperations {
     fn format_list(&self, prompts: &[Prompt], format: &ListFormat) -> AppResult<()> {
-        DisplayFormatter::format_list(prompts, format, &self.config)
+        let id_len = self.load_prompts_with_ids()?.short_id_len();
+        DisplayFormatter::format_list(prompts, format, &self.config, id_len)
     }
 
     fn format_prompt_for_selection(&self, prompt: &Prompt) -> String {
@@ -264,10 +719,15 @@ impl PromptDisplay for PromptOperations {
 
 // Implement PromptInteraction trait
 impl PromptInteraction for PromptOperations {
-    fn execute_prompt(&self, prompt: &Prompt, copy_to_clipboard: bool) -> AppResult<()> {
+    fn execute_prompt(
+        &self,
+        prompt: &Prompt,
+        values: &VariableValues,
+        copy_to_clipboard: bool,
+    ) -> AppResult<()> {
         use crate::utils::copy_to_clipboard as copy_fn;
 
-        let rendered_content = self.render_prompt_content(prompt)?;
+        let rendered_content = self.render_prompt_content(prompt, values)?;
 
         if copy_to_clipboard {
             copy_fn(&rendered_content)?;
@@ -306,17 +766,199 @@ impl PromptInteraction for PromptOperations {
 
 // Implement PromptCrud trait
 impl PromptCrud for PromptOperations {
-    fn add_prompt(&self, prompt: Prompt) -> AppResult<()> {
-        let mut collection = self.load_prompts_with_ids()?;
+    fn add_prompt(&self, mut prompt: Prompt) -> AppResult<()> {
+        let _lock = self.lock_store()?;
+        if let Some(mut db) = self.database()? {
+            prompt.touch();
+            return db.insert(&prompt);
+        }
+
+        let current = self.load_prompts_with_ids()?;
+        let mut collection = current.clone();
         collection.add_prompt(prompt);
-        self.save_prompts(&collection)
+        self.save_changes(&current, &collection)
     }
 
+    /// Move a prompt to the trash
     fn delete_prompt(&self, id: &str) -> AppResult<()> {
-        let mut collection = self.load_prompts_with_ids()?;
-        collection
-            .delete_prompt(id)
-            .ok_or_else(|| AppError::System(format!("Prompt with ID '{}' not found", id)))?;
-        self.save_prompts(&collection)
+        let _lock = self.lock_store()?;
+        let deleted = if let Some(mut db) = self.database()? {
+            let prompt = db
+                .find(id)?
+                .ok_or_else(|| AppError::NotFound(format!("Prompt with ID '{}' not found", id)))?;
+            db.delete(prompt.id.as_deref().unwrap_or(id))?;
+            prompt
+        } else {
+            let current = self.load_prompts_with_ids()?;
+            let mut collection = current.clone();
+            let prompt = collection
+                .delete_prompt(id)
+                .ok_or_else(|| AppError::NotFound(format!("Prompt with ID '{}' not found", id)))?;
+            self.save_changes(&current, &collection)?;
+            prompt
+        };
+
+        self.trash().add(&deleted)?;
+        let mut record = UndoRecord::new(&format!("delete '{}'", deleted.description));
+        record.previous.push(deleted);
+        self.undo_journal().record(&record)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::data::MIN_ID_PREFIX;
+
+    #[test]
+    fn test_concurrent_adds_are_not_lost() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = Config::default();
+        config.general.prompt_file = dir.path().join("prompts.toml");
+
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                let config = config.clone();
+                std::thread::spawn(move || {
+                    PromptOperations::new(&config)
+                        .add_prompt(Prompt::new(format!("prompt {}", i), "content".to_string()))
+                        .unwrap();
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let collection = PromptOperations::new(&config).load_prompts().unwrap();
+        assert_eq!(collection.prompts.len(), 8);
+    }
+
+    #[test]
+    fn test_prompt_dirs_are_merged_and_written_back_to_their_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let packs = dir.path().join("packs");
+        std::fs::create_dir(&packs).unwrap();
+        let team_file = packs.join("team.toml");
+        let other_file = packs.join("other.toml");
+        let pack = |description: &str| {
+            format!(
+                "# hand-written pack\n[[prompts]]\nDescription = \"{}\"\nContent = \"x\"\nCreated_at = \"2024-01-01 10:00:00\"\n",
+                description
+            )
+        };
+        std::fs::write(&team_file, pack("Team review")).unwrap();
+        std::fs::write(&other_file, pack("Other")).unwrap();
+        std::fs::write(packs.join("notes.txt"), "ignored").unwrap();
+
+        let mut config = Config::default();
+        config.general.prompt_file = dir.path().join("prompts.toml");
+        config.general.prompt_dirs = vec![packs.clone()];
+        let storage = PromptOperations::new(&config);
+
+        storage
+            .add_prompt(Prompt::new("Mine".to_string(), "content".to_string()))
+            .unwrap();
+        let collection = storage.load_prompts_with_ids().unwrap();
+        assert_eq!(collection.prompts.len(), 3);
+        let team = collection.find_by_description("Team review").unwrap();
+        assert_eq!(team.source.as_deref(), Some(team_file.as_path()));
+
+        storage.delete_prompt(team.id.as_ref().unwrap()).unwrap();
+
+        let own = read_prompt_file(&config.general.prompt_file).unwrap();
+        assert_eq!(own.prompts.len(), 1);
+        assert_eq!(own.prompts[0].description, "Mine");
+        assert!(read_prompt_file(&team_file).unwrap().prompts.is_empty());
+        // Untouched packs keep their prompts and only gain the missing IDs
+        let other = read_prompt_file(&other_file).unwrap();
+        assert_eq!(other.prompts.len(), 1);
+        assert_eq!(other.prompts[0].description, "Other");
+        assert!(other.prompts[0].id.is_some());
+    }
+
+    #[test]
+    fn test_unparseable_prompt_packs_are_skipped_and_kept() {
+        let dir = tempfile::tempdir().unwrap();
+        let broken = dir.path().join("settings.toml");
+        std::fs::write(&broken, "[editor]\ncommand = \"vim\"\n").unwrap();
+
+        let mut config = Config::default();
+        config.general.prompt_file = dir.path().join("prompts.toml");
+        config.general.prompt_dirs = vec![dir.path().to_path_buf()];
+        let storage = PromptOperations::new(&config);
+
+        storage
+            .add_prompt(Prompt::new("Mine".to_string(), "content".to_string()))
+            .unwrap();
+        let collection = storage.load_prompts().unwrap();
+        assert_eq!(collection.prompts.len(), 1);
+        assert_eq!(
+            std::fs::read_to_string(&broken).unwrap(),
+            "[editor]\ncommand = \"vim\"\n"
+        );
+    }
+
+    #[test]
+    fn test_store_sidecars_are_not_loaded_as_prompt_packs() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = Config::default();
+        config.general.prompt_file = dir.path().join("prompts.toml");
+        config.general.prompt_dirs = vec![dir.path().to_path_buf()];
+        let storage = PromptOperations::new(&config);
+
+        for description in ["Keep", "Trashed"] {
+            storage
+                .add_prompt(Prompt::new(description.to_string(), "x".to_string()))
+                .unwrap();
+        }
+        let trashed = storage.find_prompt("Trashed").unwrap().unwrap();
+        storage.delete_prompt(trashed.id.as_ref().unwrap()).unwrap();
+        assert!(storage.trash().path().exists());
+        assert!(storage.undo_journal().path().exists());
+        assert!(storage.prompt_dir_files().unwrap().is_empty());
+
+        let collection = storage.load_prompts().unwrap();
+        assert_eq!(collection.prompts.len(), 1);
+        assert_eq!(collection.prompts[0].description, "Keep");
+        assert!(collection.prompts[0].source.is_none());
+    }
+
+    #[test]
+    fn test_ids_are_persisted_and_resolved_by_prefix() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = Config::default();
+        config.general.prompt_file = dir.path().join("prompts.toml");
+        std::fs::write(
+            &config.general.prompt_file,
+            "[[prompts]]\nDescription = \"Legacy\"\nContent = \"x\"\nCreated_at = \"2024-01-01 10:00:00\"\n",
+        )
+        .unwrap();
+        let storage = PromptOperations::new(&config);
+
+        // Reading an old file leaves it alone
+        let legacy = storage.find_prompt("Legacy").unwrap().unwrap();
+        let id = legacy.id.clone().unwrap();
+        assert!(uuid::Uuid::parse_str(&id).is_ok());
+        let stored = read_prompt_file(&config.general.prompt_file).unwrap();
+        assert!(stored.prompts[0].id.is_none());
+
+        // The next save writes the ID, and renaming keeps it
+        let mut renamed = legacy.clone();
+        renamed.description = "Renamed".to_string();
+        storage.update_prompt(&renamed).unwrap();
+        let stored = read_prompt_file(&config.general.prompt_file).unwrap();
+        assert_eq!(stored.prompts[0].id.as_deref(), Some(id.as_str()));
+        let reloaded = PromptOperations::new(&config);
+        let found = reloaded.find_prompt(&id).unwrap().unwrap();
+        assert_eq!(found.description, "Renamed");
+        let found = storage.find_prompt(&id[..MIN_ID_PREFIX]).unwrap().unwrap();
+        assert_eq!(found.description, "Renamed");
+        assert!(
+            storage
+                .find_prompt(&id[..MIN_ID_PREFIX - 1])
+                .unwrap()
+                .is_none()
+        );
     }
 }
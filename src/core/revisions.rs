@@ -0,0 +1,162 @@
+//! Per-prompt revision history
+//!
+//! Whenever a prompt's content or metadata changes, the version it replaces
+//! is appended to a history file named after the prompt's ID. The files live
+//! in a `prompt-history` directory next to the store, so every storage
+//! backend keeps its history the same way.
+
+use crate::core::data::Prompt;
+use crate::utils::error::{AppError, AppResult};
+use crate::utils::file::write_atomic;
+use crate::utils::format;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Number of revisions kept per prompt before the oldest are dropped
+const MAX_REVISIONS: usize = 100;
+
+/// A previous version of a prompt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Revision {
+    /// Revision number, counting up from 1 for each prompt
+    pub number: usize,
+    /// When this version was replaced
+    #[serde(with = "format")]
+    pub saved_at: DateTime<Utc>,
+    pub prompt: Prompt,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct HistoryFile {
+    #[serde(default)]
+    revisions: Vec<Revision>,
+}
+
+pub struct RevisionStore {
+    dir: PathBuf,
+}
+
+impl RevisionStore {
+    /// History kept next to the file or directory backing the store
+    pub fn for_store(store_path: &Path) -> Self {
+        Self {
+            dir: store_path
+                .parent()
+                .unwrap_or_else(|| Path::new("."))
+                .join("prompt-history"),
+        }
+    }
+
+    /// Record `previous` as the newest revision of its prompt
+    pub fn record(&self, previous: &Prompt) -> AppResult<()> {
+        let Some(id) = previous.id.as_deref() else {
+            return Ok(());
+        };
+
+        let mut history = self.read(id)?;
+        let number = history.revisions.last().map_or(0, |r| r.number) + 1;
+        let mut prompt = previous.clone();
+        prompt.source = None;
+        history.revisions.push(Revision {
+            number,
+            saved_at: Utc::now(),
+            prompt,
+        });
+        let excess = history.revisions.len().saturating_sub(MAX_REVISIONS);
+        history.revisions.drain(..excess);
+
+        self.write(id, &history)
+    }
+
+    /// Revisions of a prompt, oldest first
+    pub fn list(&self, id: &str) -> AppResult<Vec<Revision>> {
+        Ok(self.read(id)?.revisions)
+    }
+
+    /// A single revision of a prompt
+    pub fn get(&self, id: &str, number: usize) -> AppResult<Revision> {
+        self.list(id)?
+            .into_iter()
+            .find(|r| r.number == number)
+            .ok_or_else(|| AppError::NotFound(format!("Revision {} not found", number)))
+    }
+
+    fn path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.toml", format::slugify(id)))
+    }
+
+    fn read(&self, id: &str) -> AppResult<HistoryFile> {
+        let path = self.path(id);
+        if !path.exists() {
+            return Ok(HistoryFile::default());
+        }
+
+        let content = fs::read_to_string(&path).map_err(|e| {
+            AppError::io(format!(
+                "Failed to read prompt history: {}: {}",
+                path.display(),
+                e
+            ))
+            .with_source(e)
+        })?;
+        toml::from_str(&content).map_err(|e| AppError::toml_parse(&path, &content, e))
+    }
+
+    fn write(&self, id: &str, history: &HistoryFile) -> AppResult<()> {
+        fs::create_dir_all(&self.dir).map_err(|e| {
+            AppError::io(format!(
+                "Failed to create prompt history directory: {}: {}",
+                self.dir.display(),
+                e
+            ))
+            .with_source(e)
+        })?;
+
+        let path = self.path(id);
+        let content = toml::to_string_pretty(history).map_err(|e| {
+            AppError::system(format!("Failed to serialize prompt history: {}", e)).with_source(e)
+        })?;
+        write_atomic(&path, &content).map_err(|e| {
+            AppError::io(format!(
+                "Failed to write prompt history: {}: {}",
+                path.display(),
+                e
+            ))
+            .with_source(e)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_revisions_are_numbered_per_prompt() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = RevisionStore::for_store(&dir.path().join("prompts.toml"));
+
+        let mut review = Prompt::new("Review".to_string(), "v1".to_string());
+        review.category = Some("dev".to_string());
+        store.record(&review).unwrap();
+        review.content = "v2".to_string();
+        store.record(&review).unwrap();
+        store
+            .record(&Prompt::new("Other".to_string(), "x".to_string()))
+            .unwrap();
+
+        let id = review.id.as_deref().unwrap();
+        let revisions = store.list(id).unwrap();
+        assert_eq!(revisions.len(), 2);
+        assert_eq!(revisions[1].number, 2);
+        assert_eq!(store.get(id, 1).unwrap().prompt.content, "v1");
+        assert_eq!(
+            store.get(id, 1).unwrap().prompt.category.as_deref(),
+            Some("dev")
+        );
+        assert!(matches!(store.get(id, 3), Err(AppError::NotFound(_))));
+        assert!(store.list("missing").unwrap().is_empty());
+    }
+}
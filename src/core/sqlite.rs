@@ -0,0 +1,421 @@
+//! SQLite prompt store
+//!
+//! Keeps prompts in a database with indexed tags and categories and an FTS5
+//! index over descriptions, content and tags, so large collections can be
+//! queried without re-reading everything on each command.
+
+use crate::core::data::{MIN_ID_PREFIX, Prompt, PromptCollection};
+use crate::utils::error::{AppError, AppResult};
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, OptionalExtension, Row, Transaction, params};
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
+
+const SCHEMA: &str = r#"
+CREATE TABLE IF NOT EXISTS prompts (
+    id TEXT PRIMARY KEY,
+    description TEXT NOT NULL,
+    content TEXT NOT NULL,
+    category TEXT,
+    output TEXT,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_prompts_description ON prompts(description);
+CREATE INDEX IF NOT EXISTS idx_prompts_category ON prompts(category);
+
+CREATE TABLE IF NOT EXISTS prompt_tags (
+    prompt_id TEXT NOT NULL REFERENCES prompts(id) ON DELETE CASCADE,
+    position INTEGER NOT NULL,
+    tag TEXT NOT NULL,
+    PRIMARY KEY (prompt_id, position)
+);
+CREATE INDEX IF NOT EXISTS idx_prompt_tags_tag ON prompt_tags(tag);
+
+CREATE VIRTUAL TABLE IF NOT EXISTS prompts_fts USING fts5(
+    prompt_id UNINDEXED,
+    description,
+    content,
+    tags,
+    tokenize = 'trigram'
+);
+"#;
+
+const SELECT_PROMPTS: &str =
+    "SELECT id, description, content, category, output, created_at, updated_at FROM prompts";
+
+/// Shortest query the trigram index can answer; shorter ones scan the table
+const MIN_FTS_QUERY_LEN: usize = 3;
+
+pub struct SqliteStore {
+    conn: Connection,
+}
+
+impl SqliteStore {
+    /// Open the database, creating it and its schema if needed
+    pub fn open(path: &Path) -> AppResult<Self> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent).map_err(|e| {
+                AppError::io(format!(
+                    "Failed to create database directory: {}: {}",
+                    parent.display(),
+                    e
+                ))
+                .with_source(e)
+            })?;
+        }
+
+        let conn = Connection::open(path).map_err(|e| {
+            AppError::io(format!(
+                "Failed to open database: {}: {}",
+                path.display(),
+                e
+            ))
+            .with_source(e)
+        })?;
+        conn.busy_timeout(Duration::from_secs(10))
+            .map_err(db_error)?;
+        conn.execute_batch("PRAGMA foreign_keys = ON; PRAGMA journal_mode = WAL;")
+            .map_err(db_error)?;
+        conn.execute_batch(SCHEMA).map_err(db_error)?;
+
+        Ok(Self { conn })
+    }
+
+    /// Load every prompt in insertion order
+    pub fn load_collection(&self) -> AppResult<PromptCollection> {
+        self.query_prompts(&format!("{} ORDER BY rowid", SELECT_PROMPTS), [])
+    }
+
+    /// Replace the whole store with `collection`
+    pub fn replace_all(&mut self, collection: &PromptCollection) -> AppResult<()> {
+        let tx = self.conn.transaction().map_err(db_error)?;
+        tx.execute_batch("DELETE FROM prompts_fts; DELETE FROM prompts;")
+            .map_err(db_error)?;
+        for prompt in &collection.prompts {
+            insert_prompt(&tx, prompt)?;
+        }
+        tx.commit().map_err(db_error)
+    }
+
+    pub fn insert(&mut self, prompt: &Prompt) -> AppResult<()> {
+        let tx = self.conn.transaction().map_err(db_error)?;
+        insert_prompt(&tx, prompt)?;
+        tx.commit().map_err(db_error)
+    }
+
+    /// Replace the stored prompt with the same ID
+    pub fn update(&mut self, prompt: &Prompt) -> AppResult<()> {
+        let tx = self.conn.transaction().map_err(db_error)?;
+        let id = prompt_id(prompt);
+        if !remove_prompt(&tx, &id)? {
+            return Err(AppError::NotFound(format!(
+                "Prompt with ID '{}' not found",
+                id
+            )));
+        }
+        insert_prompt(&tx, prompt)?;
+        tx.commit().map_err(db_error)
+    }
+
+    /// Delete a prompt by ID, returning whether it existed
+    pub fn delete(&mut self, id: &str) -> AppResult<bool> {
+        let tx = self.conn.transaction().map_err(db_error)?;
+        let removed = remove_prompt(&tx, id)?;
+        tx.commit().map_err(db_error)?;
+        Ok(removed)
+    }
+
+    /// Find a prompt by ID, exact description or unique ID prefix
+    pub fn find(&self, identifier: &str) -> AppResult<Option<Prompt>> {
+        let by_id = self.query_prompts(
+            &format!("{} WHERE id = ?1", SELECT_PROMPTS),
+            params![identifier],
+        )?;
+        if let Some(prompt) = by_id.prompts.into_iter().next() {
+            return Ok(Some(prompt));
+        }
+
+        let by_description = self.query_prompts(
+            &format!(
+                "{} WHERE description = ?1 ORDER BY rowid LIMIT 1",
+                SELECT_PROMPTS
+            ),
+            params![identifier],
+        )?;
+        if let Some(prompt) = by_description.prompts.into_iter().next() {
+            return Ok(Some(prompt));
+        }
+
+        if identifier.len() < MIN_ID_PREFIX {
+            return Ok(None);
+        }
+        let by_prefix = self.query_prompts(
+            &format!(
+                "{} WHERE substr(id, 1, length(?1)) = ?1 LIMIT 2",
+                SELECT_PROMPTS
+            ),
+            params![identifier],
+        )?;
+        let mut matches = by_prefix.prompts;
+        Ok(matches.pop().filter(|_| matches.is_empty()))
+    }
+
+    /// Prompts that may match `query` and `tag`
+    ///
+    /// The indexes narrow the candidates down; the caller still applies the
+    /// exact search rules (case sensitivity, sort order) on the result.
+    pub fn search_candidates(
+        &self,
+        query: Option<&str>,
+        tag: Option<&str>,
+    ) -> AppResult<PromptCollection> {
+        let mut conditions = Vec::new();
+        let mut values = Vec::new();
+
+        if let Some(query) = query.filter(|q| q.chars().count() >= MIN_FTS_QUERY_LEN) {
+            values.push(format!("\"{}\"", query.replace('"', "\"\"")));
+            conditions.push(format!(
+                "id IN (SELECT prompt_id FROM prompts_fts WHERE prompts_fts MATCH ?{})",
+                values.len()
+            ));
+        }
+        if let Some(tag) = tag {
+            values.push(tag.to_string());
+            conditions.push(format!(
+                "id IN (SELECT prompt_id FROM prompt_tags WHERE tag = ?{})",
+                values.len()
+            ));
+        }
+
+        let mut sql = SELECT_PROMPTS.to_string();
+        if !conditions.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&conditions.join(" AND "));
+        }
+        sql.push_str(" ORDER BY rowid");
+
+        self.query_prompts(&sql, rusqlite::params_from_iter(values))
+    }
+
+    pub fn tags(&self) -> AppResult<Vec<String>> {
+        self.query_strings("SELECT DISTINCT tag FROM prompt_tags ORDER BY tag")
+    }
+
+    pub fn categories(&self) -> AppResult<Vec<String>> {
+        self.query_strings(
+            "SELECT DISTINCT category FROM prompts WHERE category IS NOT NULL AND category != '' ORDER BY category",
+        )
+    }
+
+    pub fn is_empty(&self) -> AppResult<bool> {
+        let count: i64 = self
+            .conn
+            .query_row("SELECT COUNT(*) FROM prompts", [], |row| row.get(0))
+            .map_err(db_error)?;
+        Ok(count == 0)
+    }
+
+    fn query_strings(&self, sql: &str) -> AppResult<Vec<String>> {
+        let mut stmt = self.conn.prepare(sql).map_err(db_error)?;
+        stmt.query_map([], |row| row.get(0))
+            .and_then(|rows| rows.collect())
+            .map_err(db_error)
+    }
+
+    fn query_prompts(
+        &self,
+        sql: &str,
+        params: impl rusqlite::Params,
+    ) -> AppResult<PromptCollection> {
+        let mut stmt = self.conn.prepare(sql).map_err(db_error)?;
+        let mut prompts: Vec<Prompt> = stmt
+            .query_map(params, prompt_from_row)
+            .and_then(|rows| rows.collect())
+            .map_err(db_error)?;
+
+        let mut tags = self.tags_by_prompt()?;
+        for prompt in &mut prompts {
+            // Same shape as a TOML round trip, which always yields a tag list
+            prompt.tag = Some(
+                prompt
+                    .id
+                    .as_ref()
+                    .and_then(|id| tags.remove(id))
+                    .unwrap_or_default(),
+            );
+        }
+
+        Ok(PromptCollection { prompts })
+    }
+
+    fn tags_by_prompt(&self) -> AppResult<HashMap<String, Vec<String>>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT prompt_id, tag FROM prompt_tags ORDER BY prompt_id, position")
+            .map_err(db_error)?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get(1)?)))
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(db_error)?;
+
+        let mut tags: HashMap<String, Vec<String>> = HashMap::new();
+        for (id, tag) in rows {
+            tags.entry(id).or_default().push(tag);
+        }
+        Ok(tags)
+    }
+}
+
+fn insert_prompt(tx: &Transaction, prompt: &Prompt) -> AppResult<()> {
+    let id = prompt_id(prompt);
+    tx.execute(
+        "INSERT INTO prompts (id, description, content, category, output, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            id,
+            prompt.description,
+            prompt.content,
+            prompt.category,
+            prompt.output,
+            prompt.created_at.to_rfc3339(),
+            prompt.updated_at.to_rfc3339(),
+        ],
+    )
+    .map_err(db_error)?;
+
+    let tags = prompt.tag.clone().unwrap_or_default();
+    for (position, tag) in tags.iter().enumerate() {
+        tx.execute(
+            "INSERT INTO prompt_tags (prompt_id, position, tag) VALUES (?1, ?2, ?3)",
+            params![id, position as i64, tag],
+        )
+        .map_err(db_error)?;
+    }
+
+    tx.execute(
+        "INSERT INTO prompts_fts (prompt_id, description, content, tags) VALUES (?1, ?2, ?3, ?4)",
+        params![id, prompt.description, prompt.content, tags.join(" ")],
+    )
+    .map_err(db_error)?;
+
+    Ok(())
+}
+
+fn remove_prompt(tx: &Transaction, id: &str) -> AppResult<bool> {
+    let exists = tx
+        .query_row("SELECT 1 FROM prompts WHERE id = ?1", params![id], |_| {
+            Ok(())
+        })
+        .optional()
+        .map_err(db_error)?
+        .is_some();
+
+    tx.execute("DELETE FROM prompts_fts WHERE prompt_id = ?1", params![id])
+        .map_err(db_error)?;
+    tx.execute("DELETE FROM prompts WHERE id = ?1", params![id])
+        .map_err(db_error)?;
+    Ok(exists)
+}
+
+/// Prompts without an ID get a new one, the same one the TOML store would give
+fn prompt_id(prompt: &Prompt) -> String {
+    match &prompt.id {
+        Some(id) => id.clone(),
+        None => {
+            let mut collection = PromptCollection {
+                prompts: vec![prompt.clone()],
+            };
+            collection.ensure_ids();
+            collection.prompts[0].id.clone().unwrap_or_default()
+        }
+    }
+}
+
+fn prompt_from_row(row: &Row) -> rusqlite::Result<Prompt> {
+    Ok(Prompt {
+        id: Some(row.get(0)?),
+        description: row.get(1)?,
+        content: row.get(2)?,
+        category: row.get(3)?,
+        tag: None,
+        output: row.get(4)?,
+        created_at: parse_timestamp(row, 5)?,
+        updated_at: parse_timestamp(row, 6)?,
+        source: None,
+    })
+}
+
+fn parse_timestamp(row: &Row, index: usize) -> rusqlite::Result<DateTime<Utc>> {
+    let value: String = row.get(index)?;
+    DateTime::parse_from_rfc3339(&value)
+        .map(|timestamp| timestamp.with_timezone(&Utc))
+        .map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(
+                index,
+                rusqlite::types::Type::Text,
+                Box::new(e),
+            )
+        })
+}
+
+fn db_error(e: rusqlite::Error) -> AppError {
+    AppError::system(format!("Database error: {}", e)).with_source(e)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prompt(description: &str, content: &str, tags: &[&str], category: Option<&str>) -> Prompt {
+        let mut prompt = Prompt::new(description.to_string(), content.to_string());
+        prompt.tag = Some(tags.iter().map(|t| t.to_string()).collect());
+        prompt.category = category.map(String::from);
+        prompt
+    }
+
+    #[test]
+    fn test_round_trip_and_indexed_queries() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = SqliteStore::open(&dir.path().join("prompts.db")).unwrap();
+        let review = prompt(
+            "Code review",
+            "Review this diff",
+            &["code", "git"],
+            Some("dev"),
+        );
+        let mail = prompt("Polite mail", "Rewrite politely", &["writing"], None);
+        store
+            .replace_all(&PromptCollection {
+                prompts: vec![review.clone(), mail.clone()],
+            })
+            .unwrap();
+
+        let loaded = store.load_collection().unwrap();
+        assert_eq!(loaded.prompts.len(), 2);
+        assert_eq!(loaded.prompts[0].id, review.id);
+        assert_eq!(loaded.prompts[0].tag, review.tag);
+        assert_eq!(loaded.prompts[0].created_at, review.created_at);
+
+        assert_eq!(store.tags().unwrap(), vec!["code", "git", "writing"]);
+        assert_eq!(store.categories().unwrap(), vec!["dev"]);
+
+        let hits = store.search_candidates(Some("DIFF"), None).unwrap();
+        assert_eq!(hits.prompts.len(), 1);
+        assert_eq!(hits.prompts[0].description, "Code review");
+        let hits = store.search_candidates(None, Some("writing")).unwrap();
+        assert_eq!(hits.prompts[0].description, "Polite mail");
+
+        assert!(store.delete(review.id.as_ref().unwrap()).unwrap());
+        assert!(store.find("Code review").unwrap().is_none());
+        assert!(
+            store
+                .search_candidates(Some("diff"), None)
+                .unwrap()
+                .prompts
+                .is_empty()
+        );
+        assert_eq!(store.tags().unwrap(), vec!["writing"]);
+    }
+}
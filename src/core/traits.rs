@@ -6,6 +6,7 @@
 use crate::cli::ListFormat;
 use crate::core::data::{Prompt, PromptCollection, PromptStats};
 use crate::utils::error::AppResult;
+use crate::utils::variables::VariableValues;
 
 /// Storage operations for prompts
 ///
@@ -70,7 +71,12 @@ pub trait PromptDisplay {
 /// like selecting prompts from lists and getting user input.
 pub trait PromptInteraction {
     /// Execute prompt with variable substitution
-    fn execute_prompt(&self, prompt: &Prompt, copy_to_clipboard: bool) -> AppResult<()>;
+    fn execute_prompt(
+        &self,
+        prompt: &Prompt,
+        values: &VariableValues,
+        copy_to_clipboard: bool,
+    ) -> AppResult<()>;
 
     /// Select prompts interactively using standard formatting
     fn select_interactive_prompts(&self, prompts: Vec<Prompt>) -> AppResult<Option<Prompt>>;
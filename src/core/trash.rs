@@ -0,0 +1,346 @@
+//! Trash bin and undo journal
+//!
+//! Deleted prompts are moved to a trash file next to the store instead of
+//! being dropped, and are purged once they are older than the configured
+//! retention period. Destructive operations also leave an undo record with
+//! everything needed to reverse the most recent one.
+
+use crate::core::data::{MIN_ID_PREFIX, Prompt, PromptCollection};
+use crate::utils::diff::{PromptChange, diff_collections};
+use crate::utils::error::{AppError, AppResult};
+use crate::utils::file::write_atomic;
+use crate::utils::format;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A deleted prompt waiting in the trash
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashEntry {
+    #[serde(with = "format")]
+    pub deleted_at: DateTime<Utc>,
+    pub prompt: Prompt,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct TrashFile {
+    #[serde(default)]
+    entries: Vec<TrashEntry>,
+}
+
+pub struct TrashStore {
+    path: PathBuf,
+    retention_days: u32,
+}
+
+impl TrashStore {
+    /// Trash kept next to the file or directory backing the store
+    ///
+    /// Entries older than `retention_days` are purged whenever the trash is
+    /// read; 0 keeps them until the trash is emptied.
+    pub fn for_store(store_path: &Path, retention_days: u32) -> Self {
+        Self {
+            path: sidecar_path(store_path, "prompt-trash.toml"),
+            retention_days,
+        }
+    }
+
+    /// File holding the trashed prompts
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Trashed prompts, most recently deleted first
+    pub fn list(&self) -> AppResult<Vec<TrashEntry>> {
+        let mut entries = self.load()?;
+        entries.sort_by_key(|e| std::cmp::Reverse(e.deleted_at));
+        Ok(entries)
+    }
+
+    pub fn add(&self, prompt: &Prompt) -> AppResult<()> {
+        let mut entries = self.load()?;
+        let mut prompt = prompt.clone();
+        prompt.source = None;
+        entries.push(TrashEntry {
+            deleted_at: Utc::now(),
+            prompt,
+        });
+        self.save(&entries)
+    }
+
+    /// Remove and return the entry matching an ID, unique ID prefix or description
+    pub fn take(&self, identifier: &str) -> AppResult<TrashEntry> {
+        let mut entries = self.load()?;
+        let position = find_entry(&entries, identifier).ok_or_else(|| {
+            AppError::NotFound(format!("Prompt '{}' not found in trash", identifier))
+        })?;
+        let entry = entries.remove(position);
+        self.save(&entries)?;
+        Ok(entry)
+    }
+
+    /// Drop every entry, returning what was removed
+    pub fn empty(&self) -> AppResult<Vec<TrashEntry>> {
+        let entries = self.load()?;
+        self.save(&[])?;
+        Ok(entries)
+    }
+
+    /// Put entries removed by [`Self::empty`] back
+    pub fn put_back(&self, restored: Vec<TrashEntry>) -> AppResult<()> {
+        if restored.is_empty() {
+            return Ok(());
+        }
+        let mut entries = self.load()?;
+        entries.extend(restored);
+        self.save(&entries)
+    }
+
+    /// Drop the entries of prompts that are back in the store
+    pub fn discard(&self, ids: &[String]) -> AppResult<()> {
+        let mut entries = self.load()?;
+        let before = entries.len();
+        entries.retain(|e| !e.prompt.id.as_ref().is_some_and(|id| ids.contains(id)));
+        if entries.len() != before {
+            self.save(&entries)?;
+        }
+        Ok(())
+    }
+
+    /// Read the trash, purging entries past the retention period
+    fn load(&self) -> AppResult<Vec<TrashEntry>> {
+        let mut entries = read_toml::<TrashFile>(&self.path, "trash")?
+            .unwrap_or_default()
+            .entries;
+
+        if self.retention_days > 0 {
+            let cutoff = Utc::now() - Duration::days(i64::from(self.retention_days));
+            let before = entries.len();
+            entries.retain(|e| e.deleted_at >= cutoff);
+            if entries.len() != before {
+                self.save(&entries)?;
+            }
+        }
+        Ok(entries)
+    }
+
+    fn save(&self, entries: &[TrashEntry]) -> AppResult<()> {
+        let file = TrashFile {
+            entries: entries.to_vec(),
+        };
+        write_toml(&self.path, &file, "trash")
+    }
+}
+
+fn find_entry(entries: &[TrashEntry], identifier: &str) -> Option<usize> {
+    let by_id = |id: &str| {
+        entries
+            .iter()
+            .position(|e| e.prompt.id.as_deref() == Some(id))
+    };
+    let by_description = || {
+        entries
+            .iter()
+            .position(|e| e.prompt.description == identifier)
+    };
+    let by_prefix = || {
+        if identifier.len() < MIN_ID_PREFIX {
+            return None;
+        }
+        let mut matches = entries.iter().enumerate().filter(|(_, e)| {
+            e.prompt
+                .id
+                .as_deref()
+                .is_some_and(|id| id.starts_with(identifier))
+        });
+        let (position, _) = matches.next()?;
+        matches.next().is_none().then_some(position)
+    };
+
+    by_id(identifier).or_else(by_description).or_else(by_prefix)
+}
+
+/// Everything needed to reverse the most recent destructive operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UndoRecord {
+    /// What was done, e.g. "delete 'Review'"
+    pub operation: String,
+    #[serde(with = "format")]
+    pub recorded_at: DateTime<Utc>,
+    /// Prompts as they were before the operation, put back on undo
+    #[serde(default)]
+    pub previous: Vec<Prompt>,
+    /// IDs of prompts the operation created, removed on undo
+    #[serde(default)]
+    pub created: Vec<String>,
+    /// Trash entries the operation purged, put back on undo
+    #[serde(default)]
+    pub purged: Vec<TrashEntry>,
+}
+
+impl UndoRecord {
+    pub fn new(operation: &str) -> Self {
+        Self {
+            operation: operation.to_string(),
+            recorded_at: Utc::now(),
+            previous: Vec::new(),
+            created: Vec::new(),
+            purged: Vec::new(),
+        }
+    }
+
+    /// Record for an operation that turned `before` into `after`
+    pub fn from_changes(
+        operation: &str,
+        before: &PromptCollection,
+        after: &PromptCollection,
+    ) -> Self {
+        let mut record = Self::new(operation);
+        for change in diff_collections(before, after) {
+            match change {
+                PromptChange::Added(prompt) => record.created.extend(prompt.id),
+                PromptChange::Removed(prompt) => record.previous.push(prompt),
+                PromptChange::Modified { before, .. } => record.previous.push(*before),
+            }
+        }
+        record
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.previous.is_empty() && self.created.is_empty() && self.purged.is_empty()
+    }
+}
+
+/// The single undo record kept next to the store
+pub struct UndoJournal {
+    path: PathBuf,
+}
+
+impl UndoJournal {
+    pub fn for_store(store_path: &Path) -> Self {
+        Self {
+            path: sidecar_path(store_path, "prompt-undo.toml"),
+        }
+    }
+
+    /// File holding the undo record
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Replace the previous record; operations that changed nothing keep it
+    pub fn record(&self, record: &UndoRecord) -> AppResult<()> {
+        if record.is_empty() {
+            return Ok(());
+        }
+        write_toml(&self.path, record, "undo record")
+    }
+
+    pub fn last(&self) -> AppResult<Option<UndoRecord>> {
+        read_toml(&self.path, "undo record")
+    }
+
+    pub fn clear(&self) -> AppResult<()> {
+        match fs::remove_file(&self.path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(AppError::io(format!(
+                "Failed to remove undo record: {}: {}",
+                self.path.display(),
+                e
+            ))
+            .with_source(e)),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn sidecar_path(store_path: &Path, name: &str) -> PathBuf {
+    store_path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join(name)
+}
+
+fn read_toml<T: serde::de::DeserializeOwned>(path: &Path, what: &str) -> AppResult<Option<T>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(path).map_err(|e| {
+        AppError::io(format!(
+            "Failed to read {}: {}: {}",
+            what,
+            path.display(),
+            e
+        ))
+        .with_source(e)
+    })?;
+    toml::from_str(&content)
+        .map(Some)
+        .map_err(|e| AppError::toml_parse(path, &content, e))
+}
+
+fn write_toml<T: Serialize>(path: &Path, value: &T, what: &str) -> AppResult<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| {
+            AppError::io(format!(
+                "Failed to create directory: {}: {}",
+                parent.display(),
+                e
+            ))
+            .with_source(e)
+        })?;
+    }
+    let content = toml::to_string_pretty(value).map_err(|e| {
+        AppError::system(format!("Failed to serialize {}: {}", what, e)).with_source(e)
+    })?;
+    write_atomic(path, &content).map_err(|e| {
+        AppError::io(format!(
+            "Failed to write {}: {}: {}",
+            what,
+            path.display(),
+            e
+        ))
+        .with_source(e)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trash_purges_entries_past_retention() {
+        let dir = tempfile::tempdir().unwrap();
+        let store_path = dir.path().join("prompts.toml");
+        let trash = TrashStore::for_store(&store_path, 30);
+
+        let old = Prompt::new("Old".to_string(), "x".to_string());
+        let recent = Prompt::new("Recent".to_string(), "y".to_string());
+        trash.add(&old).unwrap();
+        trash.add(&recent).unwrap();
+
+        // Backdate the first entry past the retention period
+        let mut entries = trash.load().unwrap();
+        entries[0].deleted_at = Utc::now() - Duration::days(31);
+        trash.save(&entries).unwrap();
+
+        assert_eq!(
+            TrashStore::for_store(&store_path, 0).list().unwrap().len(),
+            2
+        );
+        let kept = trash.list().unwrap();
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].prompt.description, "Recent");
+        assert_eq!(
+            TrashStore::for_store(&store_path, 0).list().unwrap().len(),
+            1
+        );
+
+        let id = recent.id.as_deref().unwrap();
+        assert_eq!(
+            trash.take(&id[..MIN_ID_PREFIX]).unwrap().prompt.content,
+            "y"
+        );
+        assert!(matches!(trash.take(id), Err(AppError::NotFound(_))));
+    }
+}
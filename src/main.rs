@@ -7,13 +7,23 @@ mod sync;
 mod utils;
 
 use clap::Parser;
-use cli::Cli;
+use cli::{Cli, ErrorFormat};
 use config::Config;
-use utils::error::report_error;
+use utils::error::{AppError, report_error};
+
+/// Report the error in the requested format and exit with its code
+fn exit_with_error(error: &AppError, format: ErrorFormat) -> ! {
+    match format {
+        ErrorFormat::Text => report_error(error),
+        ErrorFormat::Json => eprintln!("{}", error.to_json()),
+    }
+    std::process::exit(error.exit_code());
+}
 
 #[tokio::main]
 async fn main() {
     let cli = Cli::parse();
+    let error_format = cli.error_format;
 
     // Ensure configuration exists and load it
     if let Err(e) = if cli.config.is_none() {
@@ -21,8 +31,7 @@ async fn main() {
     } else {
         Ok(())
     } {
-        report_error(&e);
-        std::process::exit(1);
+        exit_with_error(&e, error_format);
     }
 
     let config = match if let Some(config_path) = &cli.config {
@@ -31,15 +40,11 @@ async fn main() {
         Config::load()
     } {
         Ok(config) => config,
-        Err(e) => {
-            report_error(&e);
-            std::process::exit(1);
-        }
+        Err(e) => exit_with_error(&e, error_format),
     };
 
     // Execute command
     if let Err(e) = cli.command.execute(config).await {
-        report_error(&e);
-        std::process::exit(1);
+        exit_with_error(&e, error_format);
     }
 }
@@ -2,7 +2,7 @@
 // Consolidated from configure.rs
 
 use crate::cli::ConfigCommands;
-use crate::config::Config;
+use crate::config::{Config, StorageBackend};
 use crate::utils;
 use crate::utils::error::AppResult;
 
@@ -20,7 +20,24 @@ fn handle_show_command(config: &Config) -> AppResult<()> {
     println!("==========================");
 
     println!("General:");
+    println!("  Storage: {:?}", config.general.storage);
     println!("  Prompt file: {}", config.general.prompt_file.display());
+    if config.general.storage == StorageBackend::Sqlite {
+        println!(
+            "  Database file: {}",
+            config.general.database_file.display()
+        );
+    }
+    if config.general.storage == StorageBackend::Markdown {
+        println!("  Markdown dir: {}", config.general.markdown_dir.display());
+    }
+    println!(
+        "  Trash retention: {}",
+        match config.general.trash_retention_days {
+            0 => "forever".to_string(),
+            days => format!("{} days", days),
+        }
+    );
     if !config.general.prompt_dirs.is_empty() {
         println!(
             "  Prompt dirs: {}",
@@ -39,6 +56,7 @@ fn handle_show_command(config: &Config) -> AppResult<()> {
         println!("  Default tags: {}", config.general.default_tags.join(", "));
     }
     println!("  Auto sync: {}", config.general.auto_sync);
+    println!("  Command variables: {}", config.general.allow_command_vars);
     println!("  Sort by: {:?}", config.general.sort_by);
     println!("  Color: {}", config.general.color);
     println!("  Content preview: {}", config.general.content_preview);
@@ -61,6 +79,7 @@ fn handle_show_command(config: &Config) -> AppResult<()> {
         }
         println!("  Public: {}", gist.public);
         println!("  Auto sync: {}", gist.auto_sync);
+        println!("  Layout: {:?}", gist.layout);
     }
 
     if let Some(gitlab) = &config.gitlab {
@@ -73,11 +92,54 @@ fn handle_show_command(config: &Config) -> AppResult<()> {
         if let Some(id) = gitlab.id {
             println!("  ID: {}", id);
         }
+        if let Some(project) = &gitlab.project {
+            println!("  Project: {}", project);
+        }
         println!("  Visibility: {}", gitlab.visibility);
         println!("  Auto sync: {}", gitlab.auto_sync);
         println!("  Skip SSL: {}", gitlab.skip_ssl);
     }
 
+    if let Some(webdav) = &config.webdav {
+        println!("WebDAV:");
+        println!("  URL: {}", webdav.url);
+        if let Some(username) = &webdav.username {
+            println!("  Username: {}", username);
+        }
+        println!("  File name: {}", webdav.file_name);
+        println!("  Auto sync: {}", webdav.auto_sync);
+        println!("  Skip SSL: {}", webdav.skip_ssl);
+    }
+
+    if let Some(git) = &config.git {
+        println!("Git:");
+        println!("  Repository: {}", git.repository);
+        println!("  Branch: {}", git.branch);
+        println!("  File name: {}", git.file_name);
+        println!("  Auto sync: {}", git.auto_sync);
+    }
+
+    if let Some(directory) = &config.directory {
+        println!("Directory:");
+        println!("  Path: {}", directory.path.display());
+        println!("  File name: {}", directory.file_name);
+        println!("  Auto sync: {}", directory.auto_sync);
+    }
+
+    if let Some(encryption) = &config.encryption {
+        println!("Encryption:");
+        if let Some(key_file) = &encryption.key_file {
+            println!("  Key file: {}", key_file.display());
+        } else if encryption.passphrase.is_some() {
+            println!("  Passphrase: ***");
+        } else {
+            println!("  Passphrase: from PROMPTHEUS_SYNC_PASSPHRASE");
+        }
+        if encryption.allow_plaintext_remote {
+            println!("  Allow plaintext remote: true");
+        }
+    }
+
     Ok(())
 }
 
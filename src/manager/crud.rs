@@ -5,12 +5,13 @@ use std::fs;
 
 use crate::cli::{DeleteArgs, EditArgs, NewArgs, ShowArgs};
 use crate::config::Config;
-use crate::core::data::Prompt;
+use crate::core::data::{Prompt, PromptCollection};
 use crate::core::operations::PromptOperations;
-use crate::core::traits::{PromptCrud, PromptInteraction, PromptSearch};
+use crate::core::traits::{PromptCrud, PromptInteraction, PromptSearch, PromptStorage};
 use crate::utils::{
     self, OutputStyle,
     error::{AppError, FlowResult},
+    variables::VariableValues,
 };
 
 // Create operations
@@ -51,16 +52,14 @@ fn resolve_prompt_content(storage: &PromptOperations, args: &NewArgs) -> Result<
     }
 
     if args.editor {
-        return Ok(utils::open_editor_custom(
-            None,
-            None,
-            Some(&storage.config().general.editor),
-        )?);
+        return utils::open_editor_custom(None, None, Some(&storage.config().general.editor));
     }
 
     match utils::prompt_multiline(&format!("{}:", OutputStyle::label("Prompt content"))) {
         Some(content) => Ok(content),
-        None => Err(AppError::System("Operation cancelled by user".to_string())),
+        None => Err(AppError::Cancelled(
+            "Operation cancelled by user".to_string(),
+        )),
     }
 }
 
@@ -110,19 +109,26 @@ fn prompt_input_default(
         )
     };
 
-    result.ok_or_else(|| AppError::System("Operation cancelled by user".to_string()))
+    result.ok_or_else(|| AppError::Cancelled("Operation cancelled by user".to_string()))
 }
 
 fn prompt_input_labeled(label: &str, completions: &[String]) -> Result<String, AppError> {
     utils::prompt_input_with_autocomplete(&format!("{}: ", OutputStyle::label(label)), completions)
-        .ok_or_else(|| AppError::System("Operation cancelled by user".to_string()))
+        .ok_or_else(|| AppError::Cancelled("Operation cancelled by user".to_string()))
 }
 
 // Read operations
 pub fn handle_show_command(config: Config, args: &ShowArgs) -> Result<FlowResult, AppError> {
     let manager = PromptOperations::new(&config);
 
-    if let Some(prompt) = manager.find_prompt(&args.identifier)? {
+    if let Some(mut prompt) = manager.find_prompt(&args.identifier)? {
+        // Given values mean the caller wants the rendered prompt
+        let values = VariableValues::from_args(&args.vars, args.vars_file.as_deref())?
+            .allow_commands(config.general.allow_command_vars);
+        if !values.is_empty() {
+            prompt.content = manager.render_prompt_content(&prompt, &values)?;
+        }
+
         // Display complete prompt with all logic handled internally
         OutputStyle::display_prompt_complete(&prompt)?;
     } else {
@@ -155,23 +161,41 @@ pub async fn handle_edit_command(config: Config, args: &EditArgs) -> Result<Flow
             return Ok(FlowResult::Cancelled(msg));
         }
         Err(FlowResult::Success(_) | FlowResult::EmptyList { .. }) => {
-            return Err(AppError::System("Unexpected flow result".to_string()));
+            return Err(AppError::system("Unexpected flow result".to_string()));
         }
     };
 
-    let line_number =
-        match find_prompt_line_number(&storage.config().general.prompt_file, &prompt.description) {
-            Ok(num) => num,
-            Err(_) => {
-                return Ok(FlowResult::NotFound {
-                    item_type: "Prompt in TOML file".to_string(),
-                    search_term: prompt.description.clone(),
-                });
-            }
-        };
+    if storage.uses_database() {
+        edit_prompt_in_editor(&storage, &prompt, args.editor.as_deref())?;
+        crate::manager::sync::handle_auto_sync_after_crud(storage.config()).await;
+        return Ok(FlowResult::Success("".to_string()));
+    }
 
-    utils::edit_file_direct(
-        &storage.config().general.prompt_file,
+    if let Some(path) = storage.markdown_file(&prompt)? {
+        edit_store_file(&storage, &path, None, args.editor.as_deref())?;
+        crate::manager::sync::handle_auto_sync_after_crud(storage.config()).await;
+        return Ok(FlowResult::Success("".to_string()));
+    }
+
+    // Prompts from prompt_dirs are edited in the file they came from
+    let prompt_file = prompt
+        .source
+        .clone()
+        .unwrap_or_else(|| storage.config().general.prompt_file.clone());
+
+    let line_number = match find_prompt_line_number(&prompt_file, &prompt.description) {
+        Ok(num) => num,
+        Err(_) => {
+            return Ok(FlowResult::NotFound {
+                item_type: "Prompt in TOML file".to_string(),
+                search_term: prompt.description.clone(),
+            });
+        }
+    };
+
+    edit_store_file(
+        &storage,
+        &prompt_file,
         Some(line_number as u32),
         args.editor.as_deref(),
     )?;
@@ -181,20 +205,72 @@ pub async fn handle_edit_command(config: Config, args: &EditArgs) -> Result<Flow
     Ok(FlowResult::Success("".to_string()))
 }
 
+/// Open a file of the store in the editor and stamp the prompts it changed
+///
+/// Other invocations are kept from writing while the file is open. The lock
+/// is released on return, before auto-sync takes it again.
+fn edit_store_file(
+    storage: &PromptOperations,
+    path: &std::path::Path,
+    line: Option<u32>,
+    editor: Option<&str>,
+) -> Result<(), AppError> {
+    let _lock = storage.lock_store()?;
+    let before_edit = storage.load_prompts()?;
+    utils::edit_file_direct(path, line, editor)?;
+    storage.stamp_modified_prompts(&before_edit)?;
+    Ok(())
+}
+
+/// Edit a single prompt as TOML in a temporary file
+///
+/// Used when the store is not a TOML file the editor could open directly.
+fn edit_prompt_in_editor(
+    storage: &PromptOperations,
+    prompt: &Prompt,
+    editor: Option<&str>,
+) -> Result<(), AppError> {
+    let content = toml::to_string_pretty(&PromptCollection {
+        prompts: vec![prompt.clone()],
+    })
+    .map_err(|e| AppError::system(format!("Failed to serialize prompt: {}", e)).with_source(e))?;
+
+    let edited = utils::open_editor_custom(Some(&content), Some(1), editor)?;
+    let mut collection: PromptCollection =
+        toml::from_str(&edited).map_err(|e| AppError::toml_parse("edited prompt", &edited, e))?;
+    if collection.prompts.len() != 1 {
+        return Err(AppError::system(
+            "The edited text must contain exactly one prompt".to_string(),
+        ));
+    }
+
+    let mut updated = collection.prompts.remove(0);
+    if updated.same_content(prompt) {
+        return Ok(());
+    }
+    updated.id = prompt.id.clone();
+    updated.created_at = prompt.created_at;
+    updated.touch();
+    storage.update_prompt(&updated)
+}
+
 fn resolve_prompt_to_edit(
     storage: &PromptOperations,
     args: &EditArgs,
     prompts: Vec<Prompt>,
 ) -> Result<Prompt, FlowResult> {
     if let Some(identifier) = args.identifier.as_ref().or(args.id.as_ref()) {
-        prompts
-            .iter()
-            .find(|p| {
-                p.id.as_ref() == Some(identifier)
-                    || p.description
+        let collection = PromptCollection::from(prompts);
+        collection
+            .find_by_id(identifier)
+            .or_else(|| {
+                collection.prompts.iter().find(|p| {
+                    p.description
                         .to_lowercase()
                         .contains(&identifier.to_lowercase())
+                })
             })
+            .or_else(|| collection.find_by_id_prefix(identifier))
             .cloned()
             .ok_or_else(|| FlowResult::NotFound {
                 item_type: "Prompt".to_string(),
@@ -204,7 +280,9 @@ fn resolve_prompt_to_edit(
         storage
             .select_interactive_prompts(prompts)
             .map_err(|e| match e {
-                AppError::System(msg) => FlowResult::Cancelled(msg),
+                AppError::System { message: msg, .. } | AppError::Cancelled(msg) => {
+                    FlowResult::Cancelled(msg)
+                }
                 _ => FlowResult::Cancelled("Failed to select prompt".to_string()),
             })?
             .ok_or_else(|| FlowResult::Cancelled("Operation cancelled by user".to_string()))
@@ -215,7 +293,8 @@ fn find_prompt_line_number(
     file_path: &std::path::Path,
     prompt_description: &str,
 ) -> Result<usize, AppError> {
-    let content = fs::read_to_string(file_path).map_err(|e| AppError::Io(e.to_string()))?;
+    let content =
+        fs::read_to_string(file_path).map_err(|e| AppError::io(e.to_string()).with_source(e))?;
     let mut last_header_line = None;
     // Construct the exact line to search for, including quotes.
     let search_str = format!(r#"Description = "{}""#, prompt_description);
@@ -233,7 +312,7 @@ fn find_prompt_line_number(
         }
     }
 
-    Err(AppError::System("Prompt not found in TOML".to_string()))
+    Err(AppError::NotFound("Prompt not found in TOML".to_string()))
 }
 
 // Delete operations
@@ -260,7 +339,7 @@ fn resolve_prompt_to_delete(
     let prompts = manager.get_all_prompts_or_return_empty()?;
     manager
         .select_interactive_prompts(prompts)?
-        .ok_or_else(|| AppError::System("Prompt selection cancelled".to_string()))
+        .ok_or_else(|| AppError::Cancelled("Prompt selection cancelled".to_string()))
 }
 
 fn confirm_delete(
@@ -269,16 +348,91 @@ fn confirm_delete(
     force: bool,
 ) -> Result<(), AppError> {
     if !force && !utils::prompt_yes_no("\nAre you sure you want to delete this prompt?")? {
-        return Err(AppError::System("Prompt not deleted".to_string()));
+        return Err(AppError::Cancelled("Prompt not deleted".to_string()));
     }
 
     let id = prompt
         .id
         .as_ref()
-        .ok_or_else(|| AppError::System("Cannot delete prompt: missing ID".to_string()))?;
+        .ok_or_else(|| AppError::system("Cannot delete prompt: missing ID".to_string()))?;
 
     manager.delete_prompt(id)?;
-    println!("✓ Prompt '{}' deleted successfully!", prompt.description);
+    println!(
+        "✓ Prompt '{}' moved to the trash (restore it with 'promptheus trash restore {}')",
+        prompt.description, id
+    );
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::DirectoryConfig;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_edit_releases_lock_before_auto_sync() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = Config::default();
+        config.general.prompt_file = dir.path().join("local").join("prompts.toml");
+        config.directory = Some(DirectoryConfig {
+            path: dir.path().join("shared"),
+            file_name: "prompts.toml".to_string(),
+            auto_sync: true,
+        });
+        let storage = PromptOperations::new(&config);
+        storage
+            .add_prompt(Prompt::new("Review".to_string(), "v1".to_string()))
+            .unwrap();
+
+        // The remote has a prompt the local store lacks, so auto-sync writes locally
+        fs::create_dir_all(dir.path().join("shared")).unwrap();
+        let remote = PromptCollection {
+            prompts: vec![Prompt::new("Remote".to_string(), "r".to_string())],
+        };
+        fs::write(
+            dir.path().join("shared").join("prompts.toml"),
+            toml::to_string_pretty(&remote).unwrap(),
+        )
+        .unwrap();
+
+        // The editor gets `+line file`; change the content of the last argument
+        let editor = dir.path().join("editor.sh");
+        fs::write(
+            &editor,
+            "#!/bin/sh\nfor file; do :; done\nsed -i 's/v1/v2/' \"$file\"\n",
+        )
+        .unwrap();
+        fs::set_permissions(&editor, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let args = EditArgs {
+            identifier: Some("Review".to_string()),
+            id: None,
+            tag: None,
+            category: None,
+            file: false,
+            editor: Some(editor.display().to_string()),
+            line: None,
+        };
+        let (done, finished) = std::sync::mpsc::channel();
+        let edit_config = config.clone();
+        std::thread::spawn(move || {
+            let runtime = tokio::runtime::Runtime::new().unwrap();
+            let result = runtime.block_on(handle_edit_command(edit_config, &args));
+            done.send(result.is_ok()).unwrap();
+        });
+        assert!(
+            finished
+                .recv_timeout(std::time::Duration::from_secs(30))
+                .expect("edit deadlocked during auto-sync")
+        );
+
+        let prompts = storage.load_prompts().unwrap().prompts;
+        let review = prompts.iter().find(|p| p.description == "Review").unwrap();
+        assert_eq!(review.content, "v2");
+        assert!(prompts.iter().any(|p| p.description == "Remote"));
+    }
+}
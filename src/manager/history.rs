@@ -0,0 +1,196 @@
+// Prompt history operations
+// Lists, compares and restores earlier revisions of a prompt
+
+use crate::cli::{DiffArgs, HistoryArgs, RevertArgs};
+use crate::config::Config;
+use crate::core::data::Prompt;
+use crate::core::operations::PromptOperations;
+use crate::core::revisions::Revision;
+use crate::core::traits::PromptSearch;
+use crate::core::trash::UndoRecord;
+use crate::utils::diff::{PromptChange, changed_fields, print_changes};
+use crate::utils::error::{AppError, AppResult};
+use crate::utils::format::format_datetime;
+use crate::utils::{OutputStyle, print_success};
+
+pub fn handle_history_command(config: Config, args: &HistoryArgs) -> AppResult<()> {
+    let storage = PromptOperations::new(&config);
+    let prompt = find_prompt(&storage, &args.identifier)?;
+    let revisions = storage.revisions().list(prompt_id(&prompt)?)?;
+
+    if revisions.is_empty() {
+        println!("No earlier revisions of '{}' yet.", prompt.description);
+        return Ok(());
+    }
+
+    OutputStyle::print_header(&format!(
+        "History of '{}' ({} revision(s))",
+        prompt.description,
+        revisions.len()
+    ));
+    // Each revision is annotated with what the following version changed
+    let successors = revisions.iter().skip(1).map(|r| &r.prompt);
+    for (revision, next) in revisions.iter().zip(successors.chain([&prompt])) {
+        let fields = changed_fields(&revision.prompt, next);
+        let changes = if fields.is_empty() {
+            String::new()
+        } else {
+            format!("(then changed: {})", fields.join(", "))
+        };
+        println!(
+            "{:>5}  {}  {}  {}",
+            OutputStyle::label(&format!("#{}", revision.number)),
+            format_datetime(&revision.saved_at),
+            revision.prompt.description,
+            OutputStyle::muted(&changes)
+        );
+    }
+    println!(
+        "{:>5}  {}  {}",
+        OutputStyle::label("now"),
+        format_datetime(&prompt.updated_at),
+        prompt.description
+    );
+    println!(
+        "\n💡 Compare with: promptheus diff {id} <rev> [rev]\n💡 Restore with: promptheus revert {id} <rev>",
+        id = prompt_id(&prompt)?
+    );
+    Ok(())
+}
+
+pub fn handle_diff_command(config: Config, args: &DiffArgs) -> AppResult<()> {
+    let storage = PromptOperations::new(&config);
+    let prompt = find_prompt(&storage, &args.identifier)?;
+    let id = prompt_id(&prompt)?;
+    let revisions = storage.revisions();
+
+    // Without revisions, compare the latest revision with the current prompt
+    let from = match args.from {
+        Some(number) => revisions.get(id, number)?,
+        None => revisions.list(id)?.pop().ok_or_else(|| {
+            AppError::NotFound(format!(
+                "No earlier revisions of '{}' yet",
+                prompt.description
+            ))
+        })?,
+    };
+    let (to, to_label) = match args.to {
+        Some(number) => (revisions.get(id, number)?.prompt, revision_label(number)),
+        None => (prompt.clone(), "current".to_string()),
+    };
+
+    let fields = changed_fields(&from.prompt, &to);
+    if fields.is_empty() {
+        println!(
+            "No differences between {} and {}.",
+            revision_label(from.number),
+            to_label
+        );
+        return Ok(());
+    }
+
+    let change = PromptChange::Modified {
+        before: Box::new(from.prompt),
+        after: Box::new(to),
+        fields,
+    };
+    print_changes(&[change], &revision_label(from.number), &to_label);
+    Ok(())
+}
+
+pub fn handle_revert_command(config: Config, args: &RevertArgs) -> AppResult<()> {
+    let storage = PromptOperations::new(&config);
+    let prompt = find_prompt(&storage, &args.identifier)?;
+    let Revision {
+        number,
+        prompt: old,
+        ..
+    } = storage
+        .revisions()
+        .get(prompt_id(&prompt)?, args.revision)?;
+
+    if old.same_content(&prompt) {
+        println!(
+            "'{}' already matches revision {}.",
+            prompt.description, number
+        );
+        return Ok(());
+    }
+
+    // The current version becomes a revision itself, so a revert can be undone
+    let mut reverted = prompt.clone();
+    reverted.description = old.description;
+    reverted.content = old.content;
+    reverted.category = old.category;
+    reverted.tag = old.tag;
+    reverted.output = old.output;
+    reverted.touch();
+    storage.update_prompt(&reverted)?;
+
+    let mut record = UndoRecord::new(&format!("revert '{}'", prompt.description));
+    record.previous.push(prompt);
+    storage.undo_journal().record(&record)?;
+
+    print_success(&format!(
+        "Reverted '{}' to revision {}",
+        reverted.description, number
+    ));
+    Ok(())
+}
+
+fn find_prompt(storage: &PromptOperations, identifier: &str) -> AppResult<Prompt> {
+    storage.find_prompt(identifier)?.ok_or_else(|| {
+        AppError::NotFound(format!(
+            "Prompt with ID or description '{}' not found",
+            identifier
+        ))
+    })
+}
+
+fn prompt_id(prompt: &Prompt) -> AppResult<&str> {
+    prompt
+        .id
+        .as_deref()
+        .ok_or_else(|| AppError::system("Prompt has no ID".to_string()))
+}
+
+fn revision_label(number: usize) -> String {
+    format!("revision {}", number)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::traits::PromptCrud;
+
+    #[test]
+    fn test_changes_are_recorded_and_reverted() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = Config::default();
+        config.general.prompt_file = dir.path().join("prompts.toml");
+        let storage = PromptOperations::new(&config);
+
+        let prompt = Prompt::new("Review".to_string(), "v1".to_string());
+        let id = prompt.id.clone().unwrap();
+        storage.add_prompt(prompt.clone()).unwrap();
+        let mut edited = storage.find_prompt(&id).unwrap().unwrap();
+        edited.content = "v2".to_string();
+        storage.update_prompt(&edited).unwrap();
+
+        let revisions = storage.revisions().list(&id).unwrap();
+        assert_eq!(revisions.len(), 1);
+        assert_eq!(revisions[0].prompt.content, "v1");
+
+        let args = RevertArgs {
+            identifier: id.clone(),
+            revision: 1,
+        };
+        handle_revert_command(config.clone(), &args).unwrap();
+        assert_eq!(storage.find_prompt(&id).unwrap().unwrap().content, "v1");
+        // The reverted version is kept as well
+        assert_eq!(
+            storage.revisions().get(&id, 2).unwrap().prompt.content,
+            "v2"
+        );
+    }
+}
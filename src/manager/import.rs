@@ -0,0 +1,68 @@
+// Import operations
+// Loads prompt packs from TOML, JSON or YAML files
+
+use crate::cli::ImportArgs;
+use crate::config::Config;
+use crate::core::data::MergeReport;
+use crate::core::operations::PromptOperations;
+use crate::utils::error::{AppResult, FlowResult, handle_flow};
+use crate::utils::{self, OutputStyle, import};
+
+pub async fn handle_import_command(config: Config, args: &ImportArgs) -> AppResult<()> {
+    let storage = PromptOperations::new(&config);
+
+    let format = import::detect_format(&args.file, args.format.as_ref())?;
+    let imported = import::load_import_file(&args.file, &format)?;
+
+    if imported.prompts.is_empty() {
+        handle_flow(FlowResult::EmptyList {
+            item_type: format!("prompts in {}", args.file.display()),
+        });
+        return Ok(());
+    }
+
+    if !args.merge && !args.force {
+        let existing = storage.get_all_prompts()?;
+        if !existing.is_empty()
+            && !utils::prompt_yes_no(&format!(
+                "Replace {} existing prompt(s) with {} imported prompt(s)?",
+                existing.len(),
+                imported.prompts.len()
+            ))?
+        {
+            return FlowResult::Cancelled("Import cancelled".to_string()).into_result();
+        }
+    }
+
+    let report = storage.import_collection(imported, args.merge)?;
+    print_merge_report(&report);
+
+    crate::manager::sync::handle_auto_sync_after_crud(storage.config()).await;
+
+    Ok(())
+}
+
+fn print_merge_report(report: &MergeReport) {
+    utils::print_success(&format!(
+        "Import finished: {} added, {} updated, {} skipped",
+        report.added.len(),
+        report.updated.len(),
+        report.skipped.len()
+    ));
+
+    let sections = [
+        ("Added", &report.added),
+        ("Updated", &report.updated),
+        ("Skipped", &report.skipped),
+    ];
+
+    for (label, descriptions) in sections {
+        if descriptions.is_empty() {
+            continue;
+        }
+        println!("\n{}:", OutputStyle::header(label));
+        for description in descriptions {
+            println!("  {}", OutputStyle::description(description));
+        }
+    }
+}
@@ -0,0 +1,141 @@
+// Migration operations
+// Copies prompts between the TOML file, the SQLite database and the Markdown directory
+
+use crate::cli::{MigrateArgs, MigrateTarget};
+use crate::config::{Config, StorageBackend};
+use crate::core::operations::PromptOperations;
+use crate::core::traits::PromptStorage;
+use crate::utils::error::{AppError, AppResult};
+use crate::utils::{self, OutputStyle};
+
+pub fn handle_migrate_command(config: Config, args: &MigrateArgs) -> AppResult<()> {
+    let to = backend(args.to);
+    let from = args.from.map_or(config.general.storage, backend);
+    if from == to {
+        return Err(AppError::Config(format!(
+            "Cannot migrate the {} store onto itself. Use --from to pick the source",
+            backend_name(to)
+        )));
+    }
+    let source = store(&config, from);
+    let target = store(&config, to);
+
+    let mut collection = source.load_prompts()?;
+    collection.ensure_ids();
+
+    let _lock = target.lock_store()?;
+    let existing = target.load_prompts()?;
+    if !existing.prompts.is_empty() && !args.force {
+        return Err(AppError::system(format!(
+            "{} already contains {} prompt(s). Use --force to overwrite them",
+            target.store_path().display(),
+            existing.prompts.len()
+        )));
+    }
+    target.save_prompts(&collection)?;
+
+    utils::print_success(&format!(
+        "Copied {} prompt(s) from {} to {}",
+        collection.prompts.len(),
+        source.store_path().display(),
+        target.store_path().display()
+    ));
+    if config.general.storage != to {
+        println!(
+            "{}",
+            OutputStyle::muted(&format!(
+                "Set storage = \"{}\" in the [general] section of your config to use it.",
+                backend_name(to)
+            ))
+        );
+    }
+
+    Ok(())
+}
+
+/// Storage for one backend; prompt packs from `prompt_dirs` are not migrated
+fn store(config: &Config, backend: StorageBackend) -> PromptOperations {
+    let mut config = config.clone();
+    config.general.storage = backend;
+    config.general.prompt_dirs.clear();
+    PromptOperations::new(&config)
+}
+
+fn backend(target: MigrateTarget) -> StorageBackend {
+    match target {
+        MigrateTarget::Toml => StorageBackend::Toml,
+        MigrateTarget::Sqlite => StorageBackend::Sqlite,
+        MigrateTarget::Markdown => StorageBackend::Markdown,
+    }
+}
+
+fn backend_name(backend: StorageBackend) -> &'static str {
+    match backend {
+        StorageBackend::Toml => "toml",
+        StorageBackend::Sqlite => "sqlite",
+        StorageBackend::Markdown => "markdown",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::data::Prompt;
+    use crate::core::traits::{PromptCrud, PromptSearch};
+
+    #[test]
+    fn test_migrate_round_trip_keeps_prompts() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = Config::default();
+        config.general.prompt_file = dir.path().join("prompts.toml");
+        config.general.database_file = dir.path().join("prompts.db");
+
+        let toml_store = store(&config, StorageBackend::Toml);
+        let mut prompt = Prompt::new("Review".to_string(), "Review <file>".to_string());
+        prompt.add_tag("code".to_string());
+        toml_store.add_prompt(prompt).unwrap();
+
+        let to_sqlite = MigrateArgs {
+            to: MigrateTarget::Sqlite,
+            from: None,
+            force: false,
+        };
+        handle_migrate_command(config.clone(), &to_sqlite).unwrap();
+
+        let sqlite_store = store(&config, StorageBackend::Sqlite);
+        let found = sqlite_store.find_prompt("Review").unwrap().unwrap();
+        assert_eq!(found.tag, Some(vec!["code".to_string()]));
+        assert_eq!(
+            found.id,
+            toml_store.find_prompt("Review").unwrap().unwrap().id
+        );
+
+        // The target is no longer empty, so a second run needs --force
+        assert!(handle_migrate_command(config.clone(), &to_sqlite).is_err());
+
+        sqlite_store
+            .add_prompt(Prompt::new("Added".to_string(), "x".to_string()))
+            .unwrap();
+        let to_toml = MigrateArgs {
+            to: MigrateTarget::Toml,
+            from: Some(MigrateTarget::Sqlite),
+            force: true,
+        };
+        handle_migrate_command(config.clone(), &to_toml).unwrap();
+        assert_eq!(toml_store.get_all_prompts().unwrap().len(), 2);
+
+        config.general.markdown_dir = dir.path().join("prompts");
+        let to_markdown = MigrateArgs {
+            to: MigrateTarget::Markdown,
+            from: None,
+            force: false,
+        };
+        handle_migrate_command(config.clone(), &to_markdown).unwrap();
+        let markdown_store = store(&config, StorageBackend::Markdown);
+        assert_eq!(
+            markdown_store.find_prompt("Review").unwrap().unwrap().id,
+            found.id
+        );
+        assert!(dir.path().join("prompts/review.md").exists());
+    }
+}
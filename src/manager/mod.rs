@@ -1,13 +1,21 @@
 // Business logic management modules
-pub mod config;
+pub mod config; // Configuration management
 pub mod crud; // CRUD operations management
+pub mod history; // Prompt revision history management
+pub mod import; // Import operations management
+pub mod migrate; // Storage migration management
 pub mod query; // Query and execution management
-pub mod sync; // Synchronization operations management // Configuration management
+pub mod sync; // Synchronization operations management
+pub mod trash; // Trash and undo management
 
 // Re-export functions for backward compatibility
 pub use config::handle_config_command;
 pub use crud::{
     handle_delete_command, handle_edit_command, handle_new_command, handle_show_command,
 };
+pub use history::{handle_diff_command, handle_history_command, handle_revert_command};
+pub use import::handle_import_command;
+pub use migrate::handle_migrate_command;
 pub use query::{handle_exec_command, handle_list_command, handle_search_command};
 pub use sync::{handle_export_command, handle_push_command, handle_sync_command};
+pub use trash::{handle_trash_command, handle_undo_command};
@@ -7,6 +7,7 @@ use crate::core::operations::PromptOperations;
 use crate::core::traits::{PromptDisplay, PromptInteraction, PromptSearch};
 use crate::utils;
 use crate::utils::error::{AppResult, FlowResult, handle_flow};
+use crate::utils::variables::VariableValues;
 use crate::utils::{OutputStyle, copy_to_clipboard, print_success};
 
 // List operations
@@ -77,8 +78,7 @@ pub fn handle_search_command(config: Config, args: &SearchArgs) -> AppResult<()>
             .find_prompt_by_display_line(&prompts, &selected_line)
             .map(|index| &prompts[index])
     } else {
-        handle_flow(FlowResult::Cancelled("Search cancelled".to_string()));
-        return Ok(());
+        return FlowResult::Cancelled("Search cancelled".to_string()).into_result();
     };
 
     if let Some(prompt) = selected_prompt {
@@ -93,32 +93,39 @@ pub fn handle_search_command(config: Config, args: &SearchArgs) -> AppResult<()>
 
 // Execute operations
 pub fn handle_exec_command(config: Config, args: &ExecArgs) -> AppResult<()> {
+    let values = VariableValues::from_args(&args.vars, args.vars_file.as_deref())?
+        .allow_commands(config.general.allow_command_vars);
+
     match &args.identifier {
         Some(identifier) => {
             // Direct execution with ID or description
             let manager = PromptOperations::new(&config);
             if let Some(prompt) = manager.find_prompt(identifier)? {
-                // Execute prompt (copy to clipboard and show content)
-                manager.execute_prompt(&prompt, true)?;
+                if args.output {
+                    // Plain output for scripts and pipes
+                    println!("{}", manager.render_prompt_content(&prompt, &values)?);
+                } else {
+                    // Execute prompt (copy to clipboard and show content)
+                    manager.execute_prompt(&prompt, &values, true)?;
+                }
             } else {
-                // Handle not found as notification, not error
-                handle_flow(FlowResult::NotFound {
+                return FlowResult::NotFound {
                     item_type: "Prompt with ID or description".to_string(),
                     search_term: identifier.to_string(),
-                });
-                return Ok(());
+                }
+                .into_result();
             }
         }
         None => {
             // Interactive mode
-            handle_interactive_exec(config, args)?;
+            handle_interactive_exec(config, &values)?;
         }
     }
 
     Ok(())
 }
 
-fn handle_interactive_exec(config: Config, _args: &ExecArgs) -> AppResult<()> {
+fn handle_interactive_exec(config: Config, values: &VariableValues) -> AppResult<()> {
     let manager = PromptOperations::new(&config);
 
     // Get all prompts for selection using same method as search
@@ -143,14 +150,11 @@ fn handle_interactive_exec(config: Config, _args: &ExecArgs) -> AppResult<()> {
             .find_prompt_by_display_line(&prompts, &selected_line)
             .map(|index| &prompts[index])
     } else {
-        handle_flow(FlowResult::Cancelled(
-            "Prompt selection cancelled".to_string(),
-        ));
-        return Ok(());
+        return FlowResult::Cancelled("Prompt selection cancelled".to_string()).into_result();
     };
 
     if let Some(prompt) = selected_prompt {
-        let rendered_content = prompt.content.clone();
+        let rendered_content = manager.render_prompt_content(prompt, values)?;
 
         // Always copy to clipboard in interactive mode (before showing content to avoid minus pager issues)
         copy_to_clipboard(&rendered_content)?;
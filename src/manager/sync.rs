@@ -2,140 +2,269 @@
 // Consolidated from sync.rs, push.rs, export.rs
 
 use crate::utils::error::{AppError, AppResult, report_error};
-use chrono::Utc;
 use std::fs;
 use std::io::{self, Write};
+use std::path::PathBuf;
 
-use crate::cli::{ExportArgs, SyncArgs};
+use crate::cli::{ExportArgs, SyncArgs, SyncCommands};
 use crate::config::Config;
+use crate::core::data::{Prompt, PromptCollection};
 use crate::core::operations::PromptOperations;
 use crate::core::traits::PromptStorage;
-use crate::sync::{SyncClient, SyncDirection, gist::GistClient, should_sync};
-use crate::utils::{generate_html, open_browser, print_warning};
-
-/// Check if an error is likely network-related and provide appropriate user feedback
-fn handle_potential_network_error(error: &AppError) -> AppResult<()> {
-    let error_msg = error.to_string().to_lowercase();
-
-    // Check for common network-related error indicators
-    if error_msg.contains("network")
-        || error_msg.contains("connection")
-        || error_msg.contains("timeout")
-        || error_msg.contains("dns")
-        || error_msg.contains("unreachable")
-        || error_msg.contains("refused")
-        || error_msg.contains("host")
-        || error_msg.contains("ssl")
-        || error_msg.contains("certificate")
-        || error_msg.contains("tcp")
-        || error_msg.contains("http")
-    {
-        report_error(&AppError::Network(format!(
-            "Request failed: {}. Please check your internet connection and try again.",
-            error
-        )));
-        return Err(AppError::Network(format!("Request failed: {}", error)));
+use crate::sync::history::{self, SnapshotSide, find_snapshot, list_snapshots, save_snapshot};
+use crate::sync::merge::{
+    self, ConflictResolution, SyncConflict, collections_match, load_base_snapshot,
+    save_base_snapshot, three_way_merge,
+};
+use crate::sync::{
+    RemoteSnippet, SyncClient, SyncDirection, auto_sync_enabled, create_sync_client, should_sync,
+};
+use crate::utils::diff::{diff_collections, print_changes};
+use crate::utils::format::format_datetime;
+use crate::utils::{
+    OutputStyle, generate_html, open_browser, print_success, print_warning, prompt_input,
+};
+
+/// Add guidance for a failed remote operation, keeping the error itself intact
+fn remote_failure(action: &str, error: AppError) -> AppError {
+    let hint = match &error {
+        AppError::Connection { .. } => {
+            Some("Please check your internet connection and try again.".to_string())
+        }
+        AppError::RateLimited {
+            retry_after: Some(seconds),
+            ..
+        } => Some(format!(
+            "The service is rate limiting requests; try again in {} second(s).",
+            seconds
+        )),
+        AppError::RateLimited { .. } => {
+            Some("The service is rate limiting requests; try again later.".to_string())
+        }
+        AppError::Auth(_) => Some(
+            "Check that your credentials are valid and the access token has the required scope."
+                .to_string(),
+        ),
+        _ => None,
+    };
+
+    if let Some(hint) = hint {
+        print_warning(&format!("Failed to {}. {}", action, hint));
     }
+    error
+}
 
-    // Return the original error so the calling code can handle it
-    Err(error.clone())
+/// Storage for the prompts that take part in sync
+///
+/// Prompt packs from `prompt_dirs` are shared by other means, so only
+/// `prompt_file` is synced.
+fn sync_storage(config: &Config) -> PromptOperations {
+    let mut config = config.clone();
+    config.general.prompt_dirs.clear();
+    PromptOperations::new(&config)
 }
 
 // Sync operations
 pub async fn handle_sync_command(config: Config, args: &SyncArgs) -> AppResult<()> {
-    // Check if any sync backend is configured
-    let _gist_config = config.gist.as_ref().ok_or_else(|| {
-        AppError::System(
-            "No sync backend configured. Please configure Gist or GitLab in your config."
-                .to_string(),
-        )
-    })?;
+    match &args.command {
+        Some(SyncCommands::History) => return handle_sync_history(&config),
+        Some(SyncCommands::Rollback { snapshot, push }) => {
+            return handle_sync_rollback(&config, snapshot, *push).await;
+        }
+        None => {}
+    }
 
-    println!("🔄 Starting sync process...");
+    // Create sync client for the configured backend
+    let sync_client = create_sync_client(&config)?;
 
-    // Create sync client first to avoid moving config
-    let sync_client: Box<dyn SyncClient> = if let Some(gist_config) = &config.gist {
-        Box::new(GistClient::new(gist_config.clone())?)
-    } else {
-        return Err(AppError::System(
-            "No supported sync backend configured".to_string(),
-        ));
-    };
+    println!("🔄 Starting sync process...");
 
     // Create storage instance
-    let storage = PromptOperations::new(&config);
+    let storage = sync_storage(&config);
 
     // Load local prompts
-    let local_prompts = storage
-        .load_prompts()
-        .map_err(|e| AppError::System(format!("Failed to load local prompts: {}", e)))?;
-
-    // Get the most recent local update time
-    let local_updated = local_prompts
-        .prompts
-        .iter()
-        .map(|p| p.updated_at)
-        .max()
-        .unwrap_or_else(Utc::now);
+    let local_prompts = storage.load_prompts().map_err(|e| {
+        AppError::system(format!("Failed to load local prompts: {}", e)).with_source(e)
+    })?;
 
     // Get remote snippet
     println!("📥 Fetching remote content...");
     let remote_snippet = sync_client
         .get_remote()
         .await
-        .map_err(|e| AppError::Network(format!("Failed to fetch remote content: {}", e)))
-        .map_err(|e| handle_potential_network_error(&e).unwrap_err())?;
-
-    // Determine sync direction
-    let sync_direction = should_sync(local_updated, remote_snippet.updated_at, args.force);
-
-    match sync_direction {
-        SyncDirection::Upload => {
-            if !args.download {
-                upload_to_remote(&storage, &*sync_client, &local_prompts).await?;
-            } else {
-                print_warning("Both upload and download specified. Downloading takes precedence.");
-                download_from_remote(&storage, &remote_snippet).await?;
-            }
+        .map_err(|e| remote_failure("fetch remote content", e))?;
+    println!(
+        "   Remote last modified: {}",
+        OutputStyle::muted(&format_datetime(&remote_snippet.updated_at))
+    );
+
+    if args.dry_run {
+        return preview_sync(&storage, &local_prompts, &remote_snippet, args);
+    }
+
+    // Explicit directions overwrite one side entirely
+    if args.upload || args.force {
+        if args.download {
+            print_warning("Both upload and download specified. Uploading takes precedence.");
         }
-        SyncDirection::Download => {
-            if !args.upload {
-                download_from_remote(&storage, &remote_snippet).await?;
-            } else {
-                print_warning("Both upload and download specified. Uploading takes precedence.");
-                upload_to_remote(&storage, &*sync_client, &local_prompts).await?;
-            }
+        return upload_to_remote(
+            &storage,
+            &*sync_client,
+            &local_prompts,
+            Some(&remote_snippet),
+        )
+        .await;
+    }
+    if args.download {
+        return download_from_remote(&storage, &remote_snippet).await;
+    }
+
+    let flag_resolution = conflict_resolution_from_args(args);
+    merge_with_remote(
+        &storage,
+        &*sync_client,
+        &local_prompts,
+        &remote_snippet,
+        |conflict| match flag_resolution {
+            Some(resolution) => Ok(resolution),
+            None => prompt_conflict_resolution(conflict),
+        },
+    )
+    .await
+}
+
+/// Conflict resolution requested on the command line, if any
+fn conflict_resolution_from_args(args: &SyncArgs) -> Option<ConflictResolution> {
+    if args.ours {
+        Some(ConflictResolution::Ours)
+    } else if args.theirs {
+        Some(ConflictResolution::Theirs)
+    } else if args.keep_both {
+        Some(ConflictResolution::KeepBoth)
+    } else {
+        None
+    }
+}
+
+/// Ask the user how to resolve a single conflict
+fn prompt_conflict_resolution(conflict: &SyncConflict) -> AppResult<ConflictResolution> {
+    println!(
+        "\n⚠️  {}",
+        OutputStyle::warning(&format!("Conflict: '{}'", conflict.description()))
+    );
+    print_conflict_side("Local", conflict.local.as_ref());
+    print_conflict_side("Remote", conflict.remote.as_ref());
+
+    loop {
+        let input = prompt_input("Keep [o]urs, [t]heirs or [b]oth? ")?;
+        match input.to_lowercase().as_str() {
+            "o" | "ours" => return Ok(ConflictResolution::Ours),
+            "t" | "theirs" => return Ok(ConflictResolution::Theirs),
+            "b" | "both" => return Ok(ConflictResolution::KeepBoth),
+            _ => println!("Please enter 'o', 't' or 'b'"),
         }
-        SyncDirection::None => {
-            println!("✅ Local and remote are already in sync.");
-            if args.force {
-                println!("🔧 Force flag specified. No action needed.");
-            }
+    }
+}
+
+fn print_conflict_side(label: &str, prompt: Option<&Prompt>) {
+    match prompt {
+        Some(prompt) => {
+            println!(
+                "\n{} ({}):",
+                OutputStyle::label(label),
+                OutputStyle::muted(&format_datetime(&prompt.updated_at))
+            );
+            OutputStyle::print_content_truncated(&prompt.content);
         }
+        None => println!(
+            "\n{}: {}",
+            OutputStyle::label(label),
+            OutputStyle::muted("deleted")
+        ),
+    }
+}
+
+/// Pick the side with the most recent edit, used when nobody can be asked
+fn newest_side(conflict: &SyncConflict) -> ConflictResolution {
+    let local_updated = conflict
+        .local
+        .as_ref()
+        .map(|p| p.updated_at)
+        .unwrap_or_default();
+    let remote_updated = conflict
+        .remote
+        .as_ref()
+        .map(|p| p.updated_at)
+        .unwrap_or_default();
+
+    match should_sync(local_updated, remote_updated, false) {
+        SyncDirection::Download => ConflictResolution::Theirs,
+        SyncDirection::Upload | SyncDirection::None => ConflictResolution::Ours,
+    }
+}
+
+/// Show the differences between local and remote and what sync would do about them
+fn preview_sync(
+    storage: &PromptOperations,
+    local_prompts: &PromptCollection,
+    remote_snippet: &RemoteSnippet,
+    args: &SyncArgs,
+) -> AppResult<()> {
+    let remote_prompts = parse_remote_content(remote_snippet)?;
+    let changes = diff_collections(local_prompts, &remote_prompts);
+
+    println!();
+    if changes.is_empty() {
+        println!("✅ Local and remote prompts are identical.");
+        return Ok(());
     }
 
+    OutputStyle::print_header(&format!(
+        "Differences from local to remote ({})",
+        changes.len()
+    ));
+    print_changes(&changes, "local", "remote");
+    println!();
+
+    if args.upload || args.force {
+        println!(
+            "📤 Sync would overwrite the remote with {} local prompt(s)",
+            local_prompts.prompts.len()
+        );
+    } else if args.download {
+        println!(
+            "📥 Sync would replace local prompts with {} remote prompt(s)",
+            remote_prompts.prompts.len()
+        );
+    } else {
+        let base = load_base_snapshot(&base_snapshot_path(storage))?;
+        let outcome = three_way_merge(&base, local_prompts, &remote_prompts);
+        print_change_list("📥 Remote changes to apply", &outcome.pulled);
+        print_change_list("📤 Local changes to upload", &outcome.pushed);
+        let conflicts: Vec<String> = outcome
+            .conflicts
+            .iter()
+            .map(|c| c.description().to_string())
+            .collect();
+        print_change_list("⚠️  Conflicts to resolve", &conflicts);
+    }
+
+    println!("{}", OutputStyle::muted("Dry run: nothing was written."));
     Ok(())
 }
 
 // Push operations (force upload)
 pub async fn handle_push_command(config: Config) -> AppResult<()> {
-    // Check if sync backend is configured
-    let gist_config = config.gist.as_ref().ok_or_else(|| {
-        AppError::System(
-            "No sync backend configured. Please configure Gist in your config.".to_string(),
-        )
-    })?;
-
     println!("🚀 Starting push process...");
     println!("📤 Force uploading local prompts to remote...");
 
     // Create storage instance
-    let storage = PromptOperations::new(&config);
+    let storage = sync_storage(&config);
 
     // Load local prompts
-    let local_prompts = storage
-        .load_prompts()
-        .map_err(|e| AppError::System(format!("Failed to load local prompts: {}", e)))?;
+    let local_prompts = storage.load_prompts().map_err(|e| {
+        AppError::system(format!("Failed to load local prompts: {}", e)).with_source(e)
+    })?;
 
     if local_prompts.prompts.is_empty() {
         print_warning("No prompts found locally. Nothing to push.");
@@ -145,20 +274,28 @@ pub async fn handle_push_command(config: Config) -> AppResult<()> {
     println!("📋 Found {} local prompt(s)", local_prompts.prompts.len());
 
     // Create sync client
-    let sync_client = GistClient::new(gist_config.clone())
-        .map_err(|e| AppError::System(format!("Failed to create Gist client: {}", e)))
-        .map_err(|e| handle_potential_network_error(&e).unwrap_err())?;
+    let sync_client = create_sync_client(&config)?;
+
+    // Serialize local prompts to TOML, with IDs for prompts saved without one
+    let mut local_prompts = local_prompts;
+    local_prompts.ensure_ids();
+    let content = toml::to_string_pretty(&local_prompts).map_err(|e| {
+        AppError::system(format!("Failed to serialize local prompts: {}", e)).with_source(e)
+    })?;
 
-    // Serialize local prompts to TOML
-    let content = toml::to_string_pretty(&local_prompts)
-        .map_err(|e| AppError::System(format!("Failed to serialize local prompts: {}", e)))?;
+    // Keep what is about to be replaced; a remote that cannot be read yet
+    // (e.g. no gist created so far) has nothing to back up
+    if let Ok(previous_remote) = sync_client.get_remote().await {
+        backup_remote(&storage, &previous_remote, &content)?;
+    }
 
     // Upload to remote
     sync_client
         .upload(content)
         .await
-        .map_err(|e| AppError::Sync(format!("Failed to upload to remote: {}", e)))
-        .map_err(|e| handle_potential_network_error(&e).unwrap_err())?;
+        .map_err(|e| remote_failure("upload to remote", e))?;
+
+    save_base_snapshot(&base_snapshot_path(&storage), &local_prompts)?;
 
     println!(
         "✅ Successfully pushed {} prompt(s) to remote",
@@ -193,7 +330,7 @@ pub fn handle_export_command(config: Config, args: &ExportArgs) -> AppResult<()>
                 .general
                 .prompt_file
                 .parent()
-                .ok_or_else(|| AppError::System("Cannot determine config directory".to_string()))?;
+                .ok_or_else(|| AppError::system("Cannot determine config directory".to_string()))?;
             config_dir.join(output).to_string_lossy().to_string()
         }
     } else {
@@ -203,7 +340,7 @@ pub fn handle_export_command(config: Config, args: &ExportArgs) -> AppResult<()>
             .general
             .prompt_file
             .parent()
-            .ok_or_else(|| AppError::System("Cannot determine config directory".to_string()))?;
+            .ok_or_else(|| AppError::system("Cannot determine config directory".to_string()))?;
         config_dir
             .join(default_filename)
             .to_string_lossy()
@@ -211,8 +348,9 @@ pub fn handle_export_command(config: Config, args: &ExportArgs) -> AppResult<()>
     };
 
     // Write HTML file
-    fs::write(&output_path, html_content)
-        .map_err(|e| AppError::Io(format!("Failed to write HTML file: {}: {}", output_path, e)))?;
+    fs::write(&output_path, html_content).map_err(|e| {
+        AppError::io(format!("Failed to write HTML file: {}: {}", output_path, e)).with_source(e)
+    })?;
 
     println!("✅ Exported {} prompts to {}", prompts.len(), output_path);
 
@@ -225,25 +363,34 @@ pub fn handle_export_command(config: Config, args: &ExportArgs) -> AppResult<()>
 
 // Helper functions
 async fn upload_to_remote(
-    _storage: &PromptOperations,
+    storage: &PromptOperations,
     sync_client: &dyn SyncClient,
-    local_prompts: &crate::core::data::PromptCollection,
+    local_prompts: &PromptCollection,
+    previous_remote: Option<&RemoteSnippet>,
 ) -> AppResult<()> {
     print!("📤 Uploading local changes to remote... ");
     io::stdout()
         .flush()
-        .map_err(|e| AppError::Io(e.to_string()))?;
+        .map_err(|e| AppError::io(e.to_string()).with_source(e))?;
 
-    // Serialize local prompts to TOML
-    let content = toml::to_string_pretty(local_prompts)
-        .map_err(|e| AppError::System(format!("Failed to serialize local prompts: {}", e)))?;
+    // Serialize local prompts to TOML, with IDs for prompts saved without one
+    let mut local_prompts = local_prompts.clone();
+    local_prompts.ensure_ids();
+    let content = toml::to_string_pretty(&local_prompts).map_err(|e| {
+        AppError::system(format!("Failed to serialize local prompts: {}", e)).with_source(e)
+    })?;
+
+    if let Some(previous_remote) = previous_remote {
+        backup_remote(storage, previous_remote, &content)?;
+    }
 
     // Upload to remote
     sync_client
         .upload(content)
         .await
-        .map_err(|e| AppError::Sync(format!("Failed to upload to remote: {}", e)))
-        .map_err(|e| handle_potential_network_error(&e).unwrap_err())?;
+        .map_err(|e| remote_failure("upload to remote", e))?;
+
+    save_base_snapshot(&base_snapshot_path(storage), &local_prompts)?;
 
     println!("✅ Done");
     Ok(())
@@ -251,50 +398,219 @@ async fn upload_to_remote(
 
 async fn download_from_remote(
     storage: &PromptOperations,
-    remote_snippet: &crate::sync::RemoteSnippet,
+    remote_snippet: &RemoteSnippet,
 ) -> AppResult<()> {
     print!("📥 Downloading remote changes... ");
     io::stdout()
         .flush()
-        .map_err(|e| AppError::Io(e.to_string()))?;
+        .map_err(|e| AppError::io(e.to_string()).with_source(e))?;
 
     // Parse remote content
-    let remote_prompts: crate::core::data::PromptCollection =
-        toml::from_str(&remote_snippet.content)
-            .map_err(|e| AppError::Sync(format!("Failed to parse remote content: {}", e)))?;
+    let remote_prompts = parse_remote_content(remote_snippet)?;
 
     // Save remote prompts locally
-    storage
-        .save_prompts(&remote_prompts)
-        .map_err(|e| AppError::System(format!("Failed to save remote prompts locally: {}", e)))?;
+    replace_local_prompts(storage, &remote_prompts, None).map_err(|e| {
+        AppError::system(format!("Failed to save remote prompts locally: {}", e)).with_source(e)
+    })?;
+
+    save_base_snapshot(&base_snapshot_path(storage), &remote_prompts)?;
 
     println!("✅ Done");
     Ok(())
 }
 
-pub async fn auto_sync_if_enabled(config: &Config) -> AppResult<()> {
-    // Check if auto-sync is enabled
-    let gist_config = if let Some(gist) = &config.gist {
-        gist
-    } else {
-        return Ok(()); // No sync configured, nothing to do
-    };
+/// Three-way merge local and remote prompts and write the result to both sides
+async fn merge_with_remote<F>(
+    storage: &PromptOperations,
+    sync_client: &dyn SyncClient,
+    local_prompts: &PromptCollection,
+    remote_snippet: &RemoteSnippet,
+    choose: F,
+) -> AppResult<()>
+where
+    F: FnMut(&SyncConflict) -> AppResult<ConflictResolution>,
+{
+    let remote_prompts = parse_remote_content(remote_snippet)?;
+    let base_path = base_snapshot_path(storage);
+    let base = load_base_snapshot(&base_path)?;
+
+    let outcome = three_way_merge(&base, local_prompts, &remote_prompts);
+    if !outcome.conflicts.is_empty() {
+        print_warning(&format!(
+            "{} prompt(s) were changed both locally and remotely",
+            outcome.conflicts.len()
+        ));
+    }
+
+    let pulled = outcome.pulled.clone();
+    let pushed = outcome.pushed.clone();
+    let merged = outcome.resolve_with(choose)?;
+
+    let local_changed = !collections_match(&merged, local_prompts);
+    let remote_changed = !collections_match(&merged, &remote_prompts);
 
-    if !gist_config.auto_sync {
-        return Ok(()); // Auto-sync disabled
+    if local_changed {
+        print_change_list("📥 Remote changes applied", &pulled);
+        replace_local_prompts(storage, &merged, Some(local_prompts)).map_err(|e| match e {
+            AppError::Conflict(_) => e,
+            e => AppError::system(format!("Failed to save merged prompts: {}", e)).with_source(e),
+        })?;
     }
 
-    // Check if local file exists and has content
-    let prompt_file_path = &config.general.prompt_file;
+    if remote_changed {
+        print_change_list("📤 Local changes to upload", &pushed);
+        upload_to_remote(storage, sync_client, &merged, Some(remote_snippet)).await?;
+    }
 
-    // Check if file exists and is not empty
-    if !prompt_file_path.exists()
-        || tokio::fs::metadata(prompt_file_path)
-            .await
-            .map_err(|e| AppError::Io(e.to_string()))?
-            .len()
-            == 0
+    if !local_changed && !remote_changed {
+        println!("✅ Local and remote are already in sync.");
+    }
+
+    save_base_snapshot(&base_path, &merged)
+}
+
+fn print_change_list(title: &str, descriptions: &[String]) {
+    if descriptions.is_empty() {
+        return;
+    }
+    println!("{} ({}):", title, descriptions.len());
+    for description in descriptions {
+        println!("  {}", OutputStyle::description(description));
+    }
+}
+
+fn parse_remote_content(remote_snippet: &RemoteSnippet) -> AppResult<PromptCollection> {
+    // A freshly created remote has nothing to parse yet
+    if remote_snippet.content.trim().is_empty() {
+        return Ok(PromptCollection::default());
+    }
+
+    toml::from_str(&remote_snippet.content).map_err(|e| {
+        AppError::sync(format!("Failed to parse remote content: {}", e)).with_source(e)
+    })
+}
+
+fn base_snapshot_path(storage: &PromptOperations) -> PathBuf {
+    merge::base_snapshot_path(&storage.config().general.prompt_file)
+}
+
+fn sync_history_dir(storage: &PromptOperations) -> PathBuf {
+    history::history_dir(&storage.config().general.prompt_file)
+}
+
+/// Back up the local prompt file, then overwrite it with `collection`
+///
+/// With `expected`, the prompts `collection` was merged from, fails with
+/// `Conflict` instead when they were changed in the meantime.
+fn replace_local_prompts(
+    storage: &PromptOperations,
+    collection: &PromptCollection,
+    expected: Option<&PromptCollection>,
+) -> AppResult<()> {
+    let _lock = storage.lock_store()?;
+    let current = storage.load_prompts()?;
+    if let Some(expected) = expected
+        && !collections_match(&current, expected)
     {
+        return Err(AppError::Conflict(
+            "Local prompts changed during sync. Run sync again to merge the new changes."
+                .to_string(),
+        ));
+    }
+    if !current.prompts.is_empty() {
+        let content = toml::to_string_pretty(&current).map_err(|e| {
+            AppError::system(format!("Failed to serialize prompts: {}", e)).with_source(e)
+        })?;
+        save_snapshot(&sync_history_dir(storage), SnapshotSide::Local, &content)?;
+    }
+
+    storage.replace_prompts("sync", collection)
+}
+
+/// Back up the remote content unless the upload leaves it unchanged
+fn backup_remote(
+    storage: &PromptOperations,
+    previous_remote: &RemoteSnippet,
+    new_content: &str,
+) -> AppResult<()> {
+    if previous_remote.content != new_content {
+        save_snapshot(
+            &sync_history_dir(storage),
+            SnapshotSide::Remote,
+            &previous_remote.content,
+        )?;
+    }
+    Ok(())
+}
+
+/// List the backups taken before sync replaced prompts
+fn handle_sync_history(config: &Config) -> AppResult<()> {
+    let storage = sync_storage(config);
+    let snapshots = list_snapshots(&sync_history_dir(&storage))?;
+
+    if snapshots.is_empty() {
+        println!("No sync history yet.");
+        return Ok(());
+    }
+
+    OutputStyle::print_header(&format!("Sync history ({})", snapshots.len()));
+    for snapshot in &snapshots {
+        let side = match snapshot.side {
+            SnapshotSide::Local => "local before sync",
+            SnapshotSide::Remote => "remote before upload",
+        };
+        let count = snapshot
+            .load()
+            .map(|collection| format!("{} prompt(s)", collection.prompts.len()))
+            .unwrap_or_else(|_| "unreadable".to_string());
+        println!(
+            "{}  {}  {}  {}",
+            OutputStyle::label(&snapshot.name),
+            format_datetime(&snapshot.created_at),
+            side,
+            OutputStyle::muted(&count)
+        );
+    }
+    println!("\n💡 Restore one with: promptheus sync rollback <name> [--push]");
+    Ok(())
+}
+
+/// Restore a backup locally and optionally upload it
+async fn handle_sync_rollback(config: &Config, name: &str, push: bool) -> AppResult<()> {
+    let storage = sync_storage(config);
+    let snapshot = find_snapshot(&sync_history_dir(&storage), name)?;
+    let restored = snapshot.load()?;
+
+    // The current state is backed up too, so a rollback can itself be undone
+    replace_local_prompts(&storage, &restored, None)?;
+    print_success(&format!(
+        "Restored {} prompt(s) from {}",
+        restored.prompts.len(),
+        snapshot.name
+    ));
+
+    if push {
+        let sync_client = create_sync_client(config)?;
+        let remote_snippet = sync_client
+            .get_remote()
+            .await
+            .map_err(|e| remote_failure("fetch remote content", e))?;
+        upload_to_remote(&storage, &*sync_client, &restored, Some(&remote_snippet)).await?;
+    } else {
+        println!("💡 The next sync uploads the restored prompts; use --push to upload now.");
+    }
+
+    Ok(())
+}
+
+pub async fn auto_sync_if_enabled(config: &Config) -> AppResult<()> {
+    // Check if auto-sync is enabled for the configured backend
+    if !auto_sync_enabled(config) {
+        return Ok(());
+    }
+
+    // Check if the local store has any content yet
+    if sync_storage(config).is_store_empty()? {
         println!("🔄 Local file is empty or missing, downloading from remote...");
 
         // Download from remote
@@ -302,127 +618,57 @@ pub async fn auto_sync_if_enabled(config: &Config) -> AppResult<()> {
             upload: false,
             download: true,
             force: false,
+            ours: false,
+            theirs: false,
+            keep_both: false,
+            dry_run: false,
+            command: None,
         };
 
         return handle_sync_command(config.clone(), &sync_args)
             .await
-            .map_err(|e| AppError::Sync(format!("Auto-sync download failed: {}", e)));
+            .map_err(|e| {
+                AppError::sync(format!("Auto-sync download failed: {}", e)).with_source(e)
+            });
     }
 
-    // Get local file modification time
-    let local_metadata = tokio::fs::metadata(prompt_file_path)
-        .await
-        .map_err(|e| AppError::Io(e.to_string()))?;
-    let local_modified = local_metadata
-        .modified()
-        .map_err(|e| AppError::Io(e.to_string()))?
-        .into();
+    let storage = sync_storage(config);
+    let local_prompts = storage.load_prompts().map_err(|e| {
+        AppError::system(format!("Failed to load local prompts: {}", e)).with_source(e)
+    })?;
 
     // Create sync client to get remote info
-    let sync_client: Box<dyn SyncClient> = Box::new(GistClient::new(gist_config.clone())?);
+    let sync_client = create_sync_client(config)?;
 
     // Get remote snippet info
     let remote_snippet = sync_client
         .get_remote()
         .await
-        .map_err(|e| AppError::Network(format!("Failed to fetch remote content: {}", e)))?;
-
-    // Compare timestamps to determine if sync is needed
-    let should_sync = match should_sync(local_modified, remote_snippet.updated_at, false) {
-        SyncDirection::Upload => {
-            println!("🔄 Local changes detected, uploading to remote...");
-            true
-        }
-        SyncDirection::Download => {
-            println!("🔄 Remote changes detected, downloading...");
-            true
-        }
-        SyncDirection::None => {
-            // No sync needed, but let's verify content is the same
-            let local_content = tokio::fs::read_to_string(prompt_file_path)
-                .await
-                .map_err(|e| AppError::Io(e.to_string()))?;
-
-            // Try to parse remote content and compare
-            match toml::from_str::<crate::core::data::PromptCollection>(&remote_snippet.content) {
-                Ok(remote_prompts) => {
-                    match toml::to_string_pretty(&remote_prompts) {
-                        Ok(remote_formatted) => {
-                            // Normalize both contents for comparison
-                            let local_normalized = normalize_toml_content(&local_content);
-                            let remote_normalized = normalize_toml_content(&remote_formatted);
-
-                            if local_normalized != remote_normalized {
-                                println!("🔄 Content differences detected, syncing...");
-                                true
-                            } else {
-                                println!("✅ Already in sync");
-                                false
-                            }
-                        }
-                        Err(_) => {
-                            // If we can't format remote content, assume sync needed
-                            println!("🔄 Unable to format remote content, syncing...");
-                            true
-                        }
-                    }
-                }
-                Err(_) => {
-                    // If we can't parse remote content, assume sync needed
-                    println!("🔄 Unable to parse remote content, syncing...");
-                    true
-                }
-            }
-        }
-    };
-
-    if should_sync {
-        // Perform sync directly without going through handle_sync_command
-        // to avoid re-comparing timestamps with prompts' updated_at
-        let storage = PromptOperations::new(config);
-
-        if local_modified > remote_snippet.updated_at {
-            // Upload local changes
-            println!("📤 Uploading local changes to remote...");
-            let local_prompts = storage
-                .load_prompts()
-                .map_err(|e| AppError::System(format!("Failed to load local prompts: {}", e)))?;
-
-            upload_to_remote(&storage, &*sync_client, &local_prompts)
-                .await
-                .map_err(|e| AppError::Sync(format!("Failed to upload to remote: {}", e)))?;
-        } else if remote_snippet.updated_at > local_modified {
-            // Download remote changes
-            println!("📥 Downloading remote changes...");
-            download_from_remote(&storage, &remote_snippet)
-                .await
-                .map_err(|e| AppError::Sync(format!("Failed to download remote changes: {}", e)))?;
-        }
-    }
-
-    Ok(())
+        .map_err(|e| remote_failure("fetch remote content", e))?;
+
+    // Nobody is asked during auto-sync, so conflicts go to the newest edit
+    merge_with_remote(
+        &storage,
+        &*sync_client,
+        &local_prompts,
+        &remote_snippet,
+        |conflict| Ok(newest_side(conflict)),
+    )
+    .await
 }
-
-/// Normalize TOML content for comparison by removing insignificant whitespace differences
-fn normalize_toml_content(content: &str) -> String {
-    content
-        .lines()
-        .map(|line| line.trim())
-        .filter(|line| !line.is_empty())
-        .collect::<Vec<_>>()
-        .join("\n")
-}
-
 /// Handle auto-sync after CRUD operations with error handling
 pub async fn handle_auto_sync_after_crud(config: &Config) {
     if let Err(e) = auto_sync_if_enabled(config).await {
-        report_error(&AppError::Sync(e.to_string()));
+        report_error(&AppError::sync(e.to_string()).with_source(e));
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::DirectoryConfig;
+    use crate::core::traits::PromptCrud;
+    use crate::sync::directory::DirectoryClient;
     use chrono::Utc;
 
     #[test]
@@ -440,4 +686,143 @@ mod tests {
         assert_eq!(should_sync(earlier, later, true), SyncDirection::Upload);
         assert_eq!(should_sync(later, earlier, true), SyncDirection::Upload);
     }
+
+    fn machine(root: &std::path::Path, name: &str) -> (PromptOperations, DirectoryClient) {
+        let mut config = Config::default();
+        config.general.prompt_file = root.join(name).join("prompts.toml");
+        let storage = PromptOperations::new(&config);
+        storage.ensure_storage_exists().unwrap();
+
+        let client = DirectoryClient::new(DirectoryConfig {
+            path: root.join("shared"),
+            file_name: "prompts.toml".to_string(),
+            auto_sync: false,
+        });
+        (storage, client)
+    }
+
+    fn descriptions(storage: &PromptOperations) -> Vec<String> {
+        let mut descriptions: Vec<String> = storage
+            .load_prompts()
+            .unwrap()
+            .prompts
+            .into_iter()
+            .map(|p| p.description)
+            .collect();
+        descriptions.sort();
+        descriptions
+    }
+
+    async fn sync(storage: &PromptOperations, client: &DirectoryClient) {
+        let local = storage.load_prompts().unwrap();
+        let remote = client.get_remote().await.unwrap();
+        merge_with_remote(storage, client, &local, &remote, |_| {
+            Ok(ConflictResolution::Ours)
+        })
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_sync_merges_changes_between_machines() {
+        let dir = tempfile::tempdir().unwrap();
+        let (laptop, laptop_client) = machine(dir.path(), "laptop");
+        let (desktop, desktop_client) = machine(dir.path(), "desktop");
+
+        laptop
+            .add_prompt(Prompt::new(
+                "Review".to_string(),
+                "Review <file>".to_string(),
+            ))
+            .unwrap();
+        sync(&laptop, &laptop_client).await;
+        sync(&desktop, &desktop_client).await;
+        assert_eq!(descriptions(&desktop), vec!["Review"]);
+
+        desktop
+            .add_prompt(Prompt::new(
+                "Summarize".to_string(),
+                "Summarize".to_string(),
+            ))
+            .unwrap();
+        laptop
+            .add_prompt(Prompt::new(
+                "Translate".to_string(),
+                "Translate".to_string(),
+            ))
+            .unwrap();
+        sync(&desktop, &desktop_client).await;
+        sync(&laptop, &laptop_client).await;
+        sync(&desktop, &desktop_client).await;
+
+        let expected = vec!["Review", "Summarize", "Translate"];
+        assert_eq!(descriptions(&laptop), expected);
+        assert_eq!(descriptions(&desktop), expected);
+    }
+
+    #[tokio::test]
+    async fn test_sync_keeps_prompts_added_during_the_fetch() {
+        let dir = tempfile::tempdir().unwrap();
+        let (laptop, laptop_client) = machine(dir.path(), "laptop");
+        let (desktop, desktop_client) = machine(dir.path(), "desktop");
+
+        laptop
+            .add_prompt(Prompt::new("Review".to_string(), "x".to_string()))
+            .unwrap();
+        sync(&laptop, &laptop_client).await;
+
+        let local = desktop.load_prompts().unwrap();
+        let remote = desktop_client.get_remote().await.unwrap();
+        desktop
+            .add_prompt(Prompt::new("Added meanwhile".to_string(), "x".to_string()))
+            .unwrap();
+        let err = merge_with_remote(&desktop, &desktop_client, &local, &remote, |_| {
+            Ok(ConflictResolution::Ours)
+        })
+        .await
+        .unwrap_err();
+        assert!(matches!(err, AppError::Conflict(_)));
+        assert_eq!(descriptions(&desktop), vec!["Added meanwhile"]);
+
+        sync(&desktop, &desktop_client).await;
+        assert_eq!(descriptions(&desktop), vec!["Added meanwhile", "Review"]);
+    }
+
+    #[tokio::test]
+    async fn test_download_overwrites_local_prompts() {
+        let dir = tempfile::tempdir().unwrap();
+        let (laptop, laptop_client) = machine(dir.path(), "laptop");
+        let (desktop, desktop_client) = machine(dir.path(), "desktop");
+
+        laptop
+            .add_prompt(Prompt::new("Review".to_string(), "Review".to_string()))
+            .unwrap();
+        upload_to_remote(
+            &laptop,
+            &laptop_client,
+            &laptop.load_prompts().unwrap(),
+            None,
+        )
+        .await
+        .unwrap();
+
+        desktop
+            .add_prompt(Prompt::new("Local only".to_string(), "x".to_string()))
+            .unwrap();
+        let remote = desktop_client.get_remote().await.unwrap();
+        download_from_remote(&desktop, &remote).await.unwrap();
+
+        assert_eq!(descriptions(&desktop), vec!["Review"]);
+        let base = load_base_snapshot(&base_snapshot_path(&desktop)).unwrap();
+        assert_eq!(base.prompts.len(), 1);
+
+        // The overwritten local prompts can be rolled back
+        let snapshots = list_snapshots(&sync_history_dir(&desktop)).unwrap();
+        assert_eq!(snapshots.len(), 1);
+        assert_eq!(snapshots[0].side, SnapshotSide::Local);
+        handle_sync_rollback(desktop.config(), &snapshots[0].name, false)
+            .await
+            .unwrap();
+        assert_eq!(descriptions(&desktop), vec!["Local only"]);
+    }
 }
@@ -0,0 +1,124 @@
+// Trash operations
+// Lists, restores and purges deleted prompts, and undoes destructive operations
+
+use crate::cli::{TrashArgs, TrashCommands};
+use crate::config::Config;
+use crate::core::operations::PromptOperations;
+use crate::utils::error::{AppError, AppResult};
+use crate::utils::format::format_datetime;
+use crate::utils::{self, OutputStyle, print_success};
+use chrono::Duration;
+
+pub fn handle_trash_command(config: Config, args: &TrashArgs) -> AppResult<()> {
+    let storage = PromptOperations::new(&config);
+    match &args.command {
+        None | Some(TrashCommands::List) => list_trash(&storage),
+        Some(TrashCommands::Restore { identifier }) => {
+            let prompt = storage.restore_from_trash(identifier)?;
+            print_success(&format!("Restored '{}' from the trash", prompt.description));
+            Ok(())
+        }
+        Some(TrashCommands::Empty { force }) => empty_trash(&storage, *force),
+    }
+}
+
+pub fn handle_undo_command(config: Config) -> AppResult<()> {
+    let storage = PromptOperations::new(&config);
+    match storage.undo()? {
+        Some(record) => {
+            print_success(&format!(
+                "Undid {} from {}",
+                record.operation,
+                format_datetime(&record.recorded_at)
+            ));
+            Ok(())
+        }
+        None => Err(AppError::NotFound("Nothing to undo".to_string())),
+    }
+}
+
+fn list_trash(storage: &PromptOperations) -> AppResult<()> {
+    let entries = storage.trash().list()?;
+    if entries.is_empty() {
+        println!("The trash is empty.");
+        return Ok(());
+    }
+
+    let retention_days = storage.config().general.trash_retention_days;
+    OutputStyle::print_header(&format!("Trash ({})", entries.len()));
+    for entry in &entries {
+        let purge = if retention_days > 0 {
+            let purge_at = entry.deleted_at + Duration::days(i64::from(retention_days));
+            format!("purged after {}", format_datetime(&purge_at))
+        } else {
+            String::new()
+        };
+        println!(
+            "{}  {}  deleted {}  {}",
+            OutputStyle::muted(entry.prompt.short_id(8)),
+            OutputStyle::description(&entry.prompt.description),
+            format_datetime(&entry.deleted_at),
+            OutputStyle::muted(&purge)
+        );
+    }
+    println!("\n💡 Restore one with: promptheus trash restore <id>");
+    Ok(())
+}
+
+fn empty_trash(storage: &PromptOperations, force: bool) -> AppResult<()> {
+    if !force && !utils::prompt_yes_no("Permanently delete all prompts in the trash?")? {
+        return Err(AppError::Cancelled("Trash not emptied".to_string()));
+    }
+
+    let count = storage.empty_trash()?;
+    if count == 0 {
+        println!("The trash is already empty.");
+        return Ok(());
+    }
+    print_success(&format!(
+        "Removed {} prompt(s) from the trash; 'promptheus undo' brings them back",
+        count
+    ));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::data::Prompt;
+    use crate::core::traits::{PromptCrud, PromptSearch};
+
+    #[test]
+    fn test_delete_restore_and_undo() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = Config::default();
+        config.general.prompt_file = dir.path().join("prompts.toml");
+        let storage = PromptOperations::new(&config);
+
+        let prompt = Prompt::new("Review".to_string(), "v1".to_string());
+        let id = prompt.id.clone().unwrap();
+        storage.add_prompt(prompt).unwrap();
+
+        storage.delete_prompt(&id).unwrap();
+        assert!(storage.find_prompt(&id).unwrap().is_none());
+        assert_eq!(storage.trash().list().unwrap().len(), 1);
+
+        // Undoing the delete brings the prompt back and clears it from the trash
+        storage.undo().unwrap().unwrap();
+        assert!(storage.find_prompt(&id).unwrap().is_some());
+        assert!(storage.trash().list().unwrap().is_empty());
+        assert!(storage.undo().unwrap().is_none());
+
+        storage.delete_prompt(&id).unwrap();
+        assert_eq!(
+            storage.restore_from_trash("Review").unwrap().id,
+            Some(id.clone())
+        );
+        assert!(storage.find_prompt(&id).unwrap().is_some());
+
+        storage.delete_prompt(&id).unwrap();
+        assert_eq!(storage.empty_trash().unwrap(), 1);
+        storage.undo().unwrap().unwrap();
+        assert_eq!(storage.trash().list().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,271 @@
+//! End-to-end encryption of synced prompt content
+//!
+//! When encryption is configured, the serialized collection is sealed before
+//! it is handed to the sync backend and opened again after it is fetched, so
+//! the remote only ever stores an encrypted envelope. The envelope is TOML
+//! and records its format version and key derivation parameters, which lets
+//! the format or parameters change later without breaking existing remotes.
+
+use super::{RemoteSnippet, SyncClient, get_sync_passphrase};
+use crate::config::EncryptionConfig;
+use crate::utils::error::{AppError, AppResult};
+use crate::utils::output::print_warning;
+use argon2::{Algorithm, Argon2, Params, Version};
+use async_trait::async_trait;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+
+/// Envelope format written by this version
+const ENVELOPE_VERSION: u32 = 1;
+const CIPHER: &str = "xchacha20poly1305";
+const KDF: &str = "argon2id";
+const SALT_LEN: usize = 16;
+
+/// Highest key derivation costs accepted from a remote envelope, well above
+/// the defaults written by [`encrypt`] but low enough that a tampered remote
+/// cannot make every sync allocate gigabytes or spin for minutes
+const MAX_MEMORY_COST: u32 = 256 * 1024;
+const MAX_TIME_COST: u32 = 16;
+const MAX_PARALLELISM: u32 = 8;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct EnvelopeFile {
+    promptheus_encrypted: Envelope,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Envelope {
+    version: u32,
+    cipher: String,
+    kdf: String,
+    memory_cost: u32,
+    time_cost: u32,
+    parallelism: u32,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// Sync client wrapper that encrypts uploads and decrypts fetched content
+pub struct EncryptedClient {
+    inner: Box<dyn SyncClient>,
+    secret: Vec<u8>,
+    allow_plaintext_remote: bool,
+}
+
+impl EncryptedClient {
+    pub fn new(inner: Box<dyn SyncClient>, config: &EncryptionConfig) -> AppResult<Self> {
+        Ok(Self {
+            inner,
+            secret: load_secret(config)?,
+            allow_plaintext_remote: config.allow_plaintext_remote,
+        })
+    }
+}
+
+#[async_trait]
+impl SyncClient for EncryptedClient {
+    async fn get_remote(&self) -> AppResult<RemoteSnippet> {
+        let mut remote = self.inner.get_remote().await?;
+        if self.allow_plaintext_remote && !is_envelope(&remote.content) {
+            print_warning(
+                "Remote prompts are not encrypted; accepting them because allow_plaintext_remote is set. They are encrypted on the next upload.",
+            );
+            return Ok(remote);
+        }
+        remote.content = decrypt(&self.secret, &remote.content)?;
+        Ok(remote)
+    }
+
+    async fn upload(&self, content: String) -> AppResult<()> {
+        self.inner.upload(encrypt(&self.secret, &content)?).await
+    }
+}
+
+/// Read the passphrase or key file named in the config
+fn load_secret(config: &EncryptionConfig) -> AppResult<Vec<u8>> {
+    if let Some(key_file) = &config.key_file {
+        let key = std::fs::read(key_file).map_err(|e| {
+            AppError::io(format!(
+                "Failed to read encryption key file: {}: {}",
+                key_file.display(),
+                e
+            ))
+            .with_source(e)
+        })?;
+        if key.is_empty() {
+            return Err(AppError::system(format!(
+                "Encryption key file is empty: {}",
+                key_file.display()
+            )));
+        }
+        return Ok(key);
+    }
+
+    config
+        .passphrase
+        .clone()
+        .or_else(get_sync_passphrase)
+        .filter(|passphrase| !passphrase.is_empty())
+        .map(String::into_bytes)
+        .ok_or_else(|| {
+            AppError::Auth("Encryption passphrase not found. Set passphrase or key_file in the [encryption] config, or use PROMPTHEUS_SYNC_PASSPHRASE environment variable".to_string())
+        })
+}
+
+fn derive_key(secret: &[u8], salt: &[u8], params: Params) -> AppResult<Key> {
+    let mut key = Key::default();
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(secret, salt, &mut key)
+        .map_err(|e| AppError::system(format!("Failed to derive encryption key: {}", e)))?;
+    Ok(key)
+}
+
+/// Seal content into a new envelope with a fresh salt and nonce
+pub fn encrypt(secret: &[u8], plaintext: &str) -> AppResult<String> {
+    let params = Params::default();
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let key = derive_key(secret, &salt, params.clone())?;
+
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = XChaCha20Poly1305::new(&key)
+        .encrypt(&nonce, plaintext.as_bytes())
+        .map_err(|e| AppError::system(format!("Failed to encrypt prompts: {}", e)))?;
+
+    let envelope = EnvelopeFile {
+        promptheus_encrypted: Envelope {
+            version: ENVELOPE_VERSION,
+            cipher: CIPHER.to_string(),
+            kdf: KDF.to_string(),
+            memory_cost: params.m_cost(),
+            time_cost: params.t_cost(),
+            parallelism: params.p_cost(),
+            salt: BASE64.encode(salt),
+            nonce: BASE64.encode(nonce),
+            ciphertext: BASE64.encode(ciphertext),
+        },
+    };
+
+    toml::to_string_pretty(&envelope).map_err(|e| {
+        AppError::system(format!("Failed to serialize encrypted prompts: {}", e)).with_source(e)
+    })
+}
+
+fn is_envelope(content: &str) -> bool {
+    toml::from_str::<EnvelopeFile>(content).is_ok()
+}
+
+/// Open an envelope
+///
+/// An empty remote has nothing to open. Any other content must be an
+/// envelope, so a remote that was replaced with plaintext is not trusted.
+pub fn decrypt(secret: &[u8], content: &str) -> AppResult<String> {
+    if content.trim().is_empty() {
+        return Ok(content.to_string());
+    }
+    let Ok(EnvelopeFile {
+        promptheus_encrypted: envelope,
+    }) = toml::from_str::<EnvelopeFile>(content)
+    else {
+        return Err(AppError::sync(
+            "Remote prompts are not encrypted although encryption is configured. To encrypt a remote that was synced without encryption, set allow_plaintext_remote = true in the [encryption] config for one sync.".to_string(),
+        ));
+    };
+
+    if envelope.version != ENVELOPE_VERSION || envelope.cipher != CIPHER || envelope.kdf != KDF {
+        return Err(AppError::sync(format!(
+            "Unsupported encryption format (version {}, {}, {}). Please upgrade promptheus.",
+            envelope.version, envelope.cipher, envelope.kdf
+        )));
+    }
+
+    let decode = |field: &str, value: &str| {
+        BASE64.decode(value).map_err(|e| {
+            AppError::sync(format!("Invalid encrypted {}: {}", field, e)).with_source(e)
+        })
+    };
+    let salt = decode("salt", &envelope.salt)?;
+    let nonce = decode("nonce", &envelope.nonce)?;
+    let ciphertext = decode("content", &envelope.ciphertext)?;
+    if nonce.len() != 24 {
+        return Err(AppError::sync("Invalid encrypted nonce length".to_string()));
+    }
+
+    if envelope.memory_cost > MAX_MEMORY_COST
+        || envelope.time_cost > MAX_TIME_COST
+        || envelope.parallelism > MAX_PARALLELISM
+    {
+        return Err(AppError::sync(format!(
+            "Key derivation parameters of the remote are too high (memory {} KiB, {} passes, {} lanes)",
+            envelope.memory_cost, envelope.time_cost, envelope.parallelism
+        )));
+    }
+    let params = Params::new(
+        envelope.memory_cost,
+        envelope.time_cost,
+        envelope.parallelism,
+        None,
+    )
+    .map_err(|e| AppError::sync(format!("Invalid key derivation parameters: {}", e)))?;
+    let key = derive_key(secret, &salt, params)?;
+
+    let plaintext = XChaCha20Poly1305::new(&key)
+        .decrypt(XNonce::from_slice(&nonce), ciphertext.as_slice())
+        .map_err(|_| {
+            AppError::Auth(
+                "Failed to decrypt remote prompts. Check your passphrase or key file.".to_string(),
+            )
+        })?;
+
+    String::from_utf8(plaintext).map_err(|e| {
+        AppError::sync(format!("Decrypted prompts are not valid UTF-8: {}", e)).with_source(e)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROMPTS: &str = "[[prompts]]\nDescription = \"Secret\"\nContent = \"internal\"\n";
+
+    #[test]
+    fn test_encrypt_round_trip_hides_content() {
+        let sealed = encrypt(b"correct horse", PROMPTS).unwrap();
+        assert!(!sealed.contains("internal"));
+        assert!(sealed.contains("version = 1"));
+
+        assert_eq!(decrypt(b"correct horse", &sealed).unwrap(), PROMPTS);
+        assert!(matches!(decrypt(b"wrong", &sealed), Err(AppError::Auth(_))));
+    }
+
+    #[test]
+    fn test_plaintext_and_unknown_versions_fail() {
+        assert!(matches!(
+            decrypt(b"key", PROMPTS),
+            Err(AppError::Sync { .. })
+        ));
+        assert_eq!(decrypt(b"key", "").unwrap(), "");
+
+        let future = encrypt(b"key", PROMPTS)
+            .unwrap()
+            .replace("version = 1", "version = 2");
+        let err = decrypt(b"key", &future).unwrap_err();
+        assert!(err.to_string().contains("version 2"));
+    }
+
+    #[test]
+    fn test_excessive_key_derivation_costs_are_rejected() {
+        let sealed = encrypt(b"key", PROMPTS).unwrap();
+        let memory_cost = format!("memory_cost = {}", Params::default().m_cost());
+        assert!(sealed.contains(&memory_cost));
+
+        let tampered = sealed.replace(&memory_cost, &format!("memory_cost = {}", u32::MAX));
+        let err = decrypt(b"key", &tampered).unwrap_err();
+        assert!(err.to_string().contains("too high"));
+    }
+}
@@ -0,0 +1,130 @@
+use super::{RemoteSnippet, SyncClient};
+use crate::config::DirectoryConfig;
+use crate::utils::error::{AppError, AppResult};
+use crate::utils::file::write_atomic;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::fs;
+use std::path::PathBuf;
+
+/// Sync backend that keeps prompts in a plain directory
+///
+/// Meant for folders shared by other means, such as Dropbox, Syncthing or an
+/// NFS mount. The file modification time stands in for the remote update time.
+pub struct DirectoryClient {
+    config: DirectoryConfig,
+}
+
+impl DirectoryClient {
+    pub fn new(config: DirectoryConfig) -> Self {
+        Self { config }
+    }
+
+    fn file_path(&self) -> PathBuf {
+        self.config.path.join(&self.config.file_name)
+    }
+}
+
+#[async_trait]
+impl SyncClient for DirectoryClient {
+    async fn get_remote(&self) -> AppResult<RemoteSnippet> {
+        let path = self.file_path();
+        if !path.exists() {
+            return Ok(RemoteSnippet {
+                content: String::new(),
+                updated_at: DateTime::default(),
+            });
+        }
+
+        let content = fs::read_to_string(&path).map_err(|e| {
+            AppError::io(format!(
+                "Failed to read synced file: {}: {}",
+                path.display(),
+                e
+            ))
+            .with_source(e)
+        })?;
+        let modified = fs::metadata(&path)
+            .and_then(|metadata| metadata.modified())
+            .map_err(|e| {
+                AppError::io(format!(
+                    "Failed to read modification time: {}: {}",
+                    path.display(),
+                    e
+                ))
+                .with_source(e)
+            })?;
+
+        Ok(RemoteSnippet {
+            content,
+            updated_at: DateTime::<Utc>::from(modified),
+        })
+    }
+
+    async fn upload(&self, content: String) -> AppResult<()> {
+        fs::create_dir_all(&self.config.path).map_err(|e| {
+            AppError::io(format!(
+                "Failed to create sync directory: {}: {}",
+                self.config.path.display(),
+                e
+            ))
+            .with_source(e)
+        })?;
+
+        // Readers of a shared folder must never see a half-written file
+        let path = self.file_path();
+        write_atomic(&path, &content).map_err(|e| {
+            AppError::io(format!(
+                "Failed to write synced file: {}: {}",
+                path.display(),
+                e
+            ))
+            .with_source(e)
+        })?;
+
+        println!("✅ Wrote prompts to {}", path.display());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client(path: PathBuf) -> DirectoryClient {
+        DirectoryClient::new(DirectoryConfig {
+            path,
+            file_name: "prompts.toml".to_string(),
+            auto_sync: false,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_missing_file_is_empty_remote() {
+        let dir = tempfile::tempdir().unwrap();
+        let remote = client(dir.path().join("shared"))
+            .get_remote()
+            .await
+            .unwrap();
+
+        assert!(remote.content.is_empty());
+        assert_eq!(remote.updated_at, DateTime::<Utc>::default());
+    }
+
+    #[tokio::test]
+    async fn test_upload_replaces_file_and_updates_mtime() {
+        let dir = tempfile::tempdir().unwrap();
+        let client = client(dir.path().join("shared"));
+
+        client.upload("prompts = []\n".to_string()).await.unwrap();
+        let remote = client.get_remote().await.unwrap();
+
+        assert_eq!(remote.content, "prompts = []\n");
+        assert!(remote.updated_at > DateTime::<Utc>::default());
+        let entries: Vec<_> = fs::read_dir(dir.path().join("shared"))
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(entries, vec!["prompts.toml"]);
+    }
+}
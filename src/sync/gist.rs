@@ -1,11 +1,15 @@
+use super::http::HttpClient;
 use super::{RemoteSnippet, SyncClient, get_github_token};
-use crate::config::GistConfig;
+use crate::config::{GistConfig, GistLayout, NetworkConfig};
+use crate::core::data::{Prompt, PromptCollection};
 use crate::utils::error::{AppError, AppResult};
+use crate::utils::format::slugify;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
-use reqwest::Client;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::Mutex;
 
 const GITHUB_API_BASE: &str = "https://api.github.com";
 
@@ -18,6 +22,20 @@ struct Gist {
     updated_at: String,
     files: HashMap<String, GistFile>,
     url: String,
+    #[serde(default)]
+    history: Vec<GistHistory>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct GistHistory {
+    version: String,
+}
+
+impl Gist {
+    /// Revision the gist is currently at, newest first in the history
+    fn revision(&self) -> Option<&str> {
+        self.history.first().map(|h| h.version.as_str())
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -26,6 +44,9 @@ struct GistFile {
     content: Option<String>,
     size: i64,
     raw_url: Option<String>,
+    /// Set when the API cut the content short; the full file is at `raw_url`
+    #[serde(default)]
+    truncated: bool,
 }
 
 #[derive(Debug, Serialize)]
@@ -43,38 +64,75 @@ struct GistFileContent {
 #[derive(Debug, Serialize)]
 struct UpdateGistRequest {
     description: Option<String>,
-    files: HashMap<String, GistFileContent>,
+    /// Files to change; `None` deletes the file from the gist
+    files: HashMap<String, Option<GistFileContent>>,
 }
 
 pub struct GistClient {
-    client: Client,
+    http: HttpClient,
     config: GistConfig,
     access_token: String,
+    api_base: String,
+    /// Revision seen by the last fetch; uploads refuse to replace a newer one
+    fetched_revision: Mutex<Option<String>>,
 }
 
 impl GistClient {
-    pub fn new(config: GistConfig) -> AppResult<Self> {
+    pub fn new(config: GistConfig, network: &NetworkConfig) -> AppResult<Self> {
         // Try to get access token from config first, then environment
         let access_token = config.access_token
             .clone()
             .or_else(get_github_token)
             .ok_or_else(|| {
-                AppError::System("GitHub access token not found. Set it in config or use PROMPTHEUS_GITHUB_ACCESS_TOKEN environment variable".to_string())
+                AppError::Auth("GitHub access token not found. Set it in config or use PROMPTHEUS_GITHUB_ACCESS_TOKEN environment variable".to_string())
             })?;
 
         Ok(Self {
-            client: Client::builder()
-                .user_agent("promptheus/0.1.0")
-                .build()
-                .map_err(|e| AppError::Network(format!("Failed to create HTTP client: {}", e)))?,
+            http: HttpClient::new(network, false)?,
             config,
             access_token,
+            api_base: GITHUB_API_BASE.to_string(),
+            fetched_revision: Mutex::new(None),
         })
     }
 
+    fn fetched_revision(&self) -> Option<String> {
+        self.fetched_revision
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    fn set_fetched_revision(&self, revision: Option<&str>) {
+        *self
+            .fetched_revision
+            .lock()
+            .unwrap_or_else(|e| e.into_inner()) = revision.map(String::from);
+    }
+
+    /// Refuse to upload when the gist moved on since it was fetched
+    ///
+    /// GitHub ignores conditional headers on gist updates, so the current
+    /// revision is checked right before the PATCH instead.
+    fn ensure_unchanged_since_fetch(&self, gist: &Gist) -> AppResult<()> {
+        let Some(fetched) = self.fetched_revision() else {
+            return Ok(());
+        };
+
+        match gist.revision() {
+            Some(current) if current != fetched => Err(AppError::Conflict(format!(
+                "Gist was updated elsewhere since it was fetched (revision {} is now {}). Run sync again to merge the new changes.",
+                short_revision(&fetched),
+                short_revision(current)
+            ))),
+            _ => Ok(()),
+        }
+    }
+
     fn parse_gist_timestamp(&self, timestamp_str: &str) -> AppResult<DateTime<Utc>> {
-        let parsed = DateTime::parse_from_rfc3339(timestamp_str)
-            .map_err(|e| AppError::System(format!("Failed to parse gist timestamp: {}", e)))?;
+        let parsed = DateTime::parse_from_rfc3339(timestamp_str).map_err(|e| {
+            AppError::system(format!("Failed to parse gist timestamp: {}", e)).with_source(e)
+        })?;
         Ok(parsed.with_timezone(&Utc))
     }
 
@@ -83,40 +141,31 @@ impl GistClient {
             .config
             .gist_id
             .as_ref()
-            .ok_or_else(|| AppError::Sync("No Gist ID configured".to_string()))?;
+            .ok_or_else(|| AppError::sync("No Gist ID configured".to_string()))?;
 
-        let url = format!("{}/gists/{}", GITHUB_API_BASE, gist_id);
+        let url = format!("{}/gists/{}", self.api_base, gist_id);
 
         let response = self
-            .client
-            .get(&url)
-            .bearer_auth(&self.access_token)
-            .send()
-            .await
-            .map_err(|e| AppError::Network(format!("Failed to fetch gist from GitHub: {}", e)))?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await.unwrap_or_default();
-            return Err(AppError::Network(format!(
-                "Failed to get gist: {} - {}",
-                status, error_text
-            )));
-        }
+            .http
+            .send_checked("get gist", |client| {
+                client.get(&url).bearer_auth(&self.access_token)
+            })
+            .await?;
 
-        let gist: Gist = response
-            .json()
-            .await
-            .map_err(|e| AppError::Network(format!("Failed to parse gist response: {}", e)))?;
+        let gist: Gist = response.json().await.map_err(|e| {
+            AppError::network(format!("Failed to parse gist response: {}", e)).with_source(e)
+        })?;
 
         Ok(gist)
     }
 
     async fn create_gist(&self, content: String) -> AppResult<String> {
-        let url = format!("{}/gists", GITHUB_API_BASE);
+        let url = format!("{}/gists", self.api_base);
 
-        let mut files = HashMap::new();
-        files.insert(self.config.file_name.clone(), GistFileContent { content });
+        let files = split_into_files(self.config.layout, &self.config.file_name, &content)?
+            .into_iter()
+            .map(|(name, content)| (name, GistFileContent { content }))
+            .collect();
 
         let request = CreateGistRequest {
             description: "Promptheus snippets".to_string(),
@@ -125,41 +174,39 @@ impl GistClient {
         };
 
         let response = self
-            .client
-            .post(&url)
-            .bearer_auth(&self.access_token)
-            .json(&request)
-            .send()
-            .await
-            .map_err(|e| AppError::Network(format!("Failed to create gist: {}", e)))?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await.unwrap_or_default();
-            return Err(AppError::Network(format!(
-                "Failed to create gist: {} - {}",
-                status, error_text
-            )));
-        }
+            .http
+            .send_checked("create gist", |client| {
+                client
+                    .post(&url)
+                    .bearer_auth(&self.access_token)
+                    .json(&request)
+            })
+            .await?;
 
         let gist: Gist = response.json().await.map_err(|e| {
-            AppError::Network(format!("Failed to parse create gist response: {}", e))
+            AppError::network(format!("Failed to parse create gist response: {}", e)).with_source(e)
         })?;
 
         Ok(gist.id)
     }
 
-    async fn update_gist(&self, content: String) -> AppResult<()> {
+    /// Update the gist, sending only files that changed; returns whether anything did
+    async fn update_gist(&self, content: String) -> AppResult<bool> {
         let gist_id = self
             .config
             .gist_id
             .as_ref()
-            .ok_or_else(|| AppError::Sync("No Gist ID configured".to_string()))?;
+            .ok_or_else(|| AppError::sync("No Gist ID configured".to_string()))?;
+
+        let gist = self.get_gist().await?;
+        self.ensure_unchanged_since_fetch(&gist)?;
 
-        let url = format!("{}/gists/{}", GITHUB_API_BASE, gist_id);
+        let files = self.changed_files(&gist, &content).await?;
+        if files.is_empty() {
+            return Ok(false);
+        }
 
-        let mut files = HashMap::new();
-        files.insert(self.config.file_name.clone(), GistFileContent { content });
+        let url = format!("{}/gists/{}", self.api_base, gist_id);
 
         let request = UpdateGistRequest {
             description: Some("Promptheus snippets".to_string()),
@@ -167,49 +214,257 @@ impl GistClient {
         };
 
         let response = self
-            .client
-            .patch(&url)
-            .bearer_auth(&self.access_token)
-            .json(&request)
-            .send()
-            .await
-            .map_err(|e| AppError::Network(format!("Failed to update gist: {}", e)))?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await.unwrap_or_default();
-            return Err(AppError::Network(format!(
-                "Failed to update gist: {} - {}",
-                status, error_text
-            )));
+            .http
+            .send_checked("update gist", |client| {
+                client
+                    .patch(&url)
+                    .bearer_auth(&self.access_token)
+                    .json(&request)
+            })
+            .await?;
+
+        // Our own update is the newest revision from here on
+        let gist: Gist = response.json().await.map_err(|e| {
+            AppError::network(format!("Failed to parse update gist response: {}", e)).with_source(e)
+        })?;
+        self.set_fetched_revision(gist.revision());
+
+        Ok(true)
+    }
+
+    /// Files whose content differs from the gist, plus stale layout files to delete
+    async fn changed_files(
+        &self,
+        gist: &Gist,
+        content: &str,
+    ) -> AppResult<HashMap<String, Option<GistFileContent>>> {
+        let desired = split_into_files(self.config.layout, &self.config.file_name, content)?;
+        let mut changes = HashMap::new();
+
+        for name in self.layout_files(gist).await?.into_keys() {
+            if !desired.contains_key(&name) {
+                changes.insert(name, None);
+            }
         }
 
-        Ok(())
+        for (name, wanted) in desired {
+            let current = match gist.files.get(&name) {
+                Some(file) => self.file_content(file).await?,
+                None => None,
+            };
+            if current.as_deref() != Some(wanted.as_str()) {
+                changes.insert(name, Some(GistFileContent { content: wanted }));
+            }
+        }
+
+        Ok(changes)
+    }
+
+    /// Prompt files in the gist written for `file_name`, by any layout
+    async fn layout_files(&self, gist: &Gist) -> AppResult<BTreeMap<String, String>> {
+        let mut files = BTreeMap::new();
+        for (name, file) in &gist.files {
+            if !is_layout_file_name(&self.config.file_name, name) {
+                continue;
+            }
+            let content = self.file_content(file).await?.unwrap_or_default();
+            if is_layout_file(&self.config.file_name, name, &content) {
+                files.insert(name.clone(), content);
+            }
+        }
+        Ok(files)
+    }
+
+    /// Content of a gist file, downloading it when the API response was truncated
+    async fn file_content(&self, file: &GistFile) -> AppResult<Option<String>> {
+        let raw_url = match (&file.raw_url, file.truncated) {
+            (Some(raw_url), true) => raw_url,
+            _ => return Ok(file.content.clone()),
+        };
+
+        let response = self
+            .http
+            .send_checked("fetch gist file", |client| {
+                client.get(raw_url).bearer_auth(&self.access_token)
+            })
+            .await?;
+
+        response.text().await.map(Some).map_err(|e| {
+            AppError::network(format!("Failed to read gist file: {}", e)).with_source(e)
+        })
     }
 
     async fn get_gist_content(&self) -> AppResult<(String, DateTime<Utc>)> {
         let gist = self.get_gist().await?;
 
-        // Find the target file
-        let gist_file = gist.files.get(&self.config.file_name).ok_or_else(|| {
-            AppError::Sync(format!(
-                "File '{}' not found in gist",
-                self.config.file_name
-            ))
-        })?;
-
-        let content = gist_file
-            .content
-            .as_ref()
-            .ok_or_else(|| AppError::Sync("File content is empty".to_string()))?
-            .clone();
+        let content = match self.config.layout {
+            GistLayout::Single => {
+                // Find the target file
+                let gist_file = gist.files.get(&self.config.file_name).ok_or_else(|| {
+                    AppError::sync(format!(
+                        "File '{}' not found in gist",
+                        self.config.file_name
+                    ))
+                })?;
+
+                self.file_content(gist_file)
+                    .await?
+                    .ok_or_else(|| AppError::sync("File content is empty".to_string()))?
+            }
+            GistLayout::Category | GistLayout::Prompt => {
+                let files = self.layout_files(&gist).await?;
+                join_files(files.values().map(String::as_str))?
+            }
+        };
 
         let updated_at = self.parse_gist_timestamp(&gist.updated_at)?;
+        self.set_fetched_revision(gist.revision());
 
         Ok((content, updated_at))
     }
 }
 
+/// Whether a gist file name has the shape of a prompt file for `file_name`
+///
+/// This is `file_name` itself or a `<stem>-<key>.<ext>` file; only
+/// [`is_layout_file`] tells whether such a file was written by us.
+fn is_layout_file_name(file_name: &str, name: &str) -> bool {
+    layout_file_key(file_name, name).is_some() || name == file_name
+}
+
+/// The `<key>` of a `<stem>-<key>.<ext>` gist file name
+fn layout_file_key<'a>(file_name: &str, name: &'a str) -> Option<&'a str> {
+    let (stem, extension) = split_file_name(file_name);
+    name.strip_prefix(stem)?
+        .strip_prefix('-')?
+        .strip_suffix(extension)?
+        .strip_suffix('.')
+}
+
+/// Whether a gist file is one [`split_into_files`] writes for `file_name`
+///
+/// Besides `file_name` itself this covers the files of the per-category and
+/// per-prompt layouts, so switching layouts cleans up files written by the
+/// previous one. Those only count when the prompts in them are the ones
+/// their name stands for, so other files the user keeps in the gist, such
+/// as `prompts-notes.toml`, are neither read nor deleted.
+fn is_layout_file(file_name: &str, name: &str, content: &str) -> bool {
+    if name == file_name {
+        return true;
+    }
+    let Some(key) = layout_file_key(file_name, name) else {
+        return false;
+    };
+    let Ok(collection) = toml::from_str::<PromptCollection>(content) else {
+        return false;
+    };
+
+    let category_file = !collection.prompts.is_empty()
+        && collection
+            .prompts
+            .iter()
+            .all(|prompt| category_key(prompt) == key);
+    let prompt_file = match collection.prompts.as_slice() {
+        [prompt] => {
+            let slug = slugify(&prompt.description);
+            key == slug
+                || key
+                    .strip_prefix(slug.as_str())
+                    .and_then(|rest| rest.strip_prefix('-'))
+                    .is_some_and(|counter| counter.parse::<u32>().is_ok())
+        }
+        _ => false,
+    };
+    category_file || prompt_file
+}
+
+fn split_file_name(file_name: &str) -> (&str, &str) {
+    let path = Path::new(file_name);
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or(file_name);
+    let extension = path.extension().and_then(|s| s.to_str()).unwrap_or("toml");
+    (stem, extension)
+}
+
+/// Spread serialized prompts over gist files according to the layout
+fn split_into_files(
+    layout: GistLayout,
+    file_name: &str,
+    content: &str,
+) -> AppResult<BTreeMap<String, String>> {
+    let mut files = BTreeMap::new();
+    if layout == GistLayout::Single {
+        files.insert(file_name.to_string(), content.to_string());
+        return Ok(files);
+    }
+
+    let collection: PromptCollection = toml::from_str(content).map_err(|e| {
+        AppError::sync(format!("Failed to parse prompts for upload: {}", e)).with_source(e)
+    })?;
+    let (stem, extension) = split_file_name(file_name);
+
+    let mut grouped: BTreeMap<String, PromptCollection> = BTreeMap::new();
+    for prompt in collection.prompts {
+        let key = match layout {
+            GistLayout::Category => category_key(&prompt),
+            _ => slugify(&prompt.description),
+        };
+        let mut name = format!("{}-{}.{}", stem, key, extension);
+
+        // Prompts sharing a description still get a file each
+        if layout == GistLayout::Prompt {
+            let mut counter = 1;
+            while grouped.contains_key(&name) {
+                counter += 1;
+                name = format!("{}-{}-{}.{}", stem, key, counter, extension);
+            }
+        }
+        grouped.entry(name).or_default().prompts.push(prompt);
+    }
+
+    for (name, collection) in grouped {
+        let content = toml::to_string_pretty(&collection).map_err(|e| {
+            AppError::system(format!("Failed to serialize prompts: {}", e)).with_source(e)
+        })?;
+        files.insert(name, content);
+    }
+
+    // A gist needs at least one file, even without prompts
+    if files.is_empty() {
+        files.insert(file_name.to_string(), content.to_string());
+    }
+    Ok(files)
+}
+
+/// Key of the per-category file a prompt goes to
+///
+/// Prompts without a category are saved with an empty one, so both end up
+/// in the same file.
+fn category_key(prompt: &Prompt) -> String {
+    let category = prompt.category.as_deref().filter(|c| !c.is_empty());
+    slugify(category.unwrap_or("uncategorized"))
+}
+
+/// Reassemble prompts spread over several gist files into one collection
+fn join_files<'a>(contents: impl Iterator<Item = &'a str>) -> AppResult<String> {
+    let mut collection = PromptCollection::default();
+    for content in contents.filter(|c| !c.trim().is_empty()) {
+        let part: PromptCollection = toml::from_str(content).map_err(|e| {
+            AppError::sync(format!("Failed to parse gist file: {}", e)).with_source(e)
+        })?;
+        collection.prompts.extend(part.prompts);
+    }
+
+    toml::to_string_pretty(&collection)
+        .map_err(|e| AppError::system(format!("Failed to serialize prompts: {}", e)).with_source(e))
+}
+
+fn short_revision(revision: &str) -> &str {
+    revision.get(..7).unwrap_or(revision)
+}
+
 #[async_trait]
 impl SyncClient for GistClient {
     async fn get_remote(&self) -> AppResult<RemoteSnippet> {
@@ -231,8 +486,11 @@ impl SyncClient for GistClient {
             );
         } else {
             // Update existing gist
-            self.update_gist(content).await?;
-            println!("✅ Updated existing gist");
+            if self.update_gist(content).await? {
+                println!("✅ Updated existing gist");
+            } else {
+                println!("✅ Gist already up to date");
+            }
         }
         Ok(())
     }
@@ -242,19 +500,54 @@ impl SyncClient for GistClient {
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_parse_gist_timestamp() {
-        let client = GistClient {
-            client: Client::new(),
-            config: GistConfig {
+    const PROMPTS: &str = r#"
+[[prompts]]
+Description = "Review code"
+Content = "Review <file>"
+Category = "dev"
+Created_at = "2024-01-01 10:00:00"
+
+[[prompts]]
+Description = "Summarize"
+Content = "Summarize <text>"
+Created_at = "2024-01-02 10:00:00"
+"#;
+
+    fn test_client(api_base: String) -> GistClient {
+        layout_client(api_base, GistLayout::Single)
+    }
+
+    fn layout_client(api_base: String, layout: GistLayout) -> GistClient {
+        let mut client = GistClient::new(
+            GistConfig {
                 file_name: "test.toml".to_string(),
                 access_token: Some("test".to_string()),
                 gist_id: Some("test".to_string()),
                 public: false,
                 auto_sync: false,
+                layout,
             },
-            access_token: "test".to_string(),
-        };
+            &NetworkConfig::default(),
+        )
+        .unwrap();
+        client.api_base = api_base;
+        client
+    }
+
+    fn gist_body(revision: &str) -> String {
+        format!(
+            r#"{{"id": "test", "description": null, "public": false,
+                "created_at": "2023-01-01T00:00:00Z", "updated_at": "2023-01-02T00:00:00Z",
+                "url": "https://api.github.com/gists/test",
+                "files": {{"test.toml": {{"filename": "test.toml", "content": "prompts = []", "size": 12, "raw_url": null}}}},
+                "history": [{{"version": "{}"}}, {{"version": "older"}}]}}"#,
+            revision
+        )
+    }
+
+    #[test]
+    fn test_parse_gist_timestamp() {
+        let client = test_client(GITHUB_API_BASE.to_string());
 
         // This test would require a real timestamp string
         // In a real scenario, you'd test with actual RFC3339 timestamps
@@ -262,4 +555,169 @@ mod tests {
         let result = client.parse_gist_timestamp(timestamp);
         assert!(result.is_ok());
     }
+
+    #[tokio::test]
+    async fn test_upload_after_fetch_checks_revision() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/gists/test")
+            .with_body(gist_body("rev1"))
+            .expect(2)
+            .create_async()
+            .await;
+        let patch = server
+            .mock("PATCH", "/gists/test")
+            .with_body(gist_body("rev2"))
+            .create_async()
+            .await;
+
+        let client = test_client(server.url());
+        client.get_remote().await.unwrap();
+        client
+            .upload("prompts = [] # edited".to_string())
+            .await
+            .unwrap();
+
+        patch.assert_async().await;
+        assert_eq!(client.fetched_revision().as_deref(), Some("rev2"));
+    }
+
+    #[tokio::test]
+    async fn test_upload_refuses_when_gist_moved() {
+        let mut server = mockito::Server::new_async().await;
+        let first = server
+            .mock("GET", "/gists/test")
+            .with_body(gist_body("rev1"))
+            .create_async()
+            .await;
+
+        let client = test_client(server.url());
+        client.get_remote().await.unwrap();
+        first.remove_async().await;
+
+        server
+            .mock("GET", "/gists/test")
+            .with_body(gist_body("rev2"))
+            .create_async()
+            .await;
+        let patch = server
+            .mock("PATCH", "/gists/test")
+            .expect(0)
+            .create_async()
+            .await;
+
+        let err = client.upload("prompts = []".to_string()).await.unwrap_err();
+        assert!(matches!(err, AppError::Conflict(_)));
+        patch.assert_async().await;
+    }
+
+    #[test]
+    fn test_split_into_category_files_and_join_back() {
+        let files = split_into_files(GistLayout::Category, "test.toml", PROMPTS).unwrap();
+        let names: Vec<&str> = files.keys().map(String::as_str).collect();
+        assert_eq!(names, vec!["test-dev.toml", "test-uncategorized.toml"]);
+        assert!(
+            files
+                .iter()
+                .all(|(name, content)| is_layout_file("test.toml", name, content))
+        );
+        assert!(!is_layout_file("test.toml", "notes.md", "keep me"));
+        assert!(!is_layout_file(
+            "test.toml",
+            "test-readme.toml",
+            "title = \"x\""
+        ));
+        assert!(!is_layout_file(
+            "test.toml",
+            "test-readme.toml",
+            &files["test-dev.toml"]
+        ));
+
+        let joined = join_files(files.values().map(String::as_str)).unwrap();
+        let collection: PromptCollection = toml::from_str(&joined).unwrap();
+        assert_eq!(collection.prompts.len(), 2);
+
+        let per_prompt = split_into_files(GistLayout::Prompt, "test.toml", PROMPTS).unwrap();
+        assert!(per_prompt.contains_key("test-review-code.toml"));
+        assert!(per_prompt.contains_key("test-summarize.toml"));
+        assert!(per_prompt.iter().all(|(name, content)| is_layout_file(
+            "test.toml",
+            name,
+            content
+        )));
+    }
+
+    #[tokio::test]
+    async fn test_upload_keeps_unrelated_files() {
+        let gist = serde_json::json!({
+            "id": "test", "description": null, "public": false,
+            "created_at": "2023-01-01T00:00:00Z", "updated_at": "2023-01-02T00:00:00Z",
+            "url": "https://api.github.com/gists/test",
+            "files": {
+                "test.toml": {"filename": "test.toml", "content": "prompts = []", "size": 12, "raw_url": null},
+                "test-readme.toml": {"filename": "test-readme.toml", "content": "title = \"Notes\"", "size": 15, "raw_url": null}
+            },
+            "history": [{"version": "rev1"}]
+        });
+
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/gists/test")
+            .with_body(gist.to_string())
+            .create_async()
+            .await;
+        let patch = server
+            .mock("PATCH", "/gists/test")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "description": "Promptheus snippets",
+                "files": {"test.toml": {"content": PROMPTS}}
+            })))
+            .with_body(gist.to_string())
+            .create_async()
+            .await;
+
+        let client = test_client(server.url());
+        client.upload(PROMPTS.to_string()).await.unwrap();
+        patch.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_update_patches_only_changed_files() {
+        let files = split_into_files(GistLayout::Category, "test.toml", PROMPTS).unwrap();
+        let gist = serde_json::json!({
+            "id": "test", "description": null, "public": false,
+            "created_at": "2023-01-01T00:00:00Z", "updated_at": "2023-01-02T00:00:00Z",
+            "url": "https://api.github.com/gists/test",
+            "files": {
+                "test.toml": {"filename": "test.toml", "content": "prompts = []", "size": 12, "raw_url": null},
+                "test-dev.toml": {"filename": "test-dev.toml", "content": files["test-dev.toml"], "size": 1, "raw_url": null},
+                "notes.md": {"filename": "notes.md", "content": "keep me", "size": 7, "raw_url": null},
+                "test-readme.toml": {"filename": "test-readme.toml", "content": "title = \"Notes\"", "size": 15, "raw_url": null}
+            },
+            "history": [{"version": "rev1"}]
+        });
+
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/gists/test")
+            .with_body(gist.to_string())
+            .create_async()
+            .await;
+        let patch = server
+            .mock("PATCH", "/gists/test")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "description": "Promptheus snippets",
+                "files": {
+                    "test.toml": null,
+                    "test-uncategorized.toml": {"content": files["test-uncategorized.toml"]}
+                }
+            })))
+            .with_body(gist.to_string())
+            .create_async()
+            .await;
+
+        let client = layout_client(server.url(), GistLayout::Category);
+        client.upload(PROMPTS.to_string()).await.unwrap();
+        patch.assert_async().await;
+    }
 }
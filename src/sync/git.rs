@@ -0,0 +1,439 @@
+use super::{RemoteSnippet, SyncClient};
+use crate::config::GitConfig;
+use crate::core::data::PromptCollection;
+use crate::utils::error::{AppError, AppResult};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tokio::process::Command;
+
+/// Identity used for sync commits when git has no user configured
+const FALLBACK_USER_NAME: &str = "promptheus";
+const FALLBACK_USER_EMAIL: &str = "promptheus@localhost";
+
+/// Sync backend that keeps prompts in a git repository
+///
+/// The repository is cloned into a working directory under the config
+/// directory. Every upload is a single commit pushed to the configured branch.
+pub struct GitClient {
+    config: GitConfig,
+    work_dir: PathBuf,
+    /// Commit of the remote branch the last fetch saw, `Some(None)` when the
+    /// branch did not exist yet; uploads are committed on top of it
+    fetched_commit: Mutex<Option<Option<String>>>,
+}
+
+impl GitClient {
+    pub fn new(config: GitConfig, work_dir: PathBuf) -> Self {
+        Self {
+            config,
+            work_dir,
+            fetched_commit: Mutex::new(None),
+        }
+    }
+
+    fn set_fetched_commit(&self, commit: Option<String>) {
+        *self
+            .fetched_commit
+            .lock()
+            .unwrap_or_else(|e| e.into_inner()) = Some(commit);
+    }
+
+    fn fetched_commit(&self) -> Option<Option<String>> {
+        self.fetched_commit
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    fn file_path(&self) -> PathBuf {
+        self.work_dir.join(&self.config.file_name)
+    }
+
+    fn remote_branch(&self) -> String {
+        format!("origin/{}", self.config.branch)
+    }
+
+    /// Run git in the working directory and return its trimmed stdout
+    async fn git(&self, args: &[&str]) -> AppResult<String> {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(&self.work_dir)
+            .args(args)
+            .output()
+            .await
+            .map_err(|e| AppError::system(format!("Failed to run git: {}", e)).with_source(e))?;
+
+        if !output.status.success() {
+            return Err(AppError::sync(format!(
+                "git {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    /// Clone the repository on first use, then bring it up to date with the remote
+    ///
+    /// A clone of a different repository, left behind when `repository`
+    /// changed in the config, is replaced by a fresh clone.
+    async fn update_work_dir(&self) -> AppResult<()> {
+        if self.work_dir.join(".git").exists() {
+            let origin = self.git(&["remote", "get-url", "origin"]).await.ok();
+            if origin.as_deref() != Some(self.config.repository.as_str()) {
+                std::fs::remove_dir_all(&self.work_dir).map_err(|e| {
+                    AppError::io(format!(
+                        "Failed to remove outdated sync clone: {}: {}",
+                        self.work_dir.display(),
+                        e
+                    ))
+                    .with_source(e)
+                })?;
+            }
+        }
+
+        if !self.work_dir.join(".git").exists() {
+            if let Some(parent) = self.work_dir.parent() {
+                std::fs::create_dir_all(parent).map_err(|e| {
+                    AppError::io(format!(
+                        "Failed to create sync directory: {}: {}",
+                        parent.display(),
+                        e
+                    ))
+                    .with_source(e)
+                })?;
+            }
+
+            let output = Command::new("git")
+                .arg("clone")
+                .arg(&self.config.repository)
+                .arg(&self.work_dir)
+                .output()
+                .await
+                .map_err(|e| {
+                    AppError::system(format!("Failed to run git: {}", e)).with_source(e)
+                })?;
+
+            if !output.status.success() {
+                return Err(AppError::sync(format!(
+                    "Failed to clone {}: {}",
+                    self.config.repository,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                )));
+            }
+        }
+
+        self.git(&["fetch", "origin"]).await?;
+
+        // Uploads are pushed immediately, so the working copy never holds
+        // anything worth keeping over the remote branch
+        if self.has_remote_branch().await {
+            self.git(&["checkout", "-B", &self.config.branch, &self.remote_branch()])
+                .await?;
+            self.git(&["reset", "--hard", &self.remote_branch()])
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn has_remote_branch(&self) -> bool {
+        self.git(&["rev-parse", "--verify", "--quiet", &self.remote_branch()])
+            .await
+            .is_ok()
+    }
+
+    /// Time of the last commit touching the prompt file
+    async fn last_commit_time(&self) -> AppResult<DateTime<Utc>> {
+        if !self.has_remote_branch().await {
+            return Ok(DateTime::default());
+        }
+
+        let timestamp = self
+            .git(&["log", "-1", "--format=%cI", "--", &self.config.file_name])
+            .await?;
+
+        if timestamp.is_empty() {
+            return Ok(DateTime::default());
+        }
+
+        let parsed = DateTime::parse_from_rfc3339(&timestamp).map_err(|e| {
+            AppError::system(format!("Failed to parse commit timestamp: {}", e)).with_source(e)
+        })?;
+        Ok(parsed.with_timezone(&Utc))
+    }
+
+    fn read_file(&self) -> AppResult<String> {
+        let path = self.file_path();
+        if !path.exists() {
+            return Ok(String::new());
+        }
+
+        std::fs::read_to_string(&path).map_err(|e| {
+            AppError::io(format!(
+                "Failed to read synced file: {}: {}",
+                path.display(),
+                e
+            ))
+            .with_source(e)
+        })
+    }
+
+    /// Push the new commit, failing with `Conflict` when the branch moved on
+    async fn push(&self) -> AppResult<()> {
+        let refspec = format!("HEAD:refs/heads/{}", self.config.branch);
+        match self.git(&["push", "origin", &refspec]).await {
+            Ok(_) => {
+                self.set_fetched_commit(Some(self.git(&["rev-parse", "HEAD"]).await?));
+                Ok(())
+            }
+            Err(AppError::Sync { message, .. }) if message.contains("[rejected]") => {
+                Err(AppError::Conflict(format!(
+                    "Branch '{}' of {} changed since it was fetched. Run sync again to merge the new changes.",
+                    self.config.branch, self.config.repository
+                )))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn commit(&self, message: &str) -> AppResult<()> {
+        let mut args = Vec::new();
+        if self.git(&["config", "user.email"]).await.is_err() {
+            args.extend([
+                "-c".to_string(),
+                format!("user.name={}", FALLBACK_USER_NAME),
+                "-c".to_string(),
+                format!("user.email={}", FALLBACK_USER_EMAIL),
+            ]);
+        }
+        args.extend(["commit".to_string(), "-m".to_string(), message.to_string()]);
+
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        self.git(&args).await.map(|_| ())
+    }
+}
+
+/// Build a commit message listing the prompts that changed
+fn commit_message(previous: &str, content: &str) -> String {
+    let mut old = toml::from_str::<PromptCollection>(previous).unwrap_or_default();
+    let mut new = toml::from_str::<PromptCollection>(content).unwrap_or_default();
+    new.ensure_ids();
+    old.adopt_ids_from(&new);
+    old.ensure_ids();
+
+    let mut added = Vec::new();
+    let mut modified = Vec::new();
+    for prompt in &new.prompts {
+        match prompt.id.as_deref().and_then(|id| old.find_by_id(id)) {
+            None => added.push(prompt.description.as_str()),
+            Some(existing) if !existing.same_content(prompt) => {
+                modified.push(prompt.description.as_str())
+            }
+            Some(_) => {}
+        }
+    }
+    let removed: Vec<&str> = old
+        .prompts
+        .iter()
+        .filter(|p| p.id.as_deref().and_then(|id| new.find_by_id(id)).is_none())
+        .map(|p| p.description.as_str())
+        .collect();
+
+    let mut message = format!(
+        "Update prompts ({} added, {} modified, {} removed)\n",
+        added.len(),
+        modified.len(),
+        removed.len()
+    );
+    for (label, descriptions) in [
+        ("Added", &added),
+        ("Modified", &modified),
+        ("Removed", &removed),
+    ] {
+        if !descriptions.is_empty() {
+            message.push_str(&format!("\n{}: {}", label, descriptions.join(", ")));
+        }
+    }
+    message
+}
+
+#[async_trait]
+impl SyncClient for GitClient {
+    async fn get_remote(&self) -> AppResult<RemoteSnippet> {
+        self.update_work_dir().await?;
+        let commit = match self.has_remote_branch().await {
+            true => Some(self.git(&["rev-parse", &self.remote_branch()]).await?),
+            false => None,
+        };
+        self.set_fetched_commit(commit);
+
+        Ok(RemoteSnippet {
+            content: self.read_file()?,
+            updated_at: self.last_commit_time().await?,
+        })
+    }
+
+    async fn upload(&self, content: String) -> AppResult<()> {
+        // Commit on top of what was fetched, so the push fails instead of
+        // overwriting commits pushed elsewhere in the meantime
+        match self.fetched_commit() {
+            Some(Some(commit)) => {
+                self.git(&["checkout", "-B", &self.config.branch, &commit])
+                    .await?;
+                self.git(&["reset", "--hard", &commit]).await?;
+            }
+            Some(None) => {}
+            None => self.update_work_dir().await?,
+        }
+
+        let previous = self.read_file()?;
+        if previous == content {
+            println!("✅ Repository already up to date");
+            return Ok(());
+        }
+
+        let path = self.file_path();
+        std::fs::write(&path, &content).map_err(|e| {
+            AppError::io(format!(
+                "Failed to write synced file: {}: {}",
+                path.display(),
+                e
+            ))
+            .with_source(e)
+        })?;
+
+        self.git(&["add", "--", &self.config.file_name]).await?;
+        self.commit(&commit_message(&previous, &content)).await?;
+        self.push().await?;
+
+        println!("✅ Pushed prompts to {}", self.config.repository);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command as StdCommand;
+
+    const PROMPTS: &str = r#"
+[[prompts]]
+Description = "Review"
+Content = "Review <file>"
+Created_at = "2024-01-01 10:00:00"
+"#;
+
+    fn bare_repo(dir: &std::path::Path) -> String {
+        let repo = dir.join("remote.git");
+        let status = StdCommand::new("git")
+            .args(["init", "--bare", "--quiet"])
+            .arg(&repo)
+            .status()
+            .unwrap();
+        assert!(status.success());
+        repo.to_string_lossy().to_string()
+    }
+
+    fn client(repository: &str, work_dir: PathBuf) -> GitClient {
+        GitClient::new(
+            GitConfig {
+                repository: repository.to_string(),
+                branch: "main".to_string(),
+                file_name: "prompts.toml".to_string(),
+                auto_sync: false,
+            },
+            work_dir,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_upload_and_fetch_through_bare_repo() {
+        let dir = tempfile::tempdir().unwrap();
+        let repository = bare_repo(dir.path());
+
+        let first = client(&repository, dir.path().join("first"));
+        let empty = first.get_remote().await.unwrap();
+        assert!(empty.content.is_empty());
+        assert_eq!(empty.updated_at, DateTime::<Utc>::default());
+
+        first.upload(PROMPTS.to_string()).await.unwrap();
+
+        let second = client(&repository, dir.path().join("second"));
+        let remote = second.get_remote().await.unwrap();
+        assert_eq!(remote.content, PROMPTS);
+        assert!(remote.updated_at > DateTime::<Utc>::default());
+
+        let log = second.git(&["log", "-1", "--format=%B"]).await.unwrap();
+        assert!(log.starts_with("Update prompts (1 added, 0 modified, 0 removed)"));
+        assert!(log.contains("Added: Review"));
+    }
+
+    #[tokio::test]
+    async fn test_upload_after_remote_moved_is_a_conflict() {
+        let dir = tempfile::tempdir().unwrap();
+        let repository = bare_repo(dir.path());
+
+        let first = client(&repository, dir.path().join("first"));
+        first.get_remote().await.unwrap();
+        first.upload(PROMPTS.to_string()).await.unwrap();
+
+        let second = client(&repository, dir.path().join("second"));
+        second.get_remote().await.unwrap();
+        first.get_remote().await.unwrap();
+        first
+            .upload(PROMPTS.replace("Review <file>", "Review <file> again"))
+            .await
+            .unwrap();
+
+        let err = second
+            .upload(PROMPTS.replace("Review <file>", "Review"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::Conflict(_)));
+
+        // After fetching again the upload goes through
+        second.get_remote().await.unwrap();
+        second
+            .upload(PROMPTS.replace("Review <file>", "Review"))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_changed_repository_is_cloned_again() {
+        let dir = tempfile::tempdir().unwrap();
+        let old_repository = bare_repo(&dir.path().join("old"));
+        let new_repository = bare_repo(&dir.path().join("new"));
+        let work_dir = dir.path().join("clone");
+
+        let old = client(&old_repository, work_dir.clone());
+        old.get_remote().await.unwrap();
+        old.upload(PROMPTS.to_string()).await.unwrap();
+
+        let new = client(&new_repository, work_dir);
+        assert!(new.get_remote().await.unwrap().content.is_empty());
+        new.upload(PROMPTS.to_string()).await.unwrap();
+        let origin = new.git(&["remote", "get-url", "origin"]).await.unwrap();
+        assert_eq!(origin, new_repository);
+    }
+
+    #[test]
+    fn test_commit_message_lists_changes() {
+        let updated = PROMPTS.replace("Review <file>", "Review <file> carefully")
+            + r#"
+[[prompts]]
+Description = "Summarize"
+Content = "Summarize <text>"
+Created_at = "2024-01-02 10:00:00"
+"#;
+
+        let message = commit_message(PROMPTS, &updated);
+        assert!(message.starts_with("Update prompts (1 added, 1 modified, 0 removed)"));
+        assert!(message.contains("Added: Summarize"));
+        assert!(message.contains("Modified: Review"));
+    }
+}
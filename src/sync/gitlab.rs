@@ -0,0 +1,304 @@
+use super::http::HttpClient;
+use super::{RemoteSnippet, SyncClient, get_gitlab_token};
+use crate::config::{GitLabConfig, NetworkConfig};
+use crate::utils::error::{AppError, AppResult};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use reqwest::{Client, RequestBuilder, Response};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize)]
+struct Snippet {
+    id: i64,
+    updated_at: String,
+}
+
+#[derive(Debug, Serialize)]
+struct CreateSnippetRequest {
+    title: String,
+    visibility: String,
+    files: Vec<SnippetFile>,
+}
+
+#[derive(Debug, Serialize)]
+struct UpdateSnippetRequest {
+    files: Vec<SnippetFile>,
+}
+
+#[derive(Debug, Serialize)]
+struct SnippetFile {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    action: Option<String>,
+    file_path: String,
+    content: String,
+}
+
+pub struct GitLabClient {
+    http: HttpClient,
+    config: GitLabConfig,
+    access_token: String,
+}
+
+impl GitLabClient {
+    pub fn new(config: GitLabConfig, network: &NetworkConfig) -> AppResult<Self> {
+        // Try to get access token from config first, then environment
+        let access_token = config.access_token
+            .clone()
+            .or_else(get_gitlab_token)
+            .ok_or_else(|| {
+                AppError::Auth("GitLab access token not found. Set it in config or use PROMPTHEUS_GITLAB_ACCESS_TOKEN environment variable".to_string())
+            })?;
+
+        Ok(Self {
+            http: HttpClient::new(network, config.skip_ssl)?,
+            config,
+            access_token,
+        })
+    }
+
+    /// Snippet collection endpoint, scoped to a project when one is configured
+    fn snippets_url(&self) -> String {
+        let api_base = format!("{}/api/v4", self.config.url.trim_end_matches('/'));
+        match &self.config.project {
+            Some(project) => format!(
+                "{}/projects/{}/snippets",
+                api_base,
+                encode_path_segment(project)
+            ),
+            None => format!("{}/snippets", api_base),
+        }
+    }
+
+    fn snippet_id(&self) -> AppResult<i32> {
+        self.config
+            .id
+            .ok_or_else(|| AppError::sync("No GitLab snippet ID configured".to_string()))
+    }
+
+    async fn send<F>(&self, action: &str, build: F) -> AppResult<Response>
+    where
+        F: Fn(&Client) -> RequestBuilder,
+    {
+        self.http
+            .send_checked(action, |client| {
+                build(client).header("PRIVATE-TOKEN", &self.access_token)
+            })
+            .await
+    }
+
+    async fn get_snippet(&self, id: i32) -> AppResult<Snippet> {
+        let url = format!("{}/{}", self.snippets_url(), id);
+        let response = self
+            .send("get snippet from GitLab", |client| client.get(&url))
+            .await?;
+
+        response.json().await.map_err(|e| {
+            AppError::network(format!("Failed to parse snippet response: {}", e)).with_source(e)
+        })
+    }
+
+    async fn get_snippet_content(&self, id: i32) -> AppResult<String> {
+        let url = format!("{}/{}/raw", self.snippets_url(), id);
+        let response = self
+            .send("get snippet content from GitLab", |client| client.get(&url))
+            .await?;
+
+        response.text().await.map_err(|e| {
+            AppError::network(format!("Failed to read snippet content: {}", e)).with_source(e)
+        })
+    }
+
+    async fn create_snippet(&self, content: String) -> AppResult<i64> {
+        let request = CreateSnippetRequest {
+            title: "Promptheus snippets".to_string(),
+            visibility: self.config.visibility.clone(),
+            files: vec![SnippetFile {
+                action: None,
+                file_path: self.config.file_name.clone(),
+                content,
+            }],
+        };
+
+        let response = self
+            .send("create snippet", |client| {
+                client.post(self.snippets_url()).json(&request)
+            })
+            .await?;
+
+        let snippet: Snippet = response.json().await.map_err(|e| {
+            AppError::network(format!("Failed to parse create snippet response: {}", e))
+                .with_source(e)
+        })?;
+
+        Ok(snippet.id)
+    }
+
+    async fn update_snippet(&self, id: i32, content: String) -> AppResult<()> {
+        let url = format!("{}/{}", self.snippets_url(), id);
+        let request = UpdateSnippetRequest {
+            files: vec![SnippetFile {
+                action: Some("update".to_string()),
+                file_path: self.config.file_name.clone(),
+                content,
+            }],
+        };
+
+        self.send("update snippet", |client| client.put(&url).json(&request))
+            .await?;
+        Ok(())
+    }
+}
+
+/// Percent-encode a project path such as `group/project` for use in a URL
+fn encode_path_segment(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+fn parse_snippet_timestamp(timestamp_str: &str) -> AppResult<DateTime<Utc>> {
+    let parsed = DateTime::parse_from_rfc3339(timestamp_str).map_err(|e| {
+        AppError::system(format!("Failed to parse snippet timestamp: {}", e)).with_source(e)
+    })?;
+    Ok(parsed.with_timezone(&Utc))
+}
+
+#[async_trait]
+impl SyncClient for GitLabClient {
+    async fn get_remote(&self) -> AppResult<RemoteSnippet> {
+        let id = self.snippet_id()?;
+        let snippet = self.get_snippet(id).await?;
+        let content = self.get_snippet_content(id).await?;
+
+        Ok(RemoteSnippet {
+            content,
+            updated_at: parse_snippet_timestamp(&snippet.updated_at)?,
+        })
+    }
+
+    async fn upload(&self, content: String) -> AppResult<()> {
+        match self.config.id {
+            Some(id) => {
+                self.update_snippet(id, content).await?;
+                println!("✅ Updated existing snippet");
+            }
+            None => {
+                let id = self.create_snippet(content).await?;
+                println!("✅ Created new snippet: {}", id);
+                println!("💡 Add this snippet ID to your config file: id = {}", id);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Matcher;
+
+    fn client_for(url: String, id: Option<i32>, project: Option<&str>) -> GitLabClient {
+        GitLabClient::new(
+            GitLabConfig {
+                file_name: "prompts.toml".to_string(),
+                access_token: Some("token".to_string()),
+                url,
+                id,
+                project: project.map(String::from),
+                visibility: "private".to_string(),
+                auto_sync: false,
+                skip_ssl: false,
+            },
+            &NetworkConfig::default(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_encode_path_segment() {
+        assert_eq!(
+            encode_path_segment("group/sub project"),
+            "group%2Fsub%20project"
+        );
+        assert_eq!(encode_path_segment("42"), "42");
+    }
+
+    #[tokio::test]
+    async fn test_get_remote_fetches_metadata_and_content() {
+        let mut server = mockito::Server::new_async().await;
+        let metadata = server
+            .mock("GET", "/api/v4/snippets/7")
+            .match_header("PRIVATE-TOKEN", "token")
+            .with_body(r#"{"id": 7, "updated_at": "2024-05-01T12:00:00.000Z"}"#)
+            .create_async()
+            .await;
+        let raw = server
+            .mock("GET", "/api/v4/snippets/7/raw")
+            .with_body("prompts = []\n")
+            .create_async()
+            .await;
+
+        let client = client_for(server.url(), Some(7), None);
+        let remote = client.get_remote().await.unwrap();
+
+        metadata.assert_async().await;
+        raw.assert_async().await;
+        assert_eq!(remote.content, "prompts = []\n");
+        assert_eq!(
+            remote.updated_at,
+            "2024-05-01T12:00:00Z".parse::<DateTime<Utc>>().unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_upload_creates_project_snippet_and_updates_existing() {
+        let mut server = mockito::Server::new_async().await;
+        let create = server
+            .mock("POST", "/api/v4/projects/team%2Fprompts/snippets")
+            .match_body(Matcher::PartialJsonString(
+                r#"{"visibility": "private", "files": [{"file_path": "prompts.toml"}]}"#
+                    .to_string(),
+            ))
+            .with_status(201)
+            .with_body(r#"{"id": 9, "updated_at": "2024-05-01T12:00:00Z"}"#)
+            .create_async()
+            .await;
+        let update = server
+            .mock("PUT", "/api/v4/snippets/9")
+            .match_body(Matcher::PartialJsonString(
+                r#"{"files": [{"action": "update", "content": "prompts = []"}]}"#.to_string(),
+            ))
+            .with_body(r#"{"id": 9, "updated_at": "2024-05-01T12:00:00Z"}"#)
+            .create_async()
+            .await;
+
+        let creator = client_for(server.url(), None, Some("team/prompts"));
+        creator.upload("prompts = []".to_string()).await.unwrap();
+        create.assert_async().await;
+
+        let updater = client_for(server.url(), Some(9), None);
+        updater.upload("prompts = []".to_string()).await.unwrap();
+        update.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_failed_request_reports_status() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/api/v4/snippets/7")
+            .with_status(404)
+            .with_body("404 Not found")
+            .create_async()
+            .await;
+
+        let client = client_for(server.url(), Some(7), None);
+        let err = client.get_remote().await.unwrap_err();
+        assert!(err.to_string().contains("404"));
+    }
+}
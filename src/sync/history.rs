@@ -0,0 +1,251 @@
+//! Backups of prompts replaced by sync
+//!
+//! Before a sync overwrites the local prompt file or replaces the remote
+//! copy, the previous state is written to a timestamped snapshot so that a
+//! bad sync can be rolled back.
+
+use crate::core::data::PromptCollection;
+use crate::utils::error::{AppError, AppResult};
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Number of snapshots kept before the oldest are removed
+const MAX_SNAPSHOTS: usize = 50;
+
+const TIMESTAMP_FORMAT: &str = "%Y%m%d-%H%M%S";
+
+/// Which side a snapshot was taken from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SnapshotSide {
+    /// Local prompts, saved before sync overwrote them
+    Local,
+    /// Remote prompts, saved before an upload replaced them
+    Remote,
+}
+
+impl SnapshotSide {
+    fn as_str(&self) -> &'static str {
+        match self {
+            SnapshotSide::Local => "local",
+            SnapshotSide::Remote => "remote",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "local" => Some(SnapshotSide::Local),
+            "remote" => Some(SnapshotSide::Remote),
+            _ => None,
+        }
+    }
+}
+
+/// A backup kept in the sync history directory
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub name: String,
+    pub side: SnapshotSide,
+    pub created_at: DateTime<Utc>,
+    pub path: PathBuf,
+}
+
+impl Snapshot {
+    fn from_path(path: &Path) -> Option<Self> {
+        if path.extension()? != "toml" {
+            return None;
+        }
+        let name = path.file_stem()?.to_str()?.to_string();
+
+        // <date>-<time>-<side>, optionally followed by -<n> for duplicates
+        let mut parts = name.splitn(4, '-');
+        let timestamp = format!("{}-{}", parts.next()?, parts.next()?);
+        let side = SnapshotSide::parse(parts.next()?)?;
+        let created_at = NaiveDateTime::parse_from_str(&timestamp, TIMESTAMP_FORMAT).ok()?;
+
+        Some(Self {
+            name,
+            side,
+            created_at: Local
+                .from_local_datetime(&created_at)
+                .earliest()?
+                .with_timezone(&Utc),
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// Load the prompts stored in the snapshot
+    pub fn load(&self) -> AppResult<PromptCollection> {
+        let content = fs::read_to_string(&self.path).map_err(|e| {
+            AppError::io(format!(
+                "Failed to read snapshot: {}: {}",
+                self.path.display(),
+                e
+            ))
+            .with_source(e)
+        })?;
+
+        toml::from_str(&content).map_err(|e| AppError::toml_parse(&self.path, &content, e))
+    }
+}
+
+/// Directory holding sync snapshots, next to the prompt file
+pub fn history_dir(prompt_file: &Path) -> PathBuf {
+    prompt_file
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join("sync-history")
+}
+
+/// Store `content` as a new snapshot; empty content has nothing worth keeping
+pub fn save_snapshot(dir: &Path, side: SnapshotSide, content: &str) -> AppResult<Option<Snapshot>> {
+    if content.trim().is_empty() {
+        return Ok(None);
+    }
+
+    fs::create_dir_all(dir).map_err(|e| {
+        AppError::io(format!(
+            "Failed to create sync history directory: {}: {}",
+            dir.display(),
+            e
+        ))
+        .with_source(e)
+    })?;
+
+    let stem = format!(
+        "{}-{}",
+        Local::now().format(TIMESTAMP_FORMAT),
+        side.as_str()
+    );
+    let mut path = dir.join(format!("{}.toml", stem));
+    let mut counter = 1;
+    while path.exists() {
+        counter += 1;
+        path = dir.join(format!("{}-{}.toml", stem, counter));
+    }
+
+    fs::write(&path, content).map_err(|e| {
+        AppError::io(format!(
+            "Failed to write snapshot: {}: {}",
+            path.display(),
+            e
+        ))
+        .with_source(e)
+    })?;
+
+    prune_snapshots(dir)?;
+    Ok(Snapshot::from_path(&path))
+}
+
+/// List snapshots, newest first
+pub fn list_snapshots(dir: &Path) -> AppResult<Vec<Snapshot>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let entries = fs::read_dir(dir).map_err(|e| {
+        AppError::io(format!(
+            "Failed to read sync history directory: {}: {}",
+            dir.display(),
+            e
+        ))
+        .with_source(e)
+    })?;
+
+    let mut snapshots: Vec<Snapshot> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| Snapshot::from_path(&entry.path()))
+        .collect();
+    snapshots.sort_by(|a, b| b.name.cmp(&a.name));
+    Ok(snapshots)
+}
+
+/// Find a snapshot by name or unique name prefix
+pub fn find_snapshot(dir: &Path, name: &str) -> AppResult<Snapshot> {
+    let snapshots = list_snapshots(dir)?;
+    if let Some(snapshot) = snapshots.iter().find(|s| s.name == name) {
+        return Ok(snapshot.clone());
+    }
+
+    let matches: Vec<&Snapshot> = snapshots
+        .iter()
+        .filter(|s| s.name.starts_with(name))
+        .collect();
+    match matches.as_slice() {
+        [snapshot] => Ok((*snapshot).clone()),
+        [] => Err(AppError::NotFound(format!("Snapshot not found: {}", name))),
+        _ => Err(AppError::system(format!(
+            "Snapshot name '{}' is ambiguous ({} matches)",
+            name,
+            matches.len()
+        ))),
+    }
+}
+
+fn prune_snapshots(dir: &Path) -> AppResult<()> {
+    for snapshot in list_snapshots(dir)?.iter().skip(MAX_SNAPSHOTS) {
+        fs::remove_file(&snapshot.path).map_err(|e| {
+            AppError::io(format!(
+                "Failed to remove old snapshot: {}: {}",
+                snapshot.path.display(),
+                e
+            ))
+            .with_source(e)
+        })?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROMPTS: &str = r#"
+[[prompts]]
+Description = "Review"
+Content = "Review <file>"
+Created_at = "2024-01-01 10:00:00"
+"#;
+
+    #[test]
+    fn test_snapshots_are_listed_newest_first_and_found_by_prefix() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let first = save_snapshot(dir.path(), SnapshotSide::Local, PROMPTS)
+            .unwrap()
+            .unwrap();
+        let second = save_snapshot(dir.path(), SnapshotSide::Remote, PROMPTS)
+            .unwrap()
+            .unwrap();
+        assert!(
+            save_snapshot(dir.path(), SnapshotSide::Local, "  \n")
+                .unwrap()
+                .is_none()
+        );
+
+        let snapshots = list_snapshots(dir.path()).unwrap();
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!(snapshots[0].side, SnapshotSide::Remote);
+        assert_eq!(snapshots[1].name, first.name);
+
+        let found = find_snapshot(dir.path(), &second.name).unwrap();
+        assert_eq!(found.load().unwrap().prompts[0].description, "Review");
+        assert!(find_snapshot(dir.path(), "1999").is_err());
+    }
+
+    #[test]
+    fn test_duplicate_names_get_a_counter() {
+        let dir = tempfile::tempdir().unwrap();
+        let names: Vec<String> = (0..3)
+            .map(|_| {
+                save_snapshot(dir.path(), SnapshotSide::Local, PROMPTS)
+                    .unwrap()
+                    .unwrap()
+                    .name
+            })
+            .collect();
+
+        assert_eq!(list_snapshots(dir.path()).unwrap().len(), 3);
+        assert!(names.iter().all(|name| name.contains("-local")));
+    }
+}
@@ -0,0 +1,284 @@
+//! HTTP layer shared by the sync backends
+//!
+//! Wraps `reqwest` with the timeouts and proxy from the `[network]` config,
+//! retries transient failures with exponential backoff, and waits out rate
+//! limits announced through `Retry-After` or GitHub's `X-RateLimit-*` headers.
+
+use crate::config::NetworkConfig;
+use crate::utils::error::{AppError, AppResult};
+use chrono::Utc;
+use reqwest::header::HeaderMap;
+use reqwest::{Client, Method, RequestBuilder, Response, StatusCode};
+use std::time::Duration;
+
+/// Upper bound for a single backoff delay
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+pub struct HttpClient {
+    client: Client,
+    max_retries: u32,
+    retry_delay: Duration,
+    max_rate_limit_wait: Duration,
+}
+
+impl HttpClient {
+    pub fn new(network: &NetworkConfig, skip_ssl: bool) -> AppResult<Self> {
+        let mut builder = Client::builder()
+            .user_agent("promptheus/0.1.0")
+            .timeout(Duration::from_secs(network.timeout_secs))
+            .connect_timeout(Duration::from_secs(network.connect_timeout_secs))
+            .danger_accept_invalid_certs(skip_ssl);
+
+        if let Some(proxy) = &network.proxy {
+            let proxy = reqwest::Proxy::all(proxy).map_err(|e| {
+                AppError::system(format!("Invalid proxy '{}': {}", proxy, e)).with_source(e)
+            })?;
+            builder = builder.proxy(proxy);
+        }
+
+        Ok(Self {
+            client: builder.build().map_err(|e| {
+                AppError::system(format!("Failed to create HTTP client: {}", e)).with_source(e)
+            })?,
+            max_retries: network.max_retries,
+            retry_delay: Duration::from_millis(network.retry_delay_ms),
+            max_rate_limit_wait: Duration::from_secs(network.max_rate_limit_wait_secs),
+        })
+    }
+
+    /// Send a request, retrying transient failures
+    ///
+    /// `build` is called for every attempt. Responses that are not worth
+    /// retrying are returned as-is, whatever their status, so callers can
+    /// handle statuses such as 404 themselves. Updates send the whole
+    /// content, so repeating them is harmless, but a POST creates a new gist
+    /// or snippet each time: it is only retried when it never reached the
+    /// server or was turned away by a rate limit.
+    pub async fn send<F>(&self, action: &str, build: F) -> AppResult<Response>
+    where
+        F: Fn(&Client) -> RequestBuilder,
+    {
+        let mut attempt = 0;
+        loop {
+            let retries_left = attempt < self.max_retries;
+
+            let request = build(&self.client).build().map_err(|e| {
+                AppError::connection(format!("Failed to {}: {}", action, e)).with_source(e)
+            })?;
+            let repeatable = request.method() != Method::POST;
+
+            let response = match self.client.execute(request).await {
+                Ok(response) => response,
+                Err(e) if (repeatable || e.is_connect()) && is_transient(&e) && retries_left => {
+                    self.wait(self.backoff(attempt)).await;
+                    attempt += 1;
+                    continue;
+                }
+                Err(e) => {
+                    return Err(
+                        AppError::connection(format!("Failed to {}: {}", action, e)).with_source(e)
+                    );
+                }
+            };
+
+            if let Some(wait) = rate_limit_wait(response.status(), response.headers()) {
+                if retries_left && wait <= self.max_rate_limit_wait {
+                    self.wait(wait).await;
+                    attempt += 1;
+                    continue;
+                }
+                return Err(AppError::RateLimited {
+                    message: format!("Failed to {}: {}", action, response.status()),
+                    retry_after: Some(wait.as_secs()),
+                });
+            }
+
+            if response.status().is_server_error() && repeatable && retries_left {
+                self.wait(self.backoff(attempt)).await;
+                attempt += 1;
+                continue;
+            }
+
+            return Ok(response);
+        }
+    }
+
+    /// Send a request and turn any unsuccessful status into an error
+    pub async fn send_checked<F>(&self, action: &str, build: F) -> AppResult<Response>
+    where
+        F: Fn(&Client) -> RequestBuilder,
+    {
+        let response = self.send(action, build).await?;
+        error_for_status(response, action).await
+    }
+
+    fn backoff(&self, attempt: u32) -> Duration {
+        self.retry_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(MAX_BACKOFF)
+    }
+
+    async fn wait(&self, duration: Duration) {
+        tokio::time::sleep(duration).await;
+    }
+}
+
+/// Convert an unsuccessful response into an `AppError::Http`
+pub async fn error_for_status(response: Response, action: &str) -> AppResult<Response> {
+    if response.status().is_success() {
+        return Ok(response);
+    }
+
+    let status = response.status();
+    let error_text = response.text().await.unwrap_or_default();
+    let message = format!("Failed to {}: {}", action, error_text.trim());
+    match status {
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => Err(AppError::Auth(message)),
+        _ => Err(AppError::Http {
+            status: status.as_u16(),
+            message,
+        }),
+    }
+}
+
+fn is_transient(error: &reqwest::Error) -> bool {
+    error.is_connect() || error.is_timeout() || error.is_request()
+}
+
+/// How long the server asks us to wait, if the response is a rate limit
+fn rate_limit_wait(status: StatusCode, headers: &HeaderMap) -> Option<Duration> {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse::<i64>().ok())
+    };
+
+    let retry_after = header("retry-after");
+    let exhausted = header("x-ratelimit-remaining") == Some(0);
+    let limited = status == StatusCode::TOO_MANY_REQUESTS
+        || (status == StatusCode::FORBIDDEN && (exhausted || retry_after.is_some()));
+    if !limited {
+        return None;
+    }
+
+    let seconds = retry_after
+        .or_else(|| header("x-ratelimit-reset").map(|reset| reset - Utc::now().timestamp()))
+        .unwrap_or(60)
+        .max(1);
+    Some(Duration::from_secs(seconds as u64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn http_client(max_retries: u32) -> HttpClient {
+        HttpClient::new(
+            &NetworkConfig {
+                max_retries,
+                retry_delay_ms: 1,
+                max_rate_limit_wait_secs: 1,
+                ..NetworkConfig::default()
+            },
+            false,
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_server_errors_are_retried() {
+        let mut server = mockito::Server::new_async().await;
+        let failing = server
+            .mock("GET", "/")
+            .with_status(502)
+            .expect(3)
+            .create_async()
+            .await;
+
+        let url = server.url();
+        let response = http_client(2)
+            .send("fetch", |client| client.get(&url))
+            .await
+            .unwrap();
+
+        failing.assert_async().await;
+        assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+        let err = error_for_status(response, "fetch").await.unwrap_err();
+        assert!(matches!(err, AppError::Http { status: 502, .. }));
+    }
+
+    #[tokio::test]
+    async fn test_creates_are_not_retried_on_server_errors() {
+        let mut server = mockito::Server::new_async().await;
+        let failing = server
+            .mock("POST", "/")
+            .with_status(504)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let url = server.url();
+        let response = http_client(2)
+            .send("create gist", |client| client.post(&url))
+            .await
+            .unwrap();
+
+        failing.assert_async().await;
+        assert_eq!(response.status(), StatusCode::GATEWAY_TIMEOUT);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_beyond_max_wait_is_reported() {
+        let mut server = mockito::Server::new_async().await;
+        let reset = Utc::now().timestamp() + 600;
+        server
+            .mock("GET", "/")
+            .with_status(403)
+            .with_header("x-ratelimit-remaining", "0")
+            .with_header("x-ratelimit-reset", &reset.to_string())
+            .create_async()
+            .await;
+
+        let url = server.url();
+        let err = http_client(3)
+            .send("fetch", |client| client.get(&url))
+            .await
+            .unwrap_err();
+
+        match err {
+            AppError::RateLimited { retry_after, .. } => {
+                assert!(retry_after.unwrap() > 500);
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_connection_failures_are_structured() {
+        // Nothing listens on this port once the listener is dropped
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+
+        let err = http_client(1)
+            .send("fetch", |client| client.get(&url))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::Connection { .. }));
+    }
+
+    #[test]
+    fn test_rate_limit_wait_prefers_retry_after() {
+        let mut headers = HeaderMap::new();
+        headers.insert("retry-after", "7".parse().unwrap());
+        assert_eq!(
+            rate_limit_wait(StatusCode::TOO_MANY_REQUESTS, &headers),
+            Some(Duration::from_secs(7))
+        );
+        assert_eq!(
+            rate_limit_wait(StatusCode::FORBIDDEN, &HeaderMap::new()),
+            None
+        );
+    }
+}
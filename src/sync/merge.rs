@@ -0,0 +1,313 @@
+//! Three-way merge of prompt collections for sync
+//!
+//! Local and remote collections are compared against the base snapshot
+//! taken at the last successful sync. Changes made on only one side are
+//! applied automatically; prompts changed differently on both sides are
+//! reported as conflicts.
+
+use crate::core::data::{Prompt, PromptCollection};
+use crate::utils::error::{AppError, AppResult};
+use crate::utils::file::write_atomic;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// A prompt changed differently on both sides since the last sync
+#[derive(Debug, Clone)]
+pub struct SyncConflict {
+    pub base: Option<Prompt>,
+    pub local: Option<Prompt>,
+    pub remote: Option<Prompt>,
+}
+
+/// How to settle a sync conflict
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConflictResolution {
+    Ours,
+    Theirs,
+    KeepBoth,
+}
+
+/// Result of a three-way merge before conflicts are resolved
+#[derive(Debug)]
+pub struct MergeOutcome {
+    pub merged: PromptCollection,
+    pub conflicts: Vec<SyncConflict>,
+    /// Descriptions of remote changes applied to the local side
+    pub pulled: Vec<String>,
+    /// Descriptions of local changes that need to reach the remote
+    pub pushed: Vec<String>,
+}
+
+impl SyncConflict {
+    /// Description used to identify the conflict to the user
+    pub fn description(&self) -> &str {
+        self.local
+            .as_ref()
+            .or(self.remote.as_ref())
+            .or(self.base.as_ref())
+            .map(|p| p.description.as_str())
+            .unwrap_or_default()
+    }
+
+    /// Prompts to keep for the given resolution
+    pub fn resolve(self, resolution: ConflictResolution) -> Vec<Prompt> {
+        match (resolution, self.local, self.remote) {
+            (ConflictResolution::Ours, local, _) => local.into_iter().collect(),
+            (ConflictResolution::Theirs, _, remote) => remote.into_iter().collect(),
+            (ConflictResolution::KeepBoth, Some(local), Some(mut remote)) => {
+                remote.id = Some(Uuid::new_v4().to_string());
+                remote.description = format!("{} (remote)", remote.description);
+                vec![local, remote]
+            }
+            (ConflictResolution::KeepBoth, local, remote) => {
+                local.into_iter().chain(remote).collect()
+            }
+        }
+    }
+}
+
+impl MergeOutcome {
+    /// Resolve all conflicts and return the final collection
+    pub fn resolve_with<F>(self, mut choose: F) -> AppResult<PromptCollection>
+    where
+        F: FnMut(&SyncConflict) -> AppResult<ConflictResolution>,
+    {
+        let mut merged = self.merged;
+        for conflict in self.conflicts {
+            let resolution = choose(&conflict)?;
+            merged.prompts.extend(conflict.resolve(resolution));
+        }
+        merged.ensure_ids();
+        Ok(merged)
+    }
+}
+
+/// Merge local and remote collections against their common base
+pub fn three_way_merge(
+    base: &PromptCollection,
+    local: &PromptCollection,
+    remote: &PromptCollection,
+) -> MergeOutcome {
+    let local = with_ids(local, &[remote, base]);
+    let remote = with_ids(remote, &[&local, base]);
+    let base = with_ids(base, &[&local, &remote]);
+
+    let mut outcome = MergeOutcome {
+        merged: PromptCollection::new(),
+        conflicts: Vec::new(),
+        pulled: Vec::new(),
+        pushed: Vec::new(),
+    };
+
+    let mut seen = HashSet::new();
+    let ids = local
+        .prompts
+        .iter()
+        .chain(&remote.prompts)
+        .chain(&base.prompts)
+        .filter_map(|p| p.id.clone());
+
+    for id in ids {
+        if !seen.insert(id.clone()) {
+            continue;
+        }
+
+        let base_prompt = base.find_by_id(&id);
+        let local_prompt = local.find_by_id(&id);
+        let remote_prompt = remote.find_by_id(&id);
+
+        let local_changed = !same_prompt(base_prompt, local_prompt);
+        let remote_changed = !same_prompt(base_prompt, remote_prompt);
+
+        let kept = match (local_changed, remote_changed) {
+            (false, false) => local_prompt,
+            (true, false) => {
+                outcome.pushed.push(describe(local_prompt, base_prompt));
+                local_prompt
+            }
+            (false, true) => {
+                outcome.pulled.push(describe(remote_prompt, base_prompt));
+                remote_prompt
+            }
+            (true, true) if same_prompt(local_prompt, remote_prompt) => local_prompt,
+            (true, true) => {
+                outcome.conflicts.push(SyncConflict {
+                    base: base_prompt.cloned(),
+                    local: local_prompt.cloned(),
+                    remote: remote_prompt.cloned(),
+                });
+                continue;
+            }
+        };
+
+        if let Some(prompt) = kept {
+            outcome.merged.prompts.push(prompt.clone());
+        }
+    }
+
+    outcome
+}
+
+/// Check whether two collections hold the same prompts
+pub fn collections_match(a: &PromptCollection, b: &PromptCollection) -> bool {
+    let a = with_ids(a, &[]);
+    let b = with_ids(b, &[&a]);
+    a.prompts.len() == b.prompts.len()
+        && a.prompts.iter().all(|prompt| {
+            let other = prompt.id.as_deref().and_then(|id| b.find_by_id(id));
+            same_prompt(Some(prompt), other)
+        })
+}
+
+/// Location of the base snapshot kept next to the prompt file
+pub fn base_snapshot_path(prompt_file: &Path) -> PathBuf {
+    prompt_file.with_extension("sync-base.toml")
+}
+
+/// Load the base snapshot, treating a missing snapshot as empty
+pub fn load_base_snapshot(path: &Path) -> AppResult<PromptCollection> {
+    if !path.exists() {
+        return Ok(PromptCollection::default());
+    }
+
+    let content = std::fs::read_to_string(path).map_err(|e| {
+        AppError::io(format!(
+            "Failed to read sync base snapshot: {}: {}",
+            path.display(),
+            e
+        ))
+        .with_source(e)
+    })?;
+
+    toml::from_str(&content).map_err(|e| AppError::toml_parse(path, &content, e))
+}
+
+/// Record the collection as the new base snapshot
+pub fn save_base_snapshot(path: &Path, collection: &PromptCollection) -> AppResult<()> {
+    let content = toml::to_string_pretty(&with_ids(collection, &[])).map_err(|e| {
+        AppError::system(format!("Failed to serialize sync base snapshot: {}", e)).with_source(e)
+    })?;
+
+    write_atomic(path, &content).map_err(|e| {
+        AppError::io(format!(
+            "Failed to write sync base snapshot: {}: {}",
+            path.display(),
+            e
+        ))
+        .with_source(e)
+    })
+}
+
+/// Copy of `collection` where every prompt has an ID, taking the ID of
+/// the same prompt in `known` for copies saved without one
+fn with_ids(collection: &PromptCollection, known: &[&PromptCollection]) -> PromptCollection {
+    let mut collection = collection.clone();
+    for other in known {
+        collection.adopt_ids_from(other);
+    }
+    collection.ensure_ids();
+    collection
+}
+
+fn same_prompt(a: Option<&Prompt>, b: Option<&Prompt>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => a.same_content(b),
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+fn describe(prompt: Option<&Prompt>, fallback: Option<&Prompt>) -> String {
+    match (prompt, fallback) {
+        (Some(p), _) => p.description.clone(),
+        (None, Some(p)) => format!("{} (deleted)", p.description),
+        (None, None) => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prompt(description: &str, content: &str) -> Prompt {
+        let mut prompt = Prompt::new(description.to_string(), content.to_string());
+        prompt.id = Some(description.to_lowercase());
+        prompt
+    }
+
+    fn collection(prompts: Vec<Prompt>) -> PromptCollection {
+        PromptCollection { prompts }
+    }
+
+    #[test]
+    fn test_non_conflicting_changes_are_merged() {
+        let base = collection(vec![prompt("Shared", "v1"), prompt("Gone", "x")]);
+        let local = collection(vec![prompt("Shared", "v1"), prompt("Local", "new")]);
+        let remote = collection(vec![
+            prompt("Shared", "v2"),
+            prompt("Gone", "x"),
+            prompt("Remote", "new"),
+        ]);
+
+        let outcome = three_way_merge(&base, &local, &remote);
+
+        assert!(outcome.conflicts.is_empty());
+        let merged = outcome.resolve_with(|_| unreachable!()).unwrap();
+        let descriptions: Vec<_> = merged
+            .prompts
+            .iter()
+            .map(|p| p.description.as_str())
+            .collect();
+        assert_eq!(descriptions, vec!["Shared", "Local", "Remote"]);
+        assert_eq!(merged.find_by_id("shared").unwrap().content, "v2");
+    }
+
+    #[test]
+    fn test_conflicting_edits_are_reported_and_resolved() {
+        let base = collection(vec![prompt("Shared", "v1")]);
+        let local = collection(vec![prompt("Shared", "ours")]);
+        let remote = collection(vec![prompt("Shared", "theirs")]);
+
+        let outcome = three_way_merge(&base, &local, &remote);
+        assert_eq!(outcome.conflicts.len(), 1);
+        assert_eq!(outcome.conflicts[0].description(), "Shared");
+
+        let theirs = three_way_merge(&base, &local, &remote)
+            .resolve_with(|_| Ok(ConflictResolution::Theirs))
+            .unwrap();
+        assert_eq!(theirs.prompts[0].content, "theirs");
+
+        let both = outcome
+            .resolve_with(|_| Ok(ConflictResolution::KeepBoth))
+            .unwrap();
+        assert_eq!(both.prompts.len(), 2);
+        assert_eq!(both.prompts[1].description, "Shared (remote)");
+        assert!(both.prompts[1].id.is_some());
+    }
+
+    #[test]
+    fn test_delete_against_edit_is_a_conflict() {
+        let base = collection(vec![prompt("Shared", "v1")]);
+        let local = collection(vec![]);
+        let remote = collection(vec![prompt("Shared", "v2")]);
+
+        let outcome = three_way_merge(&base, &local, &remote);
+        assert_eq!(outcome.conflicts.len(), 1);
+
+        let ours = outcome
+            .resolve_with(|_| Ok(ConflictResolution::Ours))
+            .unwrap();
+        assert!(ours.prompts.is_empty());
+    }
+
+    #[test]
+    fn test_collections_match_ignores_order() {
+        let a = collection(vec![prompt("One", "1"), prompt("Two", "2")]);
+        let b = collection(vec![prompt("Two", "2"), prompt("One", "1")]);
+        let c = collection(vec![prompt("Two", "2"), prompt("One", "changed")]);
+
+        assert!(collections_match(&a, &b));
+        assert!(!collections_match(&a, &c));
+    }
+}
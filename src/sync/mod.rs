@@ -1,6 +1,15 @@
+pub mod crypto;
+pub mod directory;
 pub mod gist;
+pub mod git;
+pub mod gitlab;
+pub mod history;
+pub mod http;
+pub mod merge;
+pub mod webdav;
 
-use crate::utils::error::AppResult;
+use crate::config::{Config, DirectoryConfig, GistConfig, GitConfig, GitLabConfig, WebDavConfig};
+use crate::utils::error::{AppError, AppResult};
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 
@@ -11,11 +20,92 @@ pub struct RemoteSnippet {
 }
 
 #[async_trait]
-pub trait SyncClient {
+pub trait SyncClient: Send + Sync {
     async fn get_remote(&self) -> AppResult<RemoteSnippet>;
     async fn upload(&self, content: String) -> AppResult<()>;
 }
 
+/// Sync backend selected from the configuration
+enum Backend<'a> {
+    Gist(&'a GistConfig),
+    GitLab(&'a GitLabConfig),
+    WebDav(&'a WebDavConfig),
+    Git(&'a GitConfig),
+    Directory(&'a DirectoryConfig),
+}
+
+/// Pick the configured backend
+///
+/// A Gist backend in use (with a gist ID or file name) takes precedence,
+/// followed by GitLab, WebDAV, git and a plain directory. The default,
+/// unused Gist section is only picked when nothing else is configured.
+fn select_backend(config: &Config) -> Option<Backend<'_>> {
+    let gist_in_use = |gist: &&GistConfig| gist.gist_id.is_some() || !gist.file_name.is_empty();
+
+    config
+        .gist
+        .as_ref()
+        .filter(gist_in_use)
+        .map(Backend::Gist)
+        .or_else(|| config.gitlab.as_ref().map(Backend::GitLab))
+        .or_else(|| config.webdav.as_ref().map(Backend::WebDav))
+        .or_else(|| config.git.as_ref().map(Backend::Git))
+        .or_else(|| config.directory.as_ref().map(Backend::Directory))
+        .or_else(|| config.gist.as_ref().map(Backend::Gist))
+}
+
+/// Create a client for the sync backend configured in `config`
+///
+/// With encryption configured, the backend client is wrapped so that only
+/// encrypted content leaves the machine.
+pub fn create_sync_client(config: &Config) -> AppResult<Box<dyn SyncClient>> {
+    let client = create_backend_client(config)?;
+    match &config.encryption {
+        Some(encryption) => Ok(Box::new(crypto::EncryptedClient::new(client, encryption)?)),
+        None => Ok(client),
+    }
+}
+
+fn create_backend_client(config: &Config) -> AppResult<Box<dyn SyncClient>> {
+    match select_backend(config) {
+        Some(Backend::Gist(gist)) => {
+            let client = gist::GistClient::new(gist.clone(), &config.network)?;
+            Ok(Box::new(client))
+        }
+        Some(Backend::GitLab(gitlab)) => {
+            let client = gitlab::GitLabClient::new(gitlab.clone(), &config.network)?;
+            Ok(Box::new(client))
+        }
+        Some(Backend::WebDav(webdav)) => {
+            let client = webdav::WebDavClient::new(webdav.clone(), &config.network)?;
+            Ok(Box::new(client))
+        }
+        Some(Backend::Git(git_config)) => Ok(Box::new(git::GitClient::new(
+            git_config.clone(),
+            Config::config_dir().join("git-sync"),
+        ))),
+        Some(Backend::Directory(directory)) => {
+            Ok(Box::new(directory::DirectoryClient::new(directory.clone())))
+        }
+        None => Err(AppError::system(
+            "No sync backend configured. Please configure Gist, GitLab, WebDAV, Git or a sync directory in your config."
+                .to_string(),
+        )),
+    }
+}
+
+/// Check whether auto-sync is enabled for the backend `create_sync_client` picks
+pub fn auto_sync_enabled(config: &Config) -> bool {
+    match select_backend(config) {
+        Some(Backend::Gist(gist)) => gist.auto_sync,
+        Some(Backend::GitLab(gitlab)) => gitlab.auto_sync,
+        Some(Backend::WebDav(webdav)) => webdav.auto_sync,
+        Some(Backend::Git(git_config)) => git_config.auto_sync,
+        Some(Backend::Directory(directory)) => directory.auto_sync,
+        None => false,
+    }
+}
+
 /// Determine if sync should happen based on timestamps and force flag
 pub fn should_sync(
     local_updated: DateTime<Utc>,
@@ -45,3 +135,15 @@ pub enum SyncDirection {
 pub fn get_github_token() -> Option<String> {
     std::env::var("PROMPTHEUS_GITHUB_ACCESS_TOKEN").ok()
 }
+
+pub fn get_gitlab_token() -> Option<String> {
+    std::env::var("PROMPTHEUS_GITLAB_ACCESS_TOKEN").ok()
+}
+
+pub fn get_webdav_password() -> Option<String> {
+    std::env::var("PROMPTHEUS_WEBDAV_PASSWORD").ok()
+}
+
+pub fn get_sync_passphrase() -> Option<String> {
+    std::env::var("PROMPTHEUS_SYNC_PASSPHRASE").ok()
+}
@@ -0,0 +1,337 @@
+use super::http::{HttpClient, error_for_status};
+use super::{RemoteSnippet, SyncClient, get_webdav_password};
+use crate::config::{NetworkConfig, WebDavConfig};
+use crate::utils::error::{AppError, AppResult};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use reqwest::{Client, Method, RequestBuilder, Response, StatusCode};
+use std::sync::Mutex;
+
+const PROPFIND_BODY: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:">
+  <d:prop>
+    <d:getlastmodified/>
+  </d:prop>
+</d:propfind>"#;
+
+/// What the last fetch saw on the server, used to guard the next upload
+#[derive(Debug, Clone, PartialEq)]
+enum RemoteState {
+    /// Nothing fetched yet, so uploads are unconditional
+    Unknown,
+    /// The file did not exist, so uploads must not replace one created since
+    Missing,
+    /// The file existed with this ETag
+    Etag(String),
+}
+
+/// Sync backend for WebDAV servers such as Nextcloud
+pub struct WebDavClient {
+    http: HttpClient,
+    config: WebDavConfig,
+    password: Option<String>,
+    remote_state: Mutex<RemoteState>,
+}
+
+impl WebDavClient {
+    pub fn new(config: WebDavConfig, network: &NetworkConfig) -> AppResult<Self> {
+        let password = config.password.clone().or_else(get_webdav_password);
+
+        Ok(Self {
+            http: HttpClient::new(network, config.skip_ssl)?,
+            config,
+            password,
+            remote_state: Mutex::new(RemoteState::Unknown),
+        })
+    }
+
+    fn file_url(&self) -> String {
+        format!(
+            "{}/{}",
+            self.config.url.trim_end_matches('/'),
+            self.config.file_name
+        )
+    }
+
+    fn request(&self, client: &Client, method: Method) -> RequestBuilder {
+        let request = client.request(method, self.file_url());
+        match &self.config.username {
+            Some(username) => request.basic_auth(username, self.password.as_ref()),
+            None => request,
+        }
+    }
+
+    fn set_remote_state(&self, state: RemoteState) {
+        *self.remote_state.lock().unwrap_or_else(|e| e.into_inner()) = state;
+    }
+
+    fn remote_state(&self) -> RemoteState {
+        self.remote_state
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Fetch the modification time, or `None` when the file does not exist
+    async fn last_modified(&self) -> AppResult<Option<DateTime<Utc>>> {
+        let method = Method::from_bytes(b"PROPFIND").map_err(|e| {
+            AppError::system(format!("Invalid WebDAV method: {}", e)).with_source(e)
+        })?;
+        let response = self
+            .http
+            .send("query WebDAV properties", |client| {
+                self.request(client, method.clone())
+                    .header("Depth", "0")
+                    .header("Content-Type", "application/xml; charset=utf-8")
+                    .body(PROPFIND_BODY)
+            })
+            .await?;
+
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let response = error_for_status(response, "query WebDAV properties").await?;
+        let body = response.text().await.map_err(|e| {
+            AppError::network(format!("Failed to read WebDAV response: {}", e)).with_source(e)
+        })?;
+
+        let updated_at = match xml_property(&body, "getlastmodified") {
+            Some(value) => parse_http_date(&value)?,
+            None => DateTime::default(),
+        };
+        Ok(Some(updated_at))
+    }
+
+    /// Fetch the content along with the ETag of exactly that version
+    async fn download(&self) -> AppResult<(String, Option<String>)> {
+        let response = self
+            .http
+            .send_checked("download from WebDAV", |client| {
+                self.request(client, Method::GET)
+            })
+            .await?;
+        let etag = response_etag(&response);
+        let content = response.text().await.map_err(|e| {
+            AppError::network(format!("Failed to read WebDAV content: {}", e)).with_source(e)
+        })?;
+        Ok((content, etag))
+    }
+}
+
+fn response_etag(response: &Response) -> Option<String> {
+    response
+        .headers()
+        .get("ETag")
+        .and_then(|value| value.to_str().ok())
+        .map(String::from)
+}
+
+/// Extract a property value from a PROPFIND response, whatever its namespace prefix
+fn xml_property(body: &str, name: &str) -> Option<String> {
+    let pattern = format!(
+        r"<(?:[\w-]+:)?{0}(?:\s[^>]*)?>([^<]*)</(?:[\w-]+:)?{0}>",
+        name
+    );
+    let re = Regex::new(&pattern).ok()?;
+    re.captures(body)
+        .map(|caps| caps[1].trim().replace("&quot;", "\""))
+        .filter(|value| !value.is_empty())
+}
+
+fn parse_http_date(value: &str) -> AppResult<DateTime<Utc>> {
+    let parsed = DateTime::parse_from_rfc2822(value).map_err(|e| {
+        AppError::system(format!("Failed to parse WebDAV timestamp: {}", e)).with_source(e)
+    })?;
+    Ok(parsed.with_timezone(&Utc))
+}
+
+#[async_trait]
+impl SyncClient for WebDavClient {
+    async fn get_remote(&self) -> AppResult<RemoteSnippet> {
+        let Some(updated_at) = self.last_modified().await? else {
+            self.set_remote_state(RemoteState::Missing);
+            return Ok(RemoteSnippet {
+                content: String::new(),
+                updated_at: DateTime::default(),
+            });
+        };
+
+        let (content, etag) = self.download().await?;
+        self.set_remote_state(etag.map_or(RemoteState::Unknown, RemoteState::Etag));
+
+        Ok(RemoteSnippet {
+            content,
+            updated_at,
+        })
+    }
+
+    async fn upload(&self, content: String) -> AppResult<()> {
+        let state = self.remote_state();
+        let response = self
+            .http
+            .send("upload to WebDAV", |client| {
+                let request = self.request(client, Method::PUT).body(content.clone());
+                match &state {
+                    RemoteState::Unknown => request,
+                    RemoteState::Missing => request.header("If-None-Match", "*"),
+                    RemoteState::Etag(etag) => request.header("If-Match", etag),
+                }
+            })
+            .await?;
+        if response.status() == StatusCode::PRECONDITION_FAILED {
+            return Err(AppError::Conflict(
+                "Remote file changed since it was fetched. Run sync again to merge the new changes."
+                    .to_string(),
+            ));
+        }
+        let response = error_for_status(response, "upload to WebDAV").await?;
+
+        // Our own write is now the latest known version
+        let etag = response_etag(&response);
+        self.set_remote_state(etag.map_or(RemoteState::Unknown, RemoteState::Etag));
+
+        println!("✅ Uploaded prompts to {}", self.file_url());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MULTISTATUS: &str = r#"<?xml version="1.0"?>
+<d:multistatus xmlns:d="DAV:">
+  <d:response>
+    <d:href>/dav/prompts.toml</d:href>
+    <d:propstat>
+      <d:prop>
+        <d:getlastmodified>Wed, 01 May 2024 12:00:00 GMT</d:getlastmodified>
+        <d:getetag>&quot;abc123&quot;</d:getetag>
+      </d:prop>
+      <d:status>HTTP/1.1 200 OK</d:status>
+    </d:propstat>
+  </d:response>
+</d:multistatus>"#;
+
+    fn client_for(url: String) -> WebDavClient {
+        WebDavClient::new(
+            WebDavConfig {
+                url: format!("{}/dav/", url),
+                username: Some("user".to_string()),
+                password: Some("secret".to_string()),
+                file_name: "prompts.toml".to_string(),
+                auto_sync: false,
+                skip_ssl: false,
+            },
+            &NetworkConfig::default(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_xml_property_ignores_namespace_prefix() {
+        assert_eq!(
+            xml_property(MULTISTATUS, "getetag").as_deref(),
+            Some("\"abc123\"")
+        );
+        assert_eq!(
+            xml_property(
+                "<D:getlastmodified>x</D:getlastmodified>",
+                "getlastmodified"
+            )
+            .as_deref(),
+            Some("x")
+        );
+        assert_eq!(xml_property(MULTISTATUS, "getcontentlength"), None);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_then_upload_sends_if_match() {
+        let mut server = mockito::Server::new_async().await;
+        let propfind = server
+            .mock("PROPFIND", "/dav/prompts.toml")
+            .match_header("Depth", "0")
+            .match_header("Authorization", "Basic dXNlcjpzZWNyZXQ=")
+            .with_status(207)
+            .with_body(MULTISTATUS)
+            .create_async()
+            .await;
+        // The file changed after the PROPFIND; the ETag must match the content
+        server
+            .mock("GET", "/dav/prompts.toml")
+            .with_header("ETag", "\"def456\"")
+            .with_body("prompts = []\n")
+            .create_async()
+            .await;
+        let put = server
+            .mock("PUT", "/dav/prompts.toml")
+            .match_header("If-Match", "\"def456\"")
+            .match_body("prompts = [1]")
+            .with_status(204)
+            .create_async()
+            .await;
+
+        let client = client_for(server.url());
+        let remote = client.get_remote().await.unwrap();
+        assert_eq!(remote.content, "prompts = []\n");
+        assert_eq!(
+            remote.updated_at,
+            "2024-05-01T12:00:00Z".parse::<DateTime<Utc>>().unwrap()
+        );
+
+        client.upload("prompts = [1]".to_string()).await.unwrap();
+        propfind.assert_async().await;
+        put.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_missing_file_is_created_with_if_none_match() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("PROPFIND", "/dav/prompts.toml")
+            .with_status(404)
+            .create_async()
+            .await;
+        let put = server
+            .mock("PUT", "/dav/prompts.toml")
+            .match_header("If-None-Match", "*")
+            .with_status(201)
+            .create_async()
+            .await;
+
+        let client = client_for(server.url());
+        let remote = client.get_remote().await.unwrap();
+        assert!(remote.content.is_empty());
+
+        client.upload("prompts = []".to_string()).await.unwrap();
+        put.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_write_is_reported_as_conflict() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("PROPFIND", "/dav/prompts.toml")
+            .with_status(207)
+            .with_body(MULTISTATUS)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/dav/prompts.toml")
+            .with_header("ETag", "\"abc123\"")
+            .with_body("prompts = []\n")
+            .create_async()
+            .await;
+        server
+            .mock("PUT", "/dav/prompts.toml")
+            .with_status(412)
+            .create_async()
+            .await;
+
+        let client = client_for(server.url());
+        client.get_remote().await.unwrap();
+        let err = client.upload("prompts = []".to_string()).await.unwrap_err();
+        assert!(matches!(err, AppError::Conflict(_)));
+    }
+}
@@ -1,5 +1,7 @@
 use crate::utils::error::{AppError, AppResult};
 use crate::utils::output::OutputStyle;
+use crate::utils::sources::ValueSources;
+use crate::utils::variables::{VariableKind, VariableSpec};
 use crossterm::{
     cursor,
     event::{self, DisableBracketedPaste, EnableBracketedPaste, Event, KeyCode, KeyEvent},
@@ -33,7 +35,7 @@ impl std::error::Error for InteractiveError {}
 /// Convert system errors to InteractiveError
 impl From<io::Error> for InteractiveError {
     fn from(err: io::Error) -> Self {
-        InteractiveError::SystemError(AppError::Io(err.to_string()))
+        InteractiveError::SystemError(AppError::io(err.to_string()).with_source(err))
     }
 }
 
@@ -50,16 +52,18 @@ struct RawModeGuard {
 
 impl RawModeGuard {
     fn new() -> AppResult<Self> {
-        terminal::enable_raw_mode()
-            .map_err(|e| AppError::System(format!("Failed to enable raw mode: {}", e)))?;
+        terminal::enable_raw_mode().map_err(|e| {
+            AppError::system(format!("Failed to enable raw mode: {}", e)).with_source(e)
+        })?;
         Ok(RawModeGuard {
             bracketed_paste: false,
         })
     }
 
     fn with_bracketed_paste() -> AppResult<Self> {
-        terminal::enable_raw_mode()
-            .map_err(|e| AppError::System(format!("Failed to enable raw mode: {}", e)))?;
+        terminal::enable_raw_mode().map_err(|e| {
+            AppError::system(format!("Failed to enable raw mode: {}", e)).with_source(e)
+        })?;
         let _ = execute!(io::stdout(), EnableBracketedPaste);
         Ok(RawModeGuard {
             bracketed_paste: true,
@@ -79,19 +83,20 @@ impl RawModeGuard {
             cursor::MoveToColumn(0),
             terminal::Clear(ClearType::CurrentLine)
         )
-        .map_err(|e| AppError::Io(e.to_string()))?;
+        .map_err(|e| AppError::io(e.to_string()).with_source(e))?;
         io::stdout()
             .flush()
-            .map_err(|e| AppError::Io(e.to_string()))?;
+            .map_err(|e| AppError::io(e.to_string()).with_source(e))?;
         Ok(())
     }
 
     /// Print text and flush output
     fn print_and_flush(&self, text: &str) -> AppResult<()> {
-        execute!(io::stdout(), style::Print(text)).map_err(|e| AppError::Io(e.to_string()))?;
+        execute!(io::stdout(), style::Print(text))
+            .map_err(|e| AppError::io(e.to_string()).with_source(e))?;
         io::stdout()
             .flush()
-            .map_err(|e| AppError::Io(e.to_string()))?;
+            .map_err(|e| AppError::io(e.to_string()).with_source(e))?;
         Ok(())
     }
 
@@ -99,16 +104,16 @@ impl RawModeGuard {
     fn print_line(&self, prompt: &str, content: &str, suggestion: Option<&str>) -> AppResult<()> {
         self.clear_line()?;
         execute!(io::stdout(), style::Print(prompt), style::Print(content))
-            .map_err(|e| AppError::Io(e.to_string()))?;
+            .map_err(|e| AppError::io(e.to_string()).with_source(e))?;
 
         if let Some(suggestion) = suggestion {
             execute!(io::stdout(), style::Print(OutputStyle::muted(suggestion)))
-                .map_err(|e| AppError::Io(e.to_string()))?;
+                .map_err(|e| AppError::io(e.to_string()).with_source(e))?;
         }
 
         io::stdout()
             .flush()
-            .map_err(|e| AppError::Io(e.to_string()))?;
+            .map_err(|e| AppError::io(e.to_string()).with_source(e))?;
         Ok(())
     }
 
@@ -116,10 +121,10 @@ impl RawModeGuard {
     fn move_cursor_left(&self, positions: u16) -> AppResult<()> {
         if positions > 0 {
             execute!(io::stdout(), cursor::MoveLeft(positions))
-                .map_err(|e| AppError::Io(e.to_string()))?;
+                .map_err(|e| AppError::io(e.to_string()).with_source(e))?;
             io::stdout()
                 .flush()
-                .map_err(|e| AppError::Io(e.to_string()))?;
+                .map_err(|e| AppError::io(e.to_string()).with_source(e))?;
         }
         Ok(())
     }
@@ -163,12 +168,12 @@ pub fn prompt_input(prompt: &str) -> AppResult<String> {
     print!("{}", prompt);
     io::stdout()
         .flush()
-        .map_err(|e| AppError::Io(e.to_string()))?;
+        .map_err(|e| AppError::io(e.to_string()).with_source(e))?;
 
     let mut input = String::new();
     io::stdin()
         .read_line(&mut input)
-        .map_err(|e| AppError::Io(e.to_string()))?;
+        .map_err(|e| AppError::io(e.to_string()).with_source(e))?;
 
     // Ensure proper newline after input
     println!();
@@ -177,15 +182,12 @@ pub fn prompt_input(prompt: &str) -> AppResult<String> {
 }
 
 /// Generic error handling wrapper that converts InteractiveError to Option
+///
+/// Cancellation is silent here; callers report it as `AppError::Cancelled`.
 fn handle_interactive_result<T>(result: Result<T, InteractiveError>) -> Option<T> {
     match result {
         Ok(value) => Some(value),
-        Err(InteractiveError::Cancelled) => {
-            crate::utils::error::handle_flow(crate::utils::error::FlowResult::Cancelled(
-                "Operation cancelled by user".to_string(),
-            ));
-            None
-        }
+        Err(InteractiveError::Cancelled) => None,
         Err(InteractiveError::SystemError(e)) => {
             eprintln!("❌ Terminal error: {}", e);
             None
@@ -213,15 +215,16 @@ fn prompt_input_with_autocomplete_internal(
     print!("{}", prompt);
     io::stdout()
         .flush()
-        .map_err(|e| AppError::Io(e.to_string()))?;
+        .map_err(|e| AppError::io(e.to_string()).with_source(e))?;
 
     let guard = RawModeGuard::new()?;
     let mut input = String::new();
     let mut current_suggestion = String::new();
 
     loop {
-        let event = event::read()
-            .map_err(|e| InteractiveError::SystemError(AppError::Io(e.to_string())))?; // Propagate terminal errors properly
+        let event = event::read().map_err(|e| {
+            InteractiveError::SystemError(AppError::io(e.to_string()).with_source(e))
+        })?; // Propagate terminal errors properly
         match event {
             Event::Key(KeyEvent {
                 code: KeyCode::Char(c),
@@ -246,38 +249,34 @@ fn prompt_input_with_autocomplete_internal(
             }
             Event::Key(KeyEvent {
                 code: KeyCode::Tab, ..
-            }) => {
+            }) if !current_suggestion.is_empty() => {
                 // Accept current suggestion
-                if !current_suggestion.is_empty() {
-                    input.push_str(&current_suggestion);
-                    current_suggestion.clear();
+                input.push_str(&current_suggestion);
+                current_suggestion.clear();
 
-                    // Redraw line
-                    guard.print_line(prompt, &input, None)?;
-                }
+                // Redraw line
+                guard.print_line(prompt, &input, None)?;
             }
             Event::Key(KeyEvent {
                 code: KeyCode::Backspace,
                 ..
-            }) => {
-                if !input.is_empty() {
-                    input.pop();
-                    current_suggestion = find_autocomplete_suggestion(&input, suggestions);
-
-                    // Redraw current line
-                    guard.print_line(
-                        prompt,
-                        &input,
-                        if current_suggestion.is_empty() {
-                            None
-                        } else {
-                            Some(&current_suggestion)
-                        },
-                    )?;
-
-                    // Move cursor back to end of actual input
-                    guard.move_cursor_left(current_suggestion.len() as u16)?;
-                }
+            }) if !input.is_empty() => {
+                input.pop();
+                current_suggestion = find_autocomplete_suggestion(&input, suggestions);
+
+                // Redraw current line
+                guard.print_line(
+                    prompt,
+                    &input,
+                    if current_suggestion.is_empty() {
+                        None
+                    } else {
+                        Some(&current_suggestion)
+                    },
+                )?;
+
+                // Move cursor back to end of actual input
+                guard.move_cursor_left(current_suggestion.len() as u16)?;
             }
             Event::Key(KeyEvent {
                 code: KeyCode::Enter,
@@ -314,8 +313,9 @@ fn prompt_multiline_internal(prompt: &str) -> Result<String, InteractiveError> {
     let mut current_line = String::new();
 
     loop {
-        let event = event::read()
-            .map_err(|e| InteractiveError::SystemError(AppError::Io(e.to_string())))?; // Propagate terminal errors properly
+        let event = event::read().map_err(|e| {
+            InteractiveError::SystemError(AppError::io(e.to_string()).with_source(e))
+        })?; // Propagate terminal errors properly
         match event {
             Event::Key(KeyEvent {
                 code: KeyCode::Char('j'),
@@ -357,10 +357,12 @@ fn prompt_multiline_internal(prompt: &str) -> Result<String, InteractiveError> {
                         cursor::MoveLeft(1),
                         terminal::Clear(ClearType::UntilNewLine)
                     )
-                    .map_err(|e| InteractiveError::SystemError(AppError::Io(e.to_string())))?;
+                    .map_err(|e| {
+                        InteractiveError::SystemError(AppError::io(e.to_string()).with_source(e))
+                    })?;
                     io::stdout()
                         .flush()
-                        .map_err(|e| AppError::Io(e.to_string()))?;
+                        .map_err(|e| AppError::io(e.to_string()).with_source(e))?;
                 } else if !lines.is_empty() {
                     current_line = lines.pop().unwrap();
                     execute!(
@@ -369,16 +371,20 @@ fn prompt_multiline_internal(prompt: &str) -> Result<String, InteractiveError> {
                         cursor::MoveToColumn(1),
                         terminal::Clear(ClearType::UntilNewLine)
                     )
-                    .map_err(|e| InteractiveError::SystemError(AppError::Io(e.to_string())))?;
+                    .map_err(|e| {
+                        InteractiveError::SystemError(AppError::io(e.to_string()).with_source(e))
+                    })?;
                     print!("{}", current_line);
                     for _ in 0..current_line.len() {
                         execute!(io::stdout(), cursor::MoveLeft(1)).map_err(|e| {
-                            InteractiveError::SystemError(AppError::Io(e.to_string()))
+                            InteractiveError::SystemError(
+                                AppError::io(e.to_string()).with_source(e),
+                            )
                         })?;
                     }
                     io::stdout()
                         .flush()
-                        .map_err(|e| AppError::Io(e.to_string()))?;
+                        .map_err(|e| AppError::io(e.to_string()).with_source(e))?;
                 }
             }
             Event::Key(KeyEvent {
@@ -437,9 +443,11 @@ pub fn open_editor_custom(
     let temp_file = std::env::temp_dir().join(format!("promptheus_{}.tmp", std::process::id()));
 
     if let Some(content) = content {
-        std::fs::write(&temp_file, content).map_err(|e| AppError::Io(e.to_string()))?;
+        std::fs::write(&temp_file, content)
+            .map_err(|e| AppError::io(e.to_string()).with_source(e))?;
     } else {
-        std::fs::File::create(&temp_file).map_err(|e| AppError::Io(e.to_string()))?;
+        std::fs::File::create(&temp_file)
+            .map_err(|e| AppError::io(e.to_string()).with_source(e))?;
     }
 
     let mut cmd = Command::new(&editor);
@@ -457,19 +465,20 @@ pub fn open_editor_custom(
                 cmd.arg("--goto");
                 cmd.arg(format!("{}:{}", temp_file.display(), line_num));
                 // For VS Code, we don't need to add the file separately
-                let status = cmd
-                    .status()
-                    .map_err(|e| AppError::System(format!("Failed to execute editor: {}", e)))?;
+                let status = cmd.status().map_err(|e| {
+                    AppError::system(format!("Failed to execute editor: {}", e)).with_source(e)
+                })?;
 
                 if !status.success() {
-                    return Err(AppError::System(
+                    return Err(AppError::system(
                         "Editor exited with non-zero status".to_string(),
                     ));
                 }
 
-                let content =
-                    std::fs::read_to_string(&temp_file).map_err(|e| AppError::Io(e.to_string()))?;
-                std::fs::remove_file(&temp_file).map_err(|e| AppError::Io(e.to_string()))?;
+                let content = std::fs::read_to_string(&temp_file)
+                    .map_err(|e| AppError::io(e.to_string()).with_source(e))?;
+                std::fs::remove_file(&temp_file)
+                    .map_err(|e| AppError::io(e.to_string()).with_source(e))?;
                 return Ok(content.trim().to_string());
             }
             _ => {
@@ -481,18 +490,19 @@ pub fn open_editor_custom(
 
     cmd.arg(&temp_file);
 
-    let status = cmd
-        .status()
-        .map_err(|e| AppError::System(format!("Failed to execute editor: {}: {}", editor, e)))?;
+    let status = cmd.status().map_err(|e| {
+        AppError::system(format!("Failed to execute editor: {}: {}", editor, e)).with_source(e)
+    })?;
 
     if !status.success() {
-        return Err(AppError::System(
+        return Err(AppError::system(
             "Editor exited with non-zero status".to_string(),
         ));
     }
 
-    let content = std::fs::read_to_string(&temp_file).map_err(|e| AppError::Io(e.to_string()))?;
-    std::fs::remove_file(&temp_file).map_err(|e| AppError::Io(e.to_string()))?;
+    let content = std::fs::read_to_string(&temp_file)
+        .map_err(|e| AppError::io(e.to_string()).with_source(e))?;
+    std::fs::remove_file(&temp_file).map_err(|e| AppError::io(e.to_string()).with_source(e))?;
 
     Ok(content.trim().to_string())
 }
@@ -520,12 +530,12 @@ pub fn edit_file_direct(
                 cmd.arg("--goto");
                 cmd.arg(format!("{}:{}", file_path.display(), line_num));
                 // For VS Code, this is sufficient
-                let status = cmd
-                    .status()
-                    .map_err(|e| AppError::System(format!("Failed to execute editor: {}", e)))?;
+                let status = cmd.status().map_err(|e| {
+                    AppError::system(format!("Failed to execute editor: {}", e)).with_source(e)
+                })?;
 
                 if !status.success() {
-                    return Err(AppError::System(
+                    return Err(AppError::system(
                         "Editor exited with non-zero status".to_string(),
                     ));
                 }
@@ -539,12 +549,12 @@ pub fn edit_file_direct(
 
     cmd.arg(file_path);
 
-    let status = cmd
-        .status()
-        .map_err(|e| AppError::System(format!("Failed to execute editor: {}: {}", editor, e)))?;
+    let status = cmd.status().map_err(|e| {
+        AppError::system(format!("Failed to execute editor: {}: {}", editor, e)).with_source(e)
+    })?;
 
     if !status.success() {
-        return Err(AppError::System(
+        return Err(AppError::system(
             "Editor exited with non-zero status".to_string(),
         ));
     }
@@ -593,20 +603,22 @@ pub fn copy_to_clipboard(text: &str) -> AppResult<()> {
         let mut child = Command::new("pbcopy")
             .stdin(std::process::Stdio::piped())
             .spawn()
-            .map_err(|e| AppError::System(format!("Failed to spawn pbcopy: {}", e)))?;
+            .map_err(|e| {
+                AppError::system(format!("Failed to spawn pbcopy: {}", e)).with_source(e)
+            })?;
 
         if let Some(stdin) = child.stdin.as_mut() {
-            stdin
-                .write_all(text.as_bytes())
-                .map_err(|e| AppError::System(format!("Failed to write to pbcopy: {}", e)))?;
+            stdin.write_all(text.as_bytes()).map_err(|e| {
+                AppError::system(format!("Failed to write to pbcopy: {}", e)).with_source(e)
+            })?;
         }
 
-        let status = child
-            .wait()
-            .map_err(|e| AppError::System(format!("Failed to wait for pbcopy: {}", e)))?;
+        let status = child.wait().map_err(|e| {
+            AppError::system(format!("Failed to wait for pbcopy: {}", e)).with_source(e)
+        })?;
 
         if !status.success() {
-            return Err(AppError::System("pbcopy failed".to_string()));
+            return Err(AppError::system("pbcopy failed".to_string()));
         }
     }
 
@@ -656,25 +668,25 @@ pub fn copy_to_clipboard(text: &str) -> AppResult<()> {
                     if let Some(stdin) = child.stdin.as_mut()
                         && let Err(e) = stdin.write_all(text.as_bytes())
                     {
-                        last_error = Some(AppError::System(format!(
-                            "Failed to write to {}: {}",
-                            tool, e
-                        )));
+                        last_error = Some(
+                            AppError::system(format!("Failed to write to {}: {}", tool, e))
+                                .with_source(e),
+                        );
                         continue;
                     }
 
                     match child.wait() {
                         Ok(status) if status.success() => return Ok(()),
-                        Ok(_) => last_error = Some(AppError::System(format!("{} failed", tool))),
+                        Ok(_) => last_error = Some(AppError::system(format!("{} failed", tool))),
                         Err(e) => {
-                            last_error = Some(AppError::System(format!(
-                                "Failed to wait for {}: {}",
-                                tool, e
-                            )))
+                            last_error = Some(
+                                AppError::system(format!("Failed to wait for {}: {}", tool, e))
+                                    .with_source(e),
+                            )
                         }
                     }
                 } else {
-                    last_error = Some(AppError::System(format!("Failed to spawn {}", tool)));
+                    last_error = Some(AppError::system(format!("Failed to spawn {}", tool)));
                 }
             }
         }
@@ -683,17 +695,17 @@ pub fn copy_to_clipboard(text: &str) -> AppResult<()> {
         if available_tools.is_empty() {
             match display_server {
                 DisplayServer::Wayland => {
-                    return Err(AppError::System(
+                    return Err(AppError::system(
                         "No clipboard tools found. Please install wl-clipboard:\n  sudo pacman -S wl-clipboard  # Arch\n  sudo apt install wl-clipboard  # Ubuntu/Debian".to_string()
                     ));
                 }
                 DisplayServer::X11 => {
-                    return Err(AppError::System(
+                    return Err(AppError::system(
                         "No clipboard tools found. Please install one of:\n  sudo pacman -S xclip  # Arch\n  sudo apt install xclip  # Ubuntu/Debian".to_string()
                     ));
                 }
                 DisplayServer::Unknown => {
-                    return Err(AppError::System(
+                    return Err(AppError::system(
                         "No clipboard tools found. Please install:\n  sudo pacman -S wl-clipboard xclip  # Arch\n  sudo apt install wl-clipboard xclip  # Ubuntu/Debian".to_string()
                     ));
                 }
@@ -703,7 +715,7 @@ pub fn copy_to_clipboard(text: &str) -> AppResult<()> {
         if let Some(error) = last_error {
             return Err(error);
         }
-        return Err(AppError::System(
+        return Err(AppError::system(
             "All available clipboard tools failed".to_string(),
         ));
     }
@@ -713,26 +725,26 @@ pub fn copy_to_clipboard(text: &str) -> AppResult<()> {
         let mut child = Command::new("clip")
             .stdin(std::process::Stdio::piped())
             .spawn()
-            .map_err(|e| AppError::System(format!("Failed to spawn clip: {}", e)))?;
+            .map_err(|e| AppError::system(format!("Failed to spawn clip: {}", e)).with_source(e))?;
 
         if let Some(stdin) = child.stdin.as_mut() {
-            stdin
-                .write_all(text.as_bytes())
-                .map_err(|e| AppError::System(format!("Failed to write to clip: {}", e)))?;
+            stdin.write_all(text.as_bytes()).map_err(|e| {
+                AppError::system(format!("Failed to write to clip: {}", e)).with_source(e)
+            })?;
         }
 
-        let status = child
-            .wait()
-            .map_err(|e| AppError::System(format!("Failed to wait for clip: {}", e)))?;
+        let status = child.wait().map_err(|e| {
+            AppError::system(format!("Failed to wait for clip: {}", e)).with_source(e)
+        })?;
 
         if !status.success() {
-            return Err(AppError::System("clip failed"));
+            return Err(AppError::system("clip failed"));
         }
     }
 
     #[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
     {
-        return Err(AppError::System("Clipboard not supported on this platform"));
+        return Err(AppError::system("Clipboard not supported on this platform"));
     }
 
     // This line should never be reached due to the platform-specific returns above
@@ -740,17 +752,69 @@ pub fn copy_to_clipboard(text: &str) -> AppResult<()> {
     Ok(())
 }
 
+/// Read the clipboard with the same tools `copy_to_clipboard` writes with
+pub fn read_clipboard() -> AppResult<String> {
+    #[cfg(target_os = "macos")]
+    let tools: Vec<(&str, Vec<&str>)> = vec![("pbpaste", vec![])];
+
+    #[cfg(target_os = "linux")]
+    let tools: Vec<(&str, Vec<&str>)> = {
+        let wayland = ("wl-paste", vec!["--no-newline"]);
+        let xclip = ("xclip", vec!["-selection", "clipboard", "-o"]);
+        let xsel = ("xsel", vec!["--clipboard", "--output"]);
+        match detect_display_server() {
+            DisplayServer::X11 => vec![xclip, xsel, wayland],
+            DisplayServer::Wayland | DisplayServer::Unknown => vec![wayland, xclip, xsel],
+        }
+    };
+
+    #[cfg(target_os = "windows")]
+    let tools: Vec<(&str, Vec<&str>)> = vec![(
+        "powershell",
+        vec!["-NoProfile", "-Command", "Get-Clipboard -Raw"],
+    )];
+
+    #[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+    let tools: Vec<(&str, Vec<&str>)> = Vec::new();
+
+    let mut last_error = None;
+    for (tool, args) in tools {
+        match Command::new(tool).args(args).output() {
+            Ok(output) if output.status.success() => {
+                return String::from_utf8(output.stdout)
+                    .map_err(|_| AppError::system("Clipboard does not contain text".to_string()));
+            }
+            Ok(_) => last_error = Some(AppError::system(format!("{} failed", tool))),
+            // Not installed, try the next tool
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => {
+                last_error =
+                    Some(AppError::system(format!("Failed to run {}: {}", tool, e)).with_source(e))
+            }
+        }
+    }
+
+    Err(last_error.unwrap_or_else(|| {
+        AppError::system(
+            "No clipboard tools found. Please install wl-clipboard, xclip or xsel".to_string(),
+        )
+    }))
+}
+
 /// Parse variables from command string in format <param> or <param=default>
-pub fn parse_command_variables(command: &str) -> Vec<(String, Option<String>)> {
+///
+/// `param` may also carry a type, choices, a regex and a description; see
+/// [`VariableSpec::parse`].
+pub fn parse_command_variables(command: &str) -> Vec<VariableSpec> {
     use regex::Regex;
 
     let re = Regex::new(r"<([^>=]+)(?:=([^>]*))?>").unwrap();
     let mut variables = Vec::new();
 
     for cap in re.captures_iter(command) {
-        let name = cap.get(1).unwrap().as_str().to_string();
-        let default = cap.get(2).map(|m| m.as_str().to_string());
-        variables.push((name, default));
+        let raw = cap.get(1).unwrap().as_str();
+        let default = cap.get(2).map(|m| m.as_str());
+        variables.push(VariableSpec::parse(raw, default));
     }
 
     variables
@@ -766,43 +830,63 @@ pub fn replace_command_variables(
     let re = Regex::new(r"<([^>=]+)(?:=([^>]*))?>").unwrap();
 
     re.replace_all(command, |caps: &regex::Captures| {
-        let var_name = caps.get(1).unwrap().as_str();
+        let spec = VariableSpec::parse(
+            caps.get(1).unwrap().as_str(),
+            caps.get(2).map(|m| m.as_str()),
+        );
 
         // Use provided value, or default, or empty string
-        if let Some(value) = variables.get(var_name) {
-            value.clone()
-        } else if let Some(default_val) = caps.get(2) {
-            default_val.as_str().to_string()
-        } else {
-            String::new()
-        }
+        variables
+            .get(&spec.name)
+            .cloned()
+            .or(spec.default)
+            .unwrap_or_default()
     })
     .to_string()
 }
 
 /// Prompt user for variable values interactively
+///
+/// Choices are offered as a numbered menu, and invalid input is explained
+/// and asked for again. Input may name a source such as `@clipboard`.
 pub fn prompt_for_variables(
-    variables: Vec<(String, Option<String>)>,
+    variables: Vec<VariableSpec>,
+    sources: &ValueSources,
 ) -> AppResult<std::collections::HashMap<String, String>> {
     let mut result = std::collections::HashMap::new();
 
-    for (name, default) in variables {
-        let prompt = if let Some(ref default_val) = default {
-            format!("{} [default: {}]: ", name, default_val)
-        } else {
-            format!("{}: ", name)
-        };
+    for spec in variables {
+        if let VariableKind::Enum(choices) = &spec.kind {
+            for (index, choice) in choices.iter().enumerate() {
+                println!("  {}) {}", index + 1, choice);
+            }
+        }
 
-        let input = prompt_input(&prompt)?;
+        loop {
+            let input = match spec.kind {
+                VariableKind::Multiline => {
+                    prompt_multiline(&format!("{} (Shift+Enter for a new line)", spec.label()))
+                        .ok_or_else(|| AppError::Cancelled("Input cancelled".to_string()))?
+                }
+                _ => prompt_input(&format!("{}: ", spec.label()))?,
+            };
+
+            // Input such as @file:path is replaced by what it names
+            let input = match sources.expand(&input) {
+                Ok(input) => input,
+                Err(e) => {
+                    println!("{}", OutputStyle::error(&format!("  {}", e)));
+                    continue;
+                }
+            };
 
-        if input.is_empty() {
-            if let Some(default_val) = default {
-                result.insert(name, default_val);
-            } else {
-                result.insert(name, String::new());
+            match spec.validate(&input) {
+                Ok(value) => {
+                    result.insert(spec.name.clone(), value);
+                    break;
+                }
+                Err(message) => println!("{}", OutputStyle::error(&format!("  {}", message))),
             }
-        } else {
-            result.insert(name, input);
         }
     }
 
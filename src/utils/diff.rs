@@ -0,0 +1,220 @@
+//! Differences between prompt collections and prompt contents
+
+use crate::core::data::{Prompt, PromptCollection};
+use crate::utils::output::OutputStyle;
+use similar::TextDiff;
+
+/// How a single prompt differs between two collections
+#[derive(Debug, Clone)]
+pub enum PromptChange {
+    Added(Prompt),
+    Removed(Prompt),
+    Modified {
+        before: Box<Prompt>,
+        after: Box<Prompt>,
+        fields: Vec<&'static str>,
+    },
+}
+
+/// Compare two collections, matching prompts by ID and then by description
+pub fn diff_collections(before: &PromptCollection, after: &PromptCollection) -> Vec<PromptChange> {
+    let mut before = before.clone();
+    let mut after = after.clone();
+    before.ensure_ids();
+    after.ensure_ids();
+
+    let mut unmatched: Vec<Option<&Prompt>> = before.prompts.iter().map(Some).collect();
+    let mut changes = Vec::new();
+
+    for prompt in &after.prompts {
+        let position = unmatched
+            .iter()
+            .position(|p| p.is_some_and(|p| p.id.is_some() && p.id == prompt.id))
+            .or_else(|| {
+                unmatched
+                    .iter()
+                    .position(|p| p.is_some_and(|p| p.description == prompt.description))
+            });
+
+        match position.and_then(|i| unmatched[i].take()) {
+            Some(old) => {
+                let fields = changed_fields(old, prompt);
+                if !fields.is_empty() {
+                    changes.push(PromptChange::Modified {
+                        before: Box::new(old.clone()),
+                        after: Box::new(prompt.clone()),
+                        fields,
+                    });
+                }
+            }
+            None => changes.push(PromptChange::Added(prompt.clone())),
+        }
+    }
+
+    changes.extend(
+        unmatched
+            .into_iter()
+            .flatten()
+            .map(|p| PromptChange::Removed(p.clone())),
+    );
+    changes
+}
+
+/// Names of the user-visible fields that differ between two prompts
+pub fn changed_fields(before: &Prompt, after: &Prompt) -> Vec<&'static str> {
+    field_values(before)
+        .into_iter()
+        .zip(field_values(after))
+        .filter(|((_, old), (_, new))| old != new)
+        .map(|((name, _), _)| name)
+        .collect()
+}
+
+fn field_values(prompt: &Prompt) -> [(&'static str, String); 5] {
+    [
+        ("Description", prompt.description.clone()),
+        ("Content", prompt.content.clone()),
+        ("Category", prompt.category.clone().unwrap_or_default()),
+        ("Tag", prompt.tag.clone().unwrap_or_default().join(", ")),
+        ("Output", prompt.output.clone().unwrap_or_default()),
+    ]
+}
+
+/// Unified diff of two texts with three lines of context
+pub fn unified_diff(before: &str, after: &str, before_label: &str, after_label: &str) -> String {
+    TextDiff::from_lines(before, after)
+        .unified_diff()
+        .context_radius(3)
+        .missing_newline_hint(false)
+        .header(before_label, after_label)
+        .to_string()
+}
+
+/// Print a unified diff, colouring added and removed lines
+pub fn print_unified_diff(diff: &str) {
+    for line in diff.lines() {
+        if line.starts_with("+++") || line.starts_with("---") {
+            println!("{}", OutputStyle::header(line));
+        } else if line.starts_with('+') {
+            println!("{}", OutputStyle::success(line));
+        } else if line.starts_with('-') {
+            println!("{}", OutputStyle::error(line));
+        } else if line.starts_with("@@") {
+            println!("{}", OutputStyle::info(line));
+        } else {
+            println!("{}", line);
+        }
+    }
+}
+
+/// Print a per-prompt summary of changes between two collections
+pub fn print_changes(changes: &[PromptChange], before_label: &str, after_label: &str) {
+    for change in changes {
+        match change {
+            PromptChange::Added(prompt) => {
+                println!(
+                    "{} {}",
+                    OutputStyle::success("+ Added:"),
+                    prompt.description
+                );
+            }
+            PromptChange::Removed(prompt) => {
+                println!(
+                    "{} {}",
+                    OutputStyle::error("- Removed:"),
+                    prompt.description
+                );
+            }
+            PromptChange::Modified {
+                before,
+                after,
+                fields,
+            } => {
+                println!(
+                    "{} {} {}",
+                    OutputStyle::warning("~ Modified:"),
+                    after.description,
+                    OutputStyle::muted(&format!("({})", fields.join(", ")))
+                );
+                print_field_changes(before, after, fields);
+                if fields.contains(&"Content") {
+                    print_unified_diff(&unified_diff(
+                        &before.content,
+                        &after.content,
+                        before_label,
+                        after_label,
+                    ));
+                }
+            }
+        }
+    }
+}
+
+fn print_field_changes(before: &Prompt, after: &Prompt, fields: &[&str]) {
+    let old_values = field_values(before);
+    let new_values = field_values(after);
+    for ((name, old), (_, new)) in old_values.iter().zip(new_values.iter()) {
+        if *name == "Content" || !fields.contains(name) {
+            continue;
+        }
+        let display = |value: &str| {
+            if value.is_empty() {
+                "(none)".to_string()
+            } else {
+                value.to_string()
+            }
+        };
+        println!("    {}:", OutputStyle::label(name));
+        println!(
+            "      {}",
+            OutputStyle::error(&format!("- {}", display(old)))
+        );
+        println!(
+            "      {}",
+            OutputStyle::success(&format!("+ {}", display(new)))
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prompt(description: &str, content: &str) -> Prompt {
+        let mut prompt = Prompt::new(description.to_string(), content.to_string());
+        prompt.id = Some(description.to_lowercase());
+        prompt
+    }
+
+    #[test]
+    fn test_diff_collections_reports_each_kind_of_change() {
+        let before = PromptCollection {
+            prompts: vec![
+                prompt("Keep", "same"),
+                prompt("Edit", "v1"),
+                prompt("Drop", "x"),
+            ],
+        };
+        let mut edited = prompt("Edit", "v2");
+        edited.category = Some("work".to_string());
+        let after = PromptCollection {
+            prompts: vec![prompt("Keep", "same"), edited, prompt("New", "y")],
+        };
+
+        let changes = diff_collections(&before, &after);
+        assert_eq!(changes.len(), 3);
+        assert!(matches!(&changes[0], PromptChange::Modified { fields, .. }
+            if fields == &vec!["Content", "Category"]));
+        assert!(matches!(&changes[1], PromptChange::Added(p) if p.description == "New"));
+        assert!(matches!(&changes[2], PromptChange::Removed(p) if p.description == "Drop"));
+    }
+
+    #[test]
+    fn test_unified_diff_marks_changed_lines() {
+        let diff = unified_diff("a\nb\nc\n", "a\nB\nc\n", "local", "remote");
+        assert!(diff.contains("--- local"));
+        assert!(diff.contains("+++ remote"));
+        assert!(diff.contains("-b\n"));
+        assert!(diff.contains("+B\n"));
+    }
+}
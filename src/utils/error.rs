@@ -1,19 +1,296 @@
 use crate::utils::output::OutputStyle;
+use std::path::PathBuf;
+use std::sync::Arc;
 use thiserror::Error;
 
+/// Process exit codes, one per error kind
+///
+/// | Code | Meaning                                        |
+/// |------|------------------------------------------------|
+/// | 0    | Success                                        |
+/// | 1    | Other errors (system, I/O)                     |
+/// | 2    | Invalid command line usage                     |
+/// | 3    | Prompt or other item not found                 |
+/// | 4    | Cancelled by the user                          |
+/// | 5    | A file or prompt template could not be parsed  |
+/// | 6    | Invalid configuration                          |
+/// | 7    | Conflicting changes (sync)                     |
+/// | 8    | Authentication failed or credentials missing   |
+/// | 9    | Network failure (connection, HTTP, rate limit) |
+/// | 10   | Other sync failures                            |
+///
+/// Clap itself exits with code 2 when arguments cannot be parsed; input
+/// missing for a non-interactive run uses the same code.
+pub mod exit_code {
+    pub const GENERAL: i32 = 1;
+    pub const USAGE: i32 = 2;
+    pub const NOT_FOUND: i32 = 3;
+    pub const CANCELLED: i32 = 4;
+    pub const PARSE: i32 = 5;
+    pub const CONFIG: i32 = 6;
+    pub const CONFLICT: i32 = 7;
+    pub const AUTH: i32 = 8;
+    pub const NETWORK: i32 = 9;
+    pub const SYNC: i32 = 10;
+}
+
+/// Shared, cloneable handle to an underlying error
+pub type ErrorSource = Arc<dyn std::error::Error + Send + Sync>;
+
 #[derive(Error, Debug, Clone)]
 pub enum AppError {
-    #[error("Network error: {0}")]
-    Network(String),
+    #[error("{0}")]
+    NotFound(String),
+
+    #[error("{0}")]
+    Cancelled(String),
+
+    /// Required input was not given and cannot be asked for
+    #[error("{0}")]
+    Usage(String),
+
+    #[error("Failed to parse {}{}: {message}", path.display(), line.map(|l| format!(" (line {})", l)).unwrap_or_default())]
+    Parse {
+        path: PathBuf,
+        line: Option<usize>,
+        message: String,
+        #[source]
+        source: Option<ErrorSource>,
+    },
+
+    /// A prompt template could not be parsed or expanded
+    #[error("Template error: {0}")]
+    Template(String),
+
+    #[error("Configuration error: {0}")]
+    Config(String),
+
+    /// Both sides changed and the operation refused to overwrite either
+    #[error("Conflict: {0}")]
+    Conflict(String),
+
+    #[error("Authentication failed: {0}")]
+    Auth(String),
+
+    #[error("Network error: {message}")]
+    Network {
+        message: String,
+        #[source]
+        source: Option<ErrorSource>,
+    },
+
+    /// The server could not be reached (DNS, refused connection, timeout, TLS)
+    #[error("Connection error: {message}")]
+    Connection {
+        message: String,
+        #[source]
+        source: Option<ErrorSource>,
+    },
+
+    /// The server answered with an unsuccessful status
+    #[error("HTTP error {status}: {message}")]
+    Http { status: u16, message: String },
+
+    /// The server refused the request because of rate limiting
+    #[error("Rate limited: {message}")]
+    RateLimited {
+        message: String,
+        retry_after: Option<u64>,
+    },
+
+    #[error("Sync error: {message}")]
+    Sync {
+        message: String,
+        #[source]
+        source: Option<ErrorSource>,
+    },
+
+    #[error("System error: {message}")]
+    System {
+        message: String,
+        #[source]
+        source: Option<ErrorSource>,
+    },
+
+    #[error("IO error: {message}")]
+    Io {
+        message: String,
+        #[source]
+        source: Option<ErrorSource>,
+    },
+}
+
+impl AppError {
+    /// I/O failure; attach the underlying error with [`Self::with_source`]
+    pub fn io(message: impl Into<String>) -> Self {
+        AppError::Io {
+            message: message.into(),
+            source: None,
+        }
+    }
+
+    /// Sync failure that is not a network or conflict error
+    pub fn sync(message: impl Into<String>) -> Self {
+        AppError::Sync {
+            message: message.into(),
+            source: None,
+        }
+    }
+
+    /// Failure of the environment, such as a tool that could not run
+    pub fn system(message: impl Into<String>) -> Self {
+        AppError::System {
+            message: message.into(),
+            source: None,
+        }
+    }
+
+    /// Network failure other than an unreachable server
+    pub fn network(message: impl Into<String>) -> Self {
+        AppError::Network {
+            message: message.into(),
+            source: None,
+        }
+    }
+
+    /// The server could not be reached
+    pub fn connection(message: impl Into<String>) -> Self {
+        AppError::Connection {
+            message: message.into(),
+            source: None,
+        }
+    }
 
-    #[error("Sync error: {0}")]
-    Sync(String),
+    /// Keep the error this one was built from, for `causes` in JSON output
+    ///
+    /// Variants that only carry a message are returned unchanged.
+    pub fn with_source<E>(mut self, error: E) -> Self
+    where
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        match &mut self {
+            AppError::Parse { source, .. }
+            | AppError::Network { source, .. }
+            | AppError::Connection { source, .. }
+            | AppError::Sync { source, .. }
+            | AppError::System { source, .. }
+            | AppError::Io { source, .. } => *source = Some(Arc::new(error)),
+            _ => {}
+        }
+        self
+    }
+
+    /// Parse error for a file, keeping the underlying error as the source
+    pub fn parse<E>(
+        path: impl Into<PathBuf>,
+        line: Option<usize>,
+        message: String,
+        error: E,
+    ) -> Self
+    where
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        AppError::Parse {
+            path: path.into(),
+            line,
+            message,
+            source: None,
+        }
+        .with_source(error)
+    }
 
-    #[error("System error: {0}")]
-    System(String),
+    /// Parse error for a file, locating the line from a TOML error span
+    pub fn toml_parse(path: impl Into<PathBuf>, content: &str, error: toml::de::Error) -> Self {
+        let line = error
+            .span()
+            .and_then(|span| content.get(..span.start))
+            // Errors at end of input point past the final newline
+            .map(|before| {
+                if before.len() == content.len() {
+                    before.trim_end_matches('\n')
+                } else {
+                    before
+                }
+            })
+            .map(|before| before.matches('\n').count() + 1);
+        let message = error.message().to_string();
+        AppError::parse(path, line, message, error)
+    }
 
-    #[error("IO error: {0}")]
-    Io(String),
+    /// Exit code documented in [`exit_code`]
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            AppError::NotFound(_) => exit_code::NOT_FOUND,
+            AppError::Cancelled(_) => exit_code::CANCELLED,
+            AppError::Usage(_) => exit_code::USAGE,
+            AppError::Parse { .. } | AppError::Template(_) => exit_code::PARSE,
+            AppError::Config(_) => exit_code::CONFIG,
+            AppError::Conflict(_) => exit_code::CONFLICT,
+            AppError::Auth(_) => exit_code::AUTH,
+            AppError::Network { .. }
+            | AppError::Connection { .. }
+            | AppError::Http { .. }
+            | AppError::RateLimited { .. } => exit_code::NETWORK,
+            AppError::Sync { .. } => exit_code::SYNC,
+            AppError::System { .. } | AppError::Io { .. } => exit_code::GENERAL,
+        }
+    }
+
+    /// Stable machine-readable name of the error kind
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AppError::NotFound(_) => "not_found",
+            AppError::Cancelled(_) => "cancelled",
+            AppError::Usage(_) => "usage",
+            AppError::Parse { .. } => "parse",
+            AppError::Template(_) => "template",
+            AppError::Config(_) => "config",
+            AppError::Conflict(_) => "conflict",
+            AppError::Auth(_) => "auth",
+            AppError::Network { .. } => "network",
+            AppError::Connection { .. } => "connection",
+            AppError::Http { .. } => "http",
+            AppError::RateLimited { .. } => "rate_limited",
+            AppError::Sync { .. } => "sync",
+            AppError::System { .. } => "system",
+            AppError::Io { .. } => "io",
+        }
+    }
+
+    /// JSON representation used by `--error-format json`
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut error = serde_json::json!({
+            "kind": self.kind(),
+            "message": self.to_string(),
+            "exit_code": self.exit_code(),
+        });
+
+        match self {
+            AppError::Parse { path, line, .. } => {
+                error["path"] = serde_json::json!(path);
+                error["line"] = serde_json::json!(line);
+            }
+            AppError::Http { status, .. } => {
+                error["status"] = serde_json::json!(status);
+            }
+            AppError::RateLimited { retry_after, .. } => {
+                error["retry_after"] = serde_json::json!(retry_after);
+            }
+            _ => {}
+        }
+
+        let mut source = std::error::Error::source(self);
+        let mut causes = Vec::new();
+        while let Some(cause) = source {
+            causes.push(cause.to_string());
+            source = cause.source();
+        }
+        if !causes.is_empty() {
+            error["causes"] = serde_json::json!(causes);
+        }
+
+        serde_json::json!({ "error": error })
+    }
 }
 
 /// Result type alias for consistent error handling across the application
@@ -31,20 +308,69 @@ pub enum FlowResult {
     Success(String),
 }
 
+impl FlowResult {
+    /// Turn a finished flow into a command result, so not-found and
+    /// cancelled flows exit with their own codes
+    pub fn into_result(self) -> AppResult<()> {
+        match self {
+            FlowResult::NotFound {
+                item_type,
+                search_term,
+            } => Err(AppError::NotFound(format!(
+                "{} '{}' not found",
+                item_type, search_term
+            ))),
+            FlowResult::Cancelled(msg) => Err(AppError::Cancelled(msg)),
+            flow @ FlowResult::EmptyList { .. } => {
+                handle_flow(flow);
+                Ok(())
+            }
+            FlowResult::Success(_) => Ok(()),
+        }
+    }
+}
+
 pub fn report_error(err: &AppError) {
     match err {
-        AppError::Network(msg) => {
-            println!("🌐 {}", OutputStyle::error(&format!("Network: {}", msg)));
+        AppError::NotFound(msg) => {
+            eprintln!("⚠️  {}", OutputStyle::warning(msg));
+        }
+        AppError::Cancelled(msg) => {
+            eprintln!("⏹️  {}", OutputStyle::muted(msg));
+        }
+        AppError::Network { message, .. } | AppError::Connection { message, .. } => {
+            eprintln!(
+                "🌐 {}",
+                OutputStyle::error(&format!("Network: {}", message))
+            );
+        }
+        AppError::Http { status, message } => {
+            eprintln!(
+                "🌐 {}",
+                OutputStyle::error(&format!("Network: {} ({})", message, status))
+            );
         }
-        AppError::Sync(msg) => {
-            println!("⚠️  {}", OutputStyle::warning(&format!("Sync: {}", msg)));
+        AppError::RateLimited { message, .. } => {
+            eprintln!(
+                "⏳ {}",
+                OutputStyle::warning(&format!("Rate limited: {}", message))
+            );
         }
-        AppError::Io(e) => {
-            eprintln!("❌ {}", OutputStyle::error(e));
+        AppError::Sync { message: msg, .. } | AppError::Conflict(msg) => {
+            eprintln!("⚠️  {}", OutputStyle::warning(&format!("Sync: {}", msg)));
         }
-        AppError::System(msg) => {
+        AppError::Auth(msg) => {
+            eprintln!("🔒 {}", OutputStyle::error(msg));
+        }
+        AppError::Io { message: msg, .. }
+        | AppError::System { message: msg, .. }
+        | AppError::Config(msg)
+        | AppError::Usage(msg) => {
             eprintln!("❌ {}", OutputStyle::error(msg));
         }
+        AppError::Parse { .. } | AppError::Template(_) => {
+            eprintln!("❌ {}", OutputStyle::error(&err.to_string()));
+        }
     }
 }
 
@@ -69,3 +395,55 @@ pub fn handle_flow(flow: FlowResult) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_toml_parse_error_reports_line() {
+        let content = "[[prompts]]\nDescription = \"ok\"\nContent = \n";
+        let error = toml::from_str::<toml::Value>(content).unwrap_err();
+        let err = AppError::toml_parse("prompts.toml", content, error);
+
+        assert_eq!(err.exit_code(), exit_code::PARSE);
+        assert!(matches!(err, AppError::Parse { line: Some(3), .. }));
+        assert!(err.to_string().contains("prompts.toml (line 3)"));
+
+        let json = err.to_json();
+        assert_eq!(json["error"]["kind"], "parse");
+        assert_eq!(json["error"]["line"], 3);
+        assert!(json["error"]["causes"].is_array());
+    }
+
+    #[test]
+    fn test_io_errors_keep_their_source() {
+        let io_error = std::fs::read_to_string("/missing/prompts.toml").unwrap_err();
+        let err =
+            AppError::io(format!("Failed to read prompt file: {}", io_error)).with_source(io_error);
+
+        assert_eq!(err.exit_code(), exit_code::GENERAL);
+        assert!(std::error::Error::source(&err).is_some());
+        let json = err.to_json();
+        assert_eq!(json["error"]["kind"], "io");
+        assert_eq!(json["error"]["causes"].as_array().unwrap().len(), 1);
+        assert!(AppError::sync("no remote").to_json()["error"]["causes"].is_null());
+    }
+
+    #[test]
+    fn test_flow_results_map_to_exit_codes() {
+        let not_found = FlowResult::NotFound {
+            item_type: "Prompt".to_string(),
+            search_term: "x".to_string(),
+        }
+        .into_result()
+        .unwrap_err();
+        assert_eq!(not_found.exit_code(), exit_code::NOT_FOUND);
+
+        let cancelled = FlowResult::Cancelled("stop".to_string())
+            .into_result()
+            .unwrap_err();
+        assert_eq!(cancelled.exit_code(), exit_code::CANCELLED);
+        assert!(FlowResult::Success(String::new()).into_result().is_ok());
+    }
+}
@@ -2,8 +2,9 @@ use crate::utils::error::{AppError, AppResult};
 use serde_json;
 
 pub fn generate_html(prompts: &[crate::core::data::Prompt]) -> AppResult<String> {
-    let prompts_json = serde_json::to_string(prompts)
-        .map_err(|e| AppError::System(format!("Failed to serialize prompts to JSON: {}", e)))?;
+    let prompts_json = serde_json::to_string(prompts).map_err(|e| {
+        AppError::system(format!("Failed to serialize prompts to JSON: {}", e)).with_source(e)
+    })?;
 
     let html = format!(
         r#"
@@ -720,7 +721,9 @@ pub fn open_browser(path: &str) -> AppResult<()> {
         std::process::Command::new("cmd")
             .args(["/C", "start", path])
             .spawn()
-            .map_err(|e| AppError::System(format!("Failed to open browser: {}", e)))?;
+            .map_err(|e| {
+                AppError::system(format!("Failed to open browser: {}", e)).with_source(e)
+            })?;
     }
 
     #[cfg(target_os = "macos")]
@@ -728,7 +731,9 @@ pub fn open_browser(path: &str) -> AppResult<()> {
         std::process::Command::new("open")
             .arg(path)
             .spawn()
-            .map_err(|e| AppError::System(format!("Failed to open browser: {}", e)))?;
+            .map_err(|e| {
+                AppError::system(format!("Failed to open browser: {}", e)).with_source(e)
+            })?;
     }
 
     #[cfg(target_os = "linux")]
@@ -736,7 +741,9 @@ pub fn open_browser(path: &str) -> AppResult<()> {
         std::process::Command::new("xdg-open")
             .arg(path)
             .spawn()
-            .map_err(|e| AppError::System(format!("Failed to open browser: {}", e)))?;
+            .map_err(|e| {
+                AppError::system(format!("Failed to open browser: {}", e)).with_source(e)
+            })?;
     }
 
     Ok(())
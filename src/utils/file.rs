@@ -0,0 +1,209 @@
+use crate::utils::error::{AppError, AppResult};
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Replace `path` with `content` so readers see either the old or the new file
+///
+/// The content goes to a temporary file in the same directory, is flushed to
+/// disk and then renamed over the target, so a crash never leaves a
+/// truncated file behind. A symlinked file (as in a dotfiles repository) is
+/// written through the link, and the file keeps its permissions.
+pub fn write_atomic(path: &Path, content: &str) -> io::Result<()> {
+    let path = &resolve_symlink(path);
+    let permissions = fs::metadata(path).ok().map(|m| m.permissions());
+    let temp_path = temp_path(path);
+    let result =
+        write_and_sync(&temp_path, content, permissions).and_then(|_| fs::rename(&temp_path, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result?;
+
+    sync_parent_dir(path);
+    Ok(())
+}
+
+/// The file a symlink points to, so renaming replaces the file, not the link
+fn resolve_symlink(path: &Path) -> PathBuf {
+    match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_symlink() => {
+            fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+        }
+        _ => path.to_path_buf(),
+    }
+}
+
+fn temp_path(path: &Path) -> PathBuf {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    path.with_file_name(format!(
+        ".{}.{}.tmp",
+        file_name,
+        uuid::Uuid::new_v4().simple()
+    ))
+}
+
+fn write_and_sync(
+    path: &Path,
+    content: &str,
+    permissions: Option<fs::Permissions>,
+) -> io::Result<()> {
+    let mut file = File::create(path)?;
+    if let Some(permissions) = permissions {
+        file.set_permissions(permissions)?;
+    }
+    file.write_all(content.as_bytes())?;
+    file.sync_all()
+}
+
+/// Create `path` with `content` unless it already exists
+///
+/// Another writer may have created the file in the meantime, so a
+/// concurrent first run must not clobber prompts that were just saved.
+pub fn write_new(path: &Path, content: &str) -> io::Result<()> {
+    let mut file = match OpenOptions::new().write(true).create_new(true).open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    file.write_all(content.as_bytes())?;
+    file.sync_all()
+}
+
+/// Persist the rename itself; not every platform can open a directory
+fn sync_parent_dir(path: &Path) {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty())
+        && let Ok(dir) = File::open(parent)
+    {
+        let _ = dir.sync_all();
+    }
+}
+
+/// Advisory exclusive lock on a file, released when dropped
+///
+/// The lock is taken on a `<file>.lock` companion rather than the file
+/// itself, because atomic writes replace the file and a lock on the old
+/// inode would no longer protect anything.
+pub struct FileLock {
+    _file: File,
+}
+
+impl FileLock {
+    /// Block until the lock for `path` is acquired
+    pub fn exclusive(path: &Path) -> AppResult<Self> {
+        let lock_path = lock_path(path);
+        let file = open_lock_file(&lock_path)?;
+
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => {
+                eprintln!(
+                    "⏳ Waiting for another promptheus process to release {}...",
+                    path.display()
+                );
+                file.lock().map_err(|e| lock_error(&lock_path, e))?;
+            }
+            Err(TryLockError::Error(e)) => return Err(lock_error(&lock_path, e)),
+        }
+
+        Ok(Self { _file: file })
+    }
+}
+
+fn open_lock_file(lock_path: &Path) -> AppResult<File> {
+    OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(lock_path)
+        .map_err(|e| {
+            AppError::io(format!(
+                "Failed to open lock file: {}: {}",
+                lock_path.display(),
+                e
+            ))
+            .with_source(e)
+        })
+}
+
+fn lock_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".lock");
+    PathBuf::from(name)
+}
+
+fn lock_error(lock_path: &Path, e: io::Error) -> AppError {
+    AppError::io(format!(
+        "Failed to lock file: {}: {}",
+        lock_path.display(),
+        e
+    ))
+    .with_source(e)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    #[test]
+    fn test_write_atomic_replaces_content_without_leftovers() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("prompts.toml");
+
+        write_atomic(&path, "first").unwrap();
+        write_atomic(&path, "second").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second");
+        let entries: Vec<_> = fs::read_dir(dir.path()).unwrap().collect();
+        assert_eq!(entries.len(), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_atomic_keeps_symlinks_and_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("dotfiles-prompts.toml");
+        let link = dir.path().join("prompts.toml");
+        fs::write(&target, "first").unwrap();
+        fs::set_permissions(&target, fs::Permissions::from_mode(0o600)).unwrap();
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        write_atomic(&link, "second").unwrap();
+
+        assert!(
+            fs::symlink_metadata(&link)
+                .unwrap()
+                .file_type()
+                .is_symlink()
+        );
+        assert_eq!(fs::read_to_string(&target).unwrap(), "second");
+        let mode = fs::metadata(&target).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    #[test]
+    fn test_lock_blocks_until_released() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("prompts.toml");
+        let guard = FileLock::exclusive(&path).unwrap();
+
+        let (sender, receiver) = mpsc::channel();
+        let waiter_path = path.clone();
+        let waiter = std::thread::spawn(move || {
+            let _lock = FileLock::exclusive(&waiter_path).unwrap();
+            sender.send(()).unwrap();
+        });
+
+        assert!(receiver.recv_timeout(Duration::from_millis(200)).is_err());
+        drop(guard);
+        receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        waiter.join().unwrap();
+    }
+}
@@ -103,3 +103,21 @@ pub fn truncate_string(text: &str, max_len: usize) -> String {
         text.to_string()
     }
 }
+
+/// Lowercase file name fragment with runs of other characters turned into dashes
+pub fn slugify(text: &str) -> String {
+    let slug = text
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    let slug: String = slug.chars().take(60).collect();
+    let slug = slug.trim_end_matches('-');
+
+    if slug.is_empty() {
+        "prompt".to_string()
+    } else {
+        slug.to_string()
+    }
+}
@@ -0,0 +1,158 @@
+use crate::cli::ImportFormat;
+use crate::core::data::{Prompt, PromptCollection};
+use crate::utils::error::{AppError, AppResult};
+use std::path::Path;
+
+/// Resolve the import format from the explicit flag or the file extension
+pub fn detect_format(path: &Path, format: Option<&ImportFormat>) -> AppResult<ImportFormat> {
+    if let Some(format) = format {
+        return Ok(format.clone());
+    }
+
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_lowercase());
+
+    match extension.as_deref() {
+        Some("toml") => Ok(ImportFormat::Toml),
+        Some("json") => Ok(ImportFormat::Json),
+        Some("yaml") | Some("yml") => Ok(ImportFormat::Yaml),
+        _ => Err(AppError::system(format!(
+            "Cannot detect import format for {}. Use --format toml|json|yaml",
+            path.display()
+        ))),
+    }
+}
+
+/// Read and parse an import file into a prompt collection
+pub fn load_import_file(path: &Path, format: &ImportFormat) -> AppResult<PromptCollection> {
+    let content = std::fs::read_to_string(path).map_err(|e| {
+        AppError::io(format!(
+            "Failed to read import file: {}: {}",
+            path.display(),
+            e
+        ))
+        .with_source(e)
+    })?;
+
+    parse_collection(path, &content, format)
+}
+
+/// Parse content in the given format into a prompt collection
+///
+/// JSON and YAML accept either a `prompts` table or a bare list of prompts,
+/// so the output of `list --format json` can be imported as-is.
+pub fn parse_collection(
+    path: &Path,
+    content: &str,
+    format: &ImportFormat,
+) -> AppResult<PromptCollection> {
+    if content.trim().is_empty() {
+        return Ok(PromptCollection::default());
+    }
+
+    match format {
+        ImportFormat::Toml => {
+            toml::from_str(content).map_err(|e| AppError::toml_parse(path, content, e))
+        }
+        ImportFormat::Json => {
+            let parsed = if content.trim_start().starts_with('[') {
+                serde_json::from_str::<Vec<Prompt>>(content).map(PromptCollection::from)
+            } else {
+                serde_json::from_str(content)
+            };
+            parsed.map_err(|e| AppError::parse(path, Some(e.line()), e.to_string(), e))
+        }
+        ImportFormat::Yaml => {
+            let value: serde_yaml::Value =
+                serde_yaml::from_str(content).map_err(|e| yaml_error(path, e))?;
+            let parsed = if value.is_sequence() {
+                serde_yaml::from_value::<Vec<Prompt>>(value).map(PromptCollection::from)
+            } else {
+                serde_yaml::from_value(value)
+            };
+            parsed.map_err(|e| yaml_error(path, e))
+        }
+    }
+}
+
+fn yaml_error(path: &Path, error: serde_yaml::Error) -> AppError {
+    let line = error.location().map(|location| location.line());
+    AppError::parse(path, line, error.to_string(), error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_format_from_extension() {
+        assert!(matches!(
+            detect_format(Path::new("pack.yml"), None),
+            Ok(ImportFormat::Yaml)
+        ));
+        assert!(matches!(
+            detect_format(Path::new("pack.JSON"), None),
+            Ok(ImportFormat::Json)
+        ));
+        assert!(matches!(
+            detect_format(Path::new("pack.txt"), Some(&ImportFormat::Toml)),
+            Ok(ImportFormat::Toml)
+        ));
+        assert!(detect_format(Path::new("pack.txt"), None).is_err());
+    }
+
+    #[test]
+    fn test_parse_all_formats() {
+        let toml_content = r#"
+[[prompts]]
+Description = "Review"
+Content = "Review <file>"
+Category = "dev"
+Tag = ["code"]
+Created_at = "2024-01-01 10:00:00"
+"#;
+        let json_content = r#"[{"Description": "Review", "Content": "Review <file>",
+            "Category": "dev", "Tag": ["code"], "Created_at": "2024-01-01 10:00:00"}]"#;
+        let yaml_content = r#"
+prompts:
+  - Description: Review
+    Content: Review <file>
+    Category: dev
+    Tag: [code]
+    Created_at: "2024-01-01 10:00:00"
+"#;
+
+        for (content, format) in [
+            (toml_content, ImportFormat::Toml),
+            (json_content, ImportFormat::Json),
+            (yaml_content, ImportFormat::Yaml),
+        ] {
+            let collection = parse_collection(Path::new("test"), content, &format).unwrap();
+            assert_eq!(collection.prompts.len(), 1);
+            assert_eq!(collection.prompts[0].description, "Review");
+            assert_eq!(collection.prompts[0].category.as_deref(), Some("dev"));
+        }
+    }
+
+    #[test]
+    fn test_merge_reports_added_updated_skipped() {
+        let mut local = PromptCollection::default();
+        local.add_prompt(Prompt::new("Keep".to_string(), "same".to_string()));
+        local.add_prompt(Prompt::new("Change".to_string(), "old".to_string()));
+
+        let mut incoming = PromptCollection::default();
+        incoming.add_prompt(Prompt::new("Keep".to_string(), "same".to_string()));
+        incoming.add_prompt(Prompt::new("Change".to_string(), "new".to_string()));
+        incoming.add_prompt(Prompt::new("Fresh".to_string(), "added".to_string()));
+
+        let report = local.merge(incoming);
+
+        assert_eq!(report.added, vec!["Fresh"]);
+        assert_eq!(report.updated, vec!["Change"]);
+        assert_eq!(report.skipped, vec!["Keep"]);
+        assert_eq!(local.prompts.len(), 3);
+        assert_eq!(local.find_by_description("Change").unwrap().content, "new");
+    }
+}
@@ -1,11 +1,17 @@
 pub mod console;
+pub mod diff;
 pub mod error;
 pub mod export;
+pub mod file;
 pub mod format;
+pub mod import;
 pub mod output;
 pub mod pagination;
 pub mod search;
+pub mod sources;
 pub mod stats;
+pub mod template;
+pub mod variables;
 
 pub use console::*;
 pub use export::*;
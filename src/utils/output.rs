@@ -5,6 +5,7 @@ use crate::utils::error::{AppError, AppResult};
 use crate::utils::format::{
     format_category_info, format_datetime, format_tags_comma, format_tags_hash, truncate_string,
 };
+use crate::utils::variables::VariableSpec;
 use colored::*;
 
 /// Display components for a prompt, used for consistent formatting
@@ -160,9 +161,11 @@ impl OutputStyle {
         Self::print_content_full(&prompt.content);
     }
 
-    pub fn print_prompt_list_preview(prompt: &Prompt) {
+    pub fn print_prompt_list_preview(prompt: &Prompt, id_len: usize) {
         Self::print_field_colored("Description", &prompt.description, Self::description);
-        Self::print_prompt_field(PromptField::Id, prompt);
+        if prompt.id.is_some() {
+            Self::print_field_colored("ID", prompt.short_id(id_len), Self::muted);
+        }
 
         // Category with tag color
         let (cat_str, _) = format_category_info(&prompt.category);
@@ -226,7 +229,7 @@ impl OutputStyle {
     }
 
     /// Format a prompt for simple list display
-    pub fn format_prompt_line(prompt: &Prompt, config: &Config) -> String {
+    pub fn format_prompt_line(prompt: &Prompt, config: &Config, id_len: usize) -> String {
         let display = Self::build_prompt_display(prompt, config);
 
         let category_display = if let Some(category) = &prompt.category {
@@ -240,7 +243,8 @@ impl OutputStyle {
         };
 
         format!(
-            "{}: {}{}",
+            "{} {}: {}{}",
+            Self::muted(prompt.short_id(id_len)),
             Self::description(&display.description),
             category_display,
             display.tags_formatted
@@ -248,18 +252,26 @@ impl OutputStyle {
     }
 
     /// Print variables found in prompt content
-    pub fn print_variables_list(variables: &[(String, Option<String>)]) {
+    pub fn print_variables_list(variables: &[VariableSpec]) {
         println!("\n🔧 {}:", Self::header("This prompt contains variables"));
-        for (name, default) in variables {
-            if let Some(default_val) = default {
-                println!(
-                    "  <{}={}>",
-                    Self::command(&format!("<{}>", name)),
-                    Self::muted(default_val)
-                );
-            } else {
-                println!("  {}", Self::command(&format!("<{}>", name)));
+        for spec in variables {
+            let mut details = vec![spec.kind.to_string()];
+            if let Some(default_val) = &spec.default {
+                details.push(format!("default: {}", default_val));
+            } else if spec.optional {
+                details.push("optional".to_string());
             }
+            let description = spec
+                .description
+                .as_deref()
+                .map(|d| format!(" {}", d))
+                .unwrap_or_default();
+            println!(
+                "  {}{} {}",
+                Self::command(&format!("<{}>", spec.name)),
+                description,
+                Self::muted(&format!("({})", details.join(", ")))
+            );
         }
     }
 
@@ -362,7 +374,15 @@ pub struct DisplayFormatter;
 
 impl DisplayFormatter {
     /// Format prompts list according to the specified format
-    pub fn format_list(prompts: &[Prompt], format: &ListFormat, config: &Config) -> AppResult<()> {
+    ///
+    /// IDs are shortened to `id_len` characters, enough to tell all stored
+    /// prompts apart.
+    pub fn format_list(
+        prompts: &[Prompt],
+        format: &ListFormat,
+        config: &Config,
+        id_len: usize,
+    ) -> AppResult<()> {
         if prompts.is_empty() {
             crate::utils::error::handle_flow(crate::utils::error::FlowResult::EmptyList {
                 item_type: "prompts matching your criteria".to_string(),
@@ -371,9 +391,9 @@ impl DisplayFormatter {
         }
 
         match format {
-            ListFormat::Simple => Self::print_simple_list(prompts, config),
-            ListFormat::Detailed => Self::print_detailed_list(prompts),
-            ListFormat::Table => Self::print_table_list(prompts, config),
+            ListFormat::Simple => Self::print_simple_list(prompts, config, id_len),
+            ListFormat::Detailed => Self::print_detailed_list(prompts, id_len),
+            ListFormat::Table => Self::print_table_list(prompts, id_len),
             ListFormat::Json => Self::print_json_list(prompts)?,
         }
 
@@ -417,13 +437,13 @@ impl DisplayFormatter {
     }
 
     /// Print simple list format
-    fn print_simple_list(prompts: &[Prompt], config: &Config) {
+    fn print_simple_list(prompts: &[Prompt], config: &Config, id_len: usize) {
         crate::utils::print_prompt_count(prompts.len());
         println!("{}", OutputStyle::separator());
 
         for prompt in prompts {
             // Show basic info line
-            let formatted_line = OutputStyle::format_prompt_line(prompt, config);
+            let formatted_line = OutputStyle::format_prompt_line(prompt, config, id_len);
             println!("{}", formatted_line);
 
             // Show truncated content if content exists and is configured for preview
@@ -435,7 +455,7 @@ impl DisplayFormatter {
     }
 
     /// Print detailed list format
-    fn print_detailed_list(prompts: &[Prompt]) {
+    fn print_detailed_list(prompts: &[Prompt], id_len: usize) {
         OutputStyle::print_header("📝 Detailed Prompt List");
 
         for (i, prompt) in prompts.iter().enumerate() {
@@ -444,7 +464,7 @@ impl DisplayFormatter {
                 i + 1,
                 OutputStyle::description(&prompt.description)
             );
-            OutputStyle::print_prompt_list_preview(prompt);
+            OutputStyle::print_prompt_list_preview(prompt, id_len);
 
             if i < prompts.len() - 1 {
                 println!("{}", OutputStyle::separator());
@@ -453,7 +473,7 @@ impl DisplayFormatter {
     }
 
     /// Print table format
-    fn print_table_list(prompts: &[Prompt], _config: &Config) {
+    fn print_table_list(prompts: &[Prompt], id_len: usize) {
         crate::utils::print_prompt_count(prompts.len());
 
         // Calculate column widths
@@ -478,21 +498,25 @@ impl DisplayFormatter {
 
         // Print header with colors
         println!(
-            "┌─{}─┬─{}─┬─{}─┐",
+            "┌─{}─┬─{}─┬─{}─┬─{}─┐",
+            "─".repeat(id_len),
             "─".repeat(max_title_width),
             "─".repeat(max_tag_width),
             "─".repeat(19) // Date column
         );
         println!(
-            "│ {:<width_title$} │ {:<width_tags$} │ {:^19} │",
+            "│ {:<width_id$} │ {:<width_title$} │ {:<width_tags$} │ {:^19} │",
+            OutputStyle::header("ID"),
             OutputStyle::header("Description"),
             OutputStyle::header("Tags"),
             OutputStyle::header("Updated"),
+            width_id = id_len,
             width_title = max_title_width,
             width_tags = max_tag_width
         );
         println!(
-            "├─{}─┼─{}─┼─{}─┤",
+            "├─{}─┼─{}─┼─{}─┼─{}─┤",
+            "─".repeat(id_len),
             "─".repeat(max_title_width),
             "─".repeat(max_tag_width),
             "─".repeat(19)
@@ -505,17 +529,20 @@ impl DisplayFormatter {
             let tag_str = truncate_string(&tag_str, max_tag_width);
 
             println!(
-                "│ {:<width_title$} │ {:<width_tags$} │ {} │",
+                "│ {:<width_id$} │ {:<width_title$} │ {:<width_tags$} │ {} │",
+                OutputStyle::muted(prompt.short_id(id_len)),
                 OutputStyle::description(&description),
                 OutputStyle::tags(&tag_str),
                 OutputStyle::muted(&format_datetime(&prompt.updated_at)),
+                width_id = id_len,
                 width_title = max_title_width,
                 width_tags = max_tag_width
             );
         }
 
         println!(
-            "└─{}─┴─{}─┴─{}─┘",
+            "└─{}─┴─{}─┴─{}─┴─{}─┘",
+            "─".repeat(id_len),
             "─".repeat(max_title_width),
             "─".repeat(max_tag_width),
             "─".repeat(19)
@@ -524,8 +551,9 @@ impl DisplayFormatter {
 
     /// Print JSON format
     fn print_json_list(prompts: &[Prompt]) -> AppResult<()> {
-        let json = serde_json::to_string_pretty(prompts)
-            .map_err(|e| AppError::System(format!("Failed to serialize prompts to JSON: {}", e)))?;
+        let json = serde_json::to_string_pretty(prompts).map_err(|e| {
+            AppError::system(format!("Failed to serialize prompts to JSON: {}", e)).with_source(e)
+        })?;
         println!("{}", json);
         Ok(())
     }
@@ -5,7 +5,7 @@ use crossterm::terminal::size;
 pub fn get_terminal_size() -> AppResult<(u16, u16)> {
     size()
         .map(|(width, height)| (height, width))
-        .map_err(|e| AppError::System(format!("Failed to get terminal size: {}", e)))
+        .map_err(|e| AppError::system(format!("Failed to get terminal size: {}", e)).with_source(e))
 }
 
 /// Check if content should be paginated based on terminal height
@@ -18,16 +18,16 @@ pub fn should_paginate(content: &str, terminal_height: u16) -> bool {
 /// Display content using minus pager for static content
 pub fn paginate_static_content(content: &str) -> AppResult<()> {
     let pager = minus::Pager::new();
-    pager
-        .push_str(content)
-        .map_err(|e| AppError::System(format!("Failed to push content to pager: {}", e)))?;
+    pager.push_str(content).map_err(|e| {
+        AppError::system(format!("Failed to push content to pager: {}", e)).with_source(e)
+    })?;
 
     if let Err(e) = minus::page_all(pager) {
         // Don't propagate error if user quits pager (e.g., Ctrl+C)
         if e.to_string().to_lowercase().contains("abort") {
             return Ok(());
         }
-        return Err(AppError::System(format!("Failed to run pager: {}", e)));
+        return Err(AppError::system(format!("Failed to run pager: {}", e)).with_source(e));
     }
 
     Ok(())
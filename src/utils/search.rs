@@ -83,7 +83,7 @@ pub fn interactive_search_with_external_tool(
     // Check if the select command is available
     let cmd_parts: Vec<&str> = select_cmd.split_whitespace().collect();
     if cmd_parts.is_empty() {
-        return Err(AppError::System(format!(
+        return Err(AppError::system(format!(
             "Invalid select command: {}",
             select_cmd
         )));
@@ -135,26 +135,28 @@ pub fn interactive_search_with_external_tool(
         .stdout(Stdio::piped())
         .stderr(Stdio::piped()); // Capture stderr instead of inheriting
 
-    let mut child = cmd
-        .spawn()
-        .map_err(|e| AppError::System(format!("Failed to spawn command: {}: {}", select_cmd, e)))?;
+    let mut child = cmd.spawn().map_err(|e| {
+        AppError::system(format!("Failed to spawn command: {}: {}", select_cmd, e)).with_source(e)
+    })?;
 
     // Write items to stdin
     if let Some(stdin) = child.stdin.as_mut() {
         for item in items {
             // Write each item followed by NULL character for fzf --read0
-            stdin
-                .write_all(item.as_bytes())
-                .map_err(|e| AppError::Io(format!("Failed to write to stdin: {}", e)))?;
+            stdin.write_all(item.as_bytes()).map_err(|e| {
+                AppError::io(format!("Failed to write to stdin: {}", e)).with_source(e)
+            })?;
             stdin.write_all(b"\0").map_err(|e| {
-                AppError::Io(format!("Failed to write NULL separator to stdin: {}", e))
+                AppError::io(format!("Failed to write NULL separator to stdin: {}", e))
+                    .with_source(e)
             })?;
         }
     }
 
     // Read the result
     let output = child.wait_with_output().map_err(|e| {
-        AppError::System(format!("Failed to read output from: {}: {}", select_cmd, e))
+        AppError::system(format!("Failed to read output from: {}: {}", select_cmd, e))
+            .with_source(e)
     })?;
 
     // Check if the command was successful
@@ -0,0 +1,192 @@
+//! Variable values read from files, stdin, the clipboard or a command
+//!
+//! A value given with `--vars` or typed in when asked can name a source
+//! instead of holding the text itself:
+//!
+//! - `@file:path` reads a file
+//! - `@stdin` reads standard input, once; later uses get the same text
+//! - `@clipboard` reads the clipboard
+//! - `@cmd:git diff` runs a shell command, only when `allow_command_vars` is
+//!   set and after confirmation on a terminal
+//!
+//! Sources are limited to [`MAX_SOURCE_BYTES`], and trailing newlines are
+//! dropped as in shell command substitution. `@@` starts a literal `@`.
+
+use crate::utils::console::{prompt_yes_no, read_clipboard};
+use crate::utils::error::{AppError, AppResult};
+use std::cell::OnceCell;
+use std::io::{IsTerminal, Read};
+use std::process::{Command, Stdio};
+
+/// Largest value a source may produce
+pub const MAX_SOURCE_BYTES: usize = 1024 * 1024;
+
+#[derive(Debug, Default)]
+pub struct ValueSources {
+    allow_commands: bool,
+    stdin: OnceCell<String>,
+}
+
+impl ValueSources {
+    pub fn new(allow_commands: bool) -> Self {
+        Self {
+            allow_commands,
+            stdin: OnceCell::new(),
+        }
+    }
+
+    /// The text a value stands for; values naming no source are returned as is
+    pub fn expand(&self, value: &str) -> AppResult<String> {
+        if let Some(literal) = value.strip_prefix("@@") {
+            return Ok(format!("@{}", literal));
+        }
+
+        let text = if let Some(path) = value.strip_prefix("@file:") {
+            read_file(path.trim())?
+        } else if value == "@stdin" {
+            self.read_stdin()?
+        } else if value == "@clipboard" {
+            let text = read_clipboard()?;
+            check_size(text.len(), "@clipboard")?;
+            text
+        } else if let Some(command) = value.strip_prefix("@cmd:") {
+            self.run_command(command.trim())?
+        } else {
+            return Ok(value.to_string());
+        };
+
+        Ok(text.trim_end_matches(['\r', '\n']).to_string())
+    }
+
+    fn read_stdin(&self) -> AppResult<String> {
+        if let Some(text) = self.stdin.get() {
+            return Ok(text.clone());
+        }
+        let stdin = std::io::stdin();
+        if stdin.is_terminal() {
+            return Err(AppError::Usage(
+                "@stdin needs input piped to promptheus".to_string(),
+            ));
+        }
+
+        let text = read_limited(stdin.lock(), "@stdin")?;
+        Ok(self.stdin.get_or_init(|| text).clone())
+    }
+
+    fn run_command(&self, command: &str) -> AppResult<String> {
+        if !self.allow_commands {
+            return Err(AppError::Config(
+                "@cmd: values are disabled; set allow_command_vars = true in the [general] section to allow them"
+                    .to_string(),
+            ));
+        }
+        if !std::io::stdin().is_terminal() {
+            return Err(AppError::Usage(format!(
+                "Running '{}' needs confirmation on a terminal",
+                command
+            )));
+        }
+        if !prompt_yes_no(&format!("Run `{}` to fill a variable?", command))? {
+            return Err(AppError::Cancelled(format!("Did not run '{}'", command)));
+        }
+
+        let mut child = shell(command)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|e| {
+                AppError::system(format!("Failed to run '{}': {}", command, e)).with_source(e)
+            })?;
+        let stdout = child.stdout.take().expect("stdout is piped");
+        let output = read_limited(stdout, &format!("Output of '{}'", command));
+        if output.is_err() {
+            // Stop a command whose output is too large instead of waiting for it
+            let _ = child.kill();
+        }
+        let status = child.wait().map_err(|e| {
+            AppError::system(format!("Failed to wait for '{}': {}", command, e)).with_source(e)
+        })?;
+        let output = output?;
+
+        if !status.success() {
+            return Err(AppError::system(format!(
+                "Command '{}' failed ({})",
+                command, status
+            )));
+        }
+        Ok(output)
+    }
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("cmd");
+    shell.args(["/C", command]);
+    shell
+}
+
+#[cfg(not(windows))]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.args(["-c", command]);
+    shell
+}
+
+fn read_file(path: &str) -> AppResult<String> {
+    let file = std::fs::File::open(path).map_err(|e| {
+        AppError::io(format!("Failed to read variable file: {}: {}", path, e)).with_source(e)
+    })?;
+    read_limited(file, path)
+}
+
+/// Read text, failing once it grows past [`MAX_SOURCE_BYTES`]
+fn read_limited(reader: impl Read, what: &str) -> AppResult<String> {
+    let mut bytes = Vec::new();
+    reader
+        .take(MAX_SOURCE_BYTES as u64 + 1)
+        .read_to_end(&mut bytes)
+        .map_err(|e| AppError::io(format!("Failed to read {}: {}", what, e)).with_source(e))?;
+    check_size(bytes.len(), what)?;
+    String::from_utf8(bytes).map_err(|_| AppError::Usage(format!("{} is not UTF-8 text", what)))
+}
+
+fn check_size(len: usize, what: &str) -> AppResult<()> {
+    if len > MAX_SOURCE_BYTES {
+        return Err(AppError::Usage(format!(
+            "{} is larger than the {} KiB limit for variable values",
+            what,
+            MAX_SOURCE_BYTES / 1024
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_sources_and_literals() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.md");
+        std::fs::write(&path, "line 1\nline 2\n\n").unwrap();
+        let sources = ValueSources::new(false);
+
+        let value = format!("@file:{}", path.display());
+        assert_eq!(sources.expand(&value).unwrap(), "line 1\nline 2");
+        assert_eq!(sources.expand("@@file:x").unwrap(), "@file:x");
+        assert_eq!(sources.expand("@alice").unwrap(), "@alice");
+        assert!(matches!(
+            sources.expand("@file:/missing/file"),
+            Err(AppError::Io { .. })
+        ));
+        // Commands are refused unless enabled in the config
+        assert!(matches!(
+            sources.expand("@cmd:echo hi"),
+            Err(AppError::Config(_))
+        ));
+
+        std::fs::write(&path, vec![b'x'; MAX_SOURCE_BYTES + 1]).unwrap();
+        assert!(matches!(sources.expand(&value), Err(AppError::Usage(_))));
+    }
+}
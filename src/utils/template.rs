@@ -0,0 +1,775 @@
+//! Opt-in template mode for prompts
+//!
+//! A prompt whose content starts with `{# template #}` is rendered as a
+//! small Jinja-like template:
+//!
+//! - `{{ name | trim | upper }}` inserts a variable through filters
+//! - `{% if name %}...{% else %}...{% endif %}`, also with `not`, `==` and `!=`
+//! - `{% for item in items %}...{% endfor %}` over the lines of a value, or
+//!   its comma-separated items when it is a single line
+//! - `{% include "Shared rules" %}` inserts another prompt, found by ID,
+//!   unique ID prefix or description
+//! - `{# ... #}` is a comment
+//!
+//! `<name>` placeholders keep working in the text around the tags. A block
+//! tag or comment alone on its line removes the whole line from the output;
+//! an include is replaced by the included prompt and keeps its line.
+
+use crate::core::data::Prompt;
+use crate::utils::console::{parse_command_variables, replace_command_variables};
+use crate::utils::error::{AppError, AppResult};
+use crate::utils::variables::VariableSpec;
+use std::collections::HashMap;
+
+/// First line of a prompt that opts in to template mode
+pub const TEMPLATE_MARKER: &str = "{# template #}";
+
+/// Looks up a prompt referenced by `include`
+pub type PromptResolver<'a> = dyn FnMut(&str) -> AppResult<Option<Prompt>> + 'a;
+
+/// Whether `content` opts in to template mode
+pub fn is_template(content: &str) -> bool {
+    content.trim_start().starts_with(TEMPLATE_MARKER)
+}
+
+#[derive(Debug)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+#[derive(Debug)]
+enum Node {
+    Text(String),
+    Output(Expr),
+    If {
+        condition: Condition,
+        then: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+    For {
+        item: String,
+        list: String,
+        body: Vec<Node>,
+    },
+}
+
+#[derive(Debug)]
+struct Expr {
+    operand: Operand,
+    filters: Vec<Filter>,
+}
+
+#[derive(Debug)]
+enum Operand {
+    Variable(String),
+    Literal(String),
+}
+
+#[derive(Debug)]
+enum Filter {
+    Upper,
+    Lower,
+    Trim,
+    Indent(usize),
+    JsonEscape,
+}
+
+#[derive(Debug)]
+struct Condition {
+    negate: bool,
+    left: Expr,
+    /// `==` (true) or `!=` (false) and the right-hand side
+    compare: Option<(bool, Expr)>,
+}
+
+impl Template {
+    /// Parse a prompt, expanding its includes
+    ///
+    /// Includes are resolved with `resolve`; a prompt that ends up including
+    /// itself is reported with the chain of prompts involved.
+    pub fn for_prompt(prompt: &Prompt, resolve: &mut PromptResolver) -> AppResult<Self> {
+        let nodes = parse(&prompt.content, resolve, &mut vec![include_key(prompt)])?;
+        Ok(Self { nodes })
+    }
+
+    /// Variables the template needs, in order of appearance
+    ///
+    /// `<name>` placeholders keep their declared type. Variables inserted with
+    /// `{{ }}` are required; those only tested or looped over are optional.
+    pub fn variables(&self) -> Vec<VariableSpec> {
+        let mut collector = VariableCollector::default();
+        collector.visit(&self.nodes, &mut Vec::new());
+        collector.variables
+    }
+
+    pub fn render(&self, values: &HashMap<String, String>) -> AppResult<String> {
+        let mut output = String::new();
+        render_nodes(&self.nodes, values, &mut Vec::new(), &mut output);
+        Ok(output)
+    }
+}
+
+/// A prompt on the include stack: its identity and its description
+fn include_key(prompt: &Prompt) -> (String, String) {
+    let id = prompt
+        .id
+        .clone()
+        .unwrap_or_else(|| prompt.description.clone());
+    (id, prompt.description.clone())
+}
+
+#[derive(Debug)]
+enum Token {
+    Text(String),
+    Output { source: String, line: usize },
+    Tag { source: String, line: usize },
+}
+
+fn error_at(line: usize, message: &str) -> AppError {
+    AppError::Template(format!("line {}: {}", line, message))
+}
+
+fn tokenize(source: &str) -> AppResult<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut position = 0;
+
+    while let Some(offset) = ["{{", "{%", "{#"]
+        .iter()
+        .filter_map(|open| source[position..].find(open))
+        .min()
+    {
+        let start = position + offset;
+        let line = source[..start].matches('\n').count() + 1;
+        let opener = &source[start..start + 2];
+        let closer = match opener {
+            "{{" => "}}",
+            "{%" => "%}",
+            _ => "#}",
+        };
+        let inner_start = start + 2;
+        let inner_end = source[inner_start..]
+            .find(closer)
+            .map(|i| inner_start + i)
+            .ok_or_else(|| error_at(line, &format!("'{}' is never closed", opener)))?;
+        let inner = source[inner_start..inner_end].trim().to_string();
+        let mut end = inner_end + 2;
+        let mut text_end = start;
+
+        // Block tags and comments alone on their line take the line with them
+        if opener != "{{" && !inner.starts_with("include") {
+            let line_start = source[..start].rfind('\n').map_or(0, |i| i + 1);
+            let line_end = source[end..].find('\n').map_or(source.len(), |i| end + i);
+            if line_start >= position
+                && source[line_start..start].trim().is_empty()
+                && source[end..line_end].trim().is_empty()
+            {
+                text_end = line_start;
+                end = (line_end + 1).min(source.len());
+            }
+        }
+
+        if text_end > position {
+            tokens.push(Token::Text(source[position..text_end].to_string()));
+        }
+        match opener {
+            "{{" => tokens.push(Token::Output {
+                source: inner,
+                line,
+            }),
+            "{%" => tokens.push(Token::Tag {
+                source: inner,
+                line,
+            }),
+            _ => {}
+        }
+        position = end;
+    }
+
+    if position < source.len() {
+        tokens.push(Token::Text(source[position..].to_string()));
+    }
+    Ok(tokens)
+}
+
+fn parse(
+    source: &str,
+    resolve: &mut PromptResolver,
+    stack: &mut Vec<(String, String)>,
+) -> AppResult<Vec<Node>> {
+    let mut parser = Parser {
+        tokens: tokenize(source)?.into_iter(),
+        resolve,
+        stack,
+    };
+    match parser.parse_nodes()? {
+        (nodes, None) => Ok(nodes),
+        (_, Some((keyword, line))) => Err(error_at(
+            line,
+            &format!("'{}' without a matching opening tag", keyword),
+        )),
+    }
+}
+
+/// Keyword and line of the tag that closed a block
+type Closing = Option<(String, usize)>;
+
+struct Parser<'a, 'r> {
+    tokens: std::vec::IntoIter<Token>,
+    resolve: &'a mut PromptResolver<'r>,
+    stack: &'a mut Vec<(String, String)>,
+}
+
+impl Parser<'_, '_> {
+    /// Nodes up to the end of input or a closing tag, returned with its line
+    fn parse_nodes(&mut self) -> AppResult<(Vec<Node>, Closing)> {
+        let mut nodes = Vec::new();
+
+        while let Some(token) = self.tokens.next() {
+            match token {
+                Token::Text(text) => nodes.push(Node::Text(text)),
+                Token::Output { source, line } => {
+                    nodes.push(Node::Output(parse_expr(&source, line)?));
+                }
+                Token::Tag { source, line } => {
+                    let (keyword, rest) = source
+                        .split_once(char::is_whitespace)
+                        .map_or((source.as_str(), ""), |(k, r)| (k, r.trim()));
+                    match keyword {
+                        "if" => nodes.push(self.parse_if(rest, line)?),
+                        "for" => nodes.push(self.parse_for(rest, line)?),
+                        "include" => nodes.extend(self.parse_include(rest, line)?),
+                        "else" | "endif" | "endfor" => {
+                            return Ok((nodes, Some((keyword.to_string(), line))));
+                        }
+                        other => {
+                            return Err(error_at(line, &format!("unknown tag '{}'", other)));
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok((nodes, None))
+    }
+
+    fn parse_if(&mut self, source: &str, line: usize) -> AppResult<Node> {
+        let condition = parse_condition(source, line)?;
+        let (then, closing) = self.parse_nodes()?;
+        let otherwise = match closing {
+            Some((keyword, _)) if keyword == "endif" => Vec::new(),
+            Some((keyword, _)) if keyword == "else" => match self.parse_nodes()? {
+                (otherwise, Some((keyword, _))) if keyword == "endif" => otherwise,
+                _ => return Err(error_at(line, "'if' is missing its 'endif'")),
+            },
+            _ => return Err(error_at(line, "'if' is missing its 'endif'")),
+        };
+        Ok(Node::If {
+            condition,
+            then,
+            otherwise,
+        })
+    }
+
+    fn parse_for(&mut self, source: &str, line: usize) -> AppResult<Node> {
+        let words: Vec<&str> = source.split_whitespace().collect();
+        let [item, "in", list] = words[..] else {
+            return Err(error_at(line, "expected 'for item in list'"));
+        };
+        if !is_identifier(item) || !is_identifier(list) {
+            return Err(error_at(line, "expected 'for item in list'"));
+        }
+
+        match self.parse_nodes()? {
+            (body, Some((keyword, _))) if keyword == "endfor" => Ok(Node::For {
+                item: item.to_string(),
+                list: list.to_string(),
+                body,
+            }),
+            _ => Err(error_at(line, "'for' is missing its 'endfor'")),
+        }
+    }
+
+    fn parse_include(&mut self, source: &str, line: usize) -> AppResult<Vec<Node>> {
+        let identifier = match parse_expr(source, line)? {
+            Expr {
+                operand: Operand::Literal(identifier),
+                filters,
+            } if filters.is_empty() => identifier,
+            _ => return Err(error_at(line, "expected 'include \"prompt\"'")),
+        };
+
+        let prompt = (self.resolve)(&identifier)?.ok_or_else(|| {
+            AppError::NotFound(format!("Included prompt '{}' not found", identifier))
+        })?;
+        let key = include_key(&prompt);
+        if self.stack.iter().any(|(id, _)| *id == key.0) {
+            let chain: Vec<String> = self
+                .stack
+                .iter()
+                .chain([&key])
+                .map(|(_, description)| format!("'{}'", description))
+                .collect();
+            return Err(error_at(
+                line,
+                &format!("include cycle: {}", chain.join(" -> ")),
+            ));
+        }
+
+        // Plain prompts are inserted as text, templates are expanded in place
+        if !is_template(&prompt.content) {
+            return Ok(vec![Node::Text(prompt.content)]);
+        }
+        self.stack.push(key);
+        let nodes = parse(&prompt.content, self.resolve, self.stack).map_err(|e| match e {
+            AppError::Template(message) => AppError::Template(format!(
+                "in included prompt '{}': {}",
+                prompt.description, message
+            )),
+            other => other,
+        })?;
+        self.stack.pop();
+        Ok(nodes)
+    }
+}
+
+fn is_identifier(word: &str) -> bool {
+    !word.is_empty()
+        && word
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
+
+#[derive(Debug, PartialEq)]
+enum ExprToken {
+    Word(String),
+    Literal(String),
+    Pipe,
+    Open,
+    Close,
+    Equal,
+    NotEqual,
+}
+
+fn lex_expr(source: &str, line: usize) -> AppResult<Vec<ExprToken>> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '|' => {
+                chars.next();
+                tokens.push(ExprToken::Pipe);
+            }
+            '(' => {
+                chars.next();
+                tokens.push(ExprToken::Open);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(ExprToken::Close);
+            }
+            '=' | '!' => {
+                chars.next();
+                if chars.next() != Some('=') {
+                    return Err(error_at(line, "expected '==' or '!='"));
+                }
+                tokens.push(if c == '=' {
+                    ExprToken::Equal
+                } else {
+                    ExprToken::NotEqual
+                });
+            }
+            '"' | '\'' => {
+                chars.next();
+                let mut literal = String::new();
+                loop {
+                    match chars.next() {
+                        Some(q) if q == c => break,
+                        Some('\\') => literal.extend(chars.next()),
+                        Some(other) => literal.push(other),
+                        None => return Err(error_at(line, "unterminated string")),
+                    }
+                }
+                tokens.push(ExprToken::Literal(literal));
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(&w) = chars.peek() {
+                    if !(w.is_alphanumeric() || matches!(w, '_' | '-' | '.')) {
+                        break;
+                    }
+                    word.push(w);
+                    chars.next();
+                }
+                if word.is_empty() {
+                    return Err(error_at(line, &format!("unexpected '{}'", c)));
+                }
+                tokens.push(ExprToken::Word(word));
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn parse_expr(source: &str, line: usize) -> AppResult<Expr> {
+    let tokens = lex_expr(source, line)?;
+    let mut tokens = tokens.into_iter().peekable();
+    let expr = parse_expr_tokens(&mut tokens, line)?;
+    match tokens.next() {
+        None => Ok(expr),
+        Some(_) => Err(error_at(line, &format!("unexpected text in '{}'", source))),
+    }
+}
+
+fn parse_expr_tokens(
+    tokens: &mut std::iter::Peekable<std::vec::IntoIter<ExprToken>>,
+    line: usize,
+) -> AppResult<Expr> {
+    let operand = match tokens.next() {
+        // Bare numbers are literals, anything else is a variable
+        Some(ExprToken::Word(word)) if word.parse::<f64>().is_ok() => Operand::Literal(word),
+        Some(ExprToken::Word(word)) => Operand::Variable(word),
+        Some(ExprToken::Literal(literal)) => Operand::Literal(literal),
+        _ => return Err(error_at(line, "expected a variable or a quoted value")),
+    };
+
+    let mut filters = Vec::new();
+    while tokens.next_if_eq(&ExprToken::Pipe).is_some() {
+        let Some(ExprToken::Word(name)) = tokens.next() else {
+            return Err(error_at(line, "expected a filter name after '|'"));
+        };
+        let argument = if tokens.next_if_eq(&ExprToken::Open).is_some() {
+            let argument = match tokens.next() {
+                Some(ExprToken::Word(word)) => word,
+                _ => return Err(error_at(line, "expected a filter argument")),
+            };
+            if tokens.next() != Some(ExprToken::Close) {
+                return Err(error_at(line, "expected ')'"));
+            }
+            Some(argument)
+        } else {
+            None
+        };
+
+        filters.push(match (name.as_str(), argument) {
+            ("upper", None) => Filter::Upper,
+            ("lower", None) => Filter::Lower,
+            ("trim", None) => Filter::Trim,
+            ("json_escape", None) => Filter::JsonEscape,
+            ("indent", None) => Filter::Indent(4),
+            ("indent", Some(width)) => Filter::Indent(
+                width
+                    .parse()
+                    .map_err(|_| error_at(line, "indent takes a number of spaces"))?,
+            ),
+            (name, _) => return Err(error_at(line, &format!("unknown filter '{}'", name))),
+        });
+    }
+
+    Ok(Expr { operand, filters })
+}
+
+fn parse_condition(source: &str, line: usize) -> AppResult<Condition> {
+    let mut tokens = lex_expr(source, line)?.into_iter().peekable();
+    let negate = tokens
+        .next_if_eq(&ExprToken::Word("not".to_string()))
+        .is_some();
+    let left = parse_expr_tokens(&mut tokens, line)?;
+    let compare = match tokens.next() {
+        None => None,
+        Some(ExprToken::Equal) => Some((true, parse_expr_tokens(&mut tokens, line)?)),
+        Some(ExprToken::NotEqual) => Some((false, parse_expr_tokens(&mut tokens, line)?)),
+        Some(_) => return Err(error_at(line, &format!("unexpected text in '{}'", source))),
+    };
+    if tokens.next().is_some() {
+        return Err(error_at(line, &format!("unexpected text in '{}'", source)));
+    }
+
+    Ok(Condition {
+        negate,
+        left,
+        compare,
+    })
+}
+
+#[derive(Default)]
+struct VariableCollector {
+    variables: Vec<VariableSpec>,
+    /// Names declared by a `<name>` placeholder, whose spec takes precedence
+    declared: Vec<String>,
+}
+
+impl VariableCollector {
+    fn visit(&mut self, nodes: &[Node], loop_items: &mut Vec<String>) {
+        for node in nodes {
+            match node {
+                Node::Text(text) => {
+                    for spec in parse_command_variables(text) {
+                        self.declare(spec);
+                    }
+                }
+                Node::Output(expr) => self.expression(expr, false, loop_items),
+                Node::If {
+                    condition,
+                    then,
+                    otherwise,
+                } => {
+                    self.expression(&condition.left, true, loop_items);
+                    if let Some((_, right)) = &condition.compare {
+                        self.expression(right, true, loop_items);
+                    }
+                    self.visit(then, loop_items);
+                    self.visit(otherwise, loop_items);
+                }
+                Node::For { item, list, body } => {
+                    if !loop_items.contains(list) {
+                        self.reference(list, true);
+                    }
+                    loop_items.push(item.clone());
+                    self.visit(body, loop_items);
+                    loop_items.pop();
+                }
+            }
+        }
+    }
+
+    fn expression(&mut self, expr: &Expr, optional: bool, loop_items: &[String]) {
+        if let Operand::Variable(name) = &expr.operand
+            && !loop_items.contains(name)
+        {
+            self.reference(name, optional);
+        }
+    }
+
+    fn declare(&mut self, spec: VariableSpec) {
+        if self.declared.contains(&spec.name) {
+            return;
+        }
+        self.declared.push(spec.name.clone());
+        match self.variables.iter_mut().find(|v| v.name == spec.name) {
+            Some(existing) => *existing = spec,
+            None => self.variables.push(spec),
+        }
+    }
+
+    fn reference(&mut self, name: &str, optional: bool) {
+        match self.variables.iter_mut().find(|v| v.name == name) {
+            Some(existing) if !self.declared.contains(&existing.name) => {
+                existing.optional &= optional;
+            }
+            Some(_) => {}
+            None => self.variables.push(VariableSpec::text(name, optional)),
+        }
+    }
+}
+
+fn render_nodes(
+    nodes: &[Node],
+    values: &HashMap<String, String>,
+    scope: &mut Vec<(String, String)>,
+    output: &mut String,
+) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => output.push_str(&replace_command_variables(text, values)),
+            Node::Output(expr) => output.push_str(&evaluate(expr, values, scope)),
+            Node::If {
+                condition,
+                then,
+                otherwise,
+            } => {
+                let left = evaluate(&condition.left, values, scope);
+                let holds = match &condition.compare {
+                    None => is_truthy(&left),
+                    Some((equal, right)) => (left == evaluate(right, values, scope)) == *equal,
+                };
+                let branch = if holds != condition.negate {
+                    then
+                } else {
+                    otherwise
+                };
+                render_nodes(branch, values, scope, output);
+            }
+            Node::For { item, list, body } => {
+                let list = lookup(list, values, scope);
+                for element in list_items(&list) {
+                    scope.push((item.clone(), element));
+                    render_nodes(body, values, scope, output);
+                    scope.pop();
+                }
+            }
+        }
+    }
+}
+
+fn lookup(name: &str, values: &HashMap<String, String>, scope: &[(String, String)]) -> String {
+    scope
+        .iter()
+        .rev()
+        .find(|(item, _)| item == name)
+        .map(|(_, value)| value.clone())
+        .or_else(|| values.get(name).cloned())
+        .unwrap_or_default()
+}
+
+fn evaluate(expr: &Expr, values: &HashMap<String, String>, scope: &[(String, String)]) -> String {
+    let mut value = match &expr.operand {
+        Operand::Variable(name) => lookup(name, values, scope),
+        Operand::Literal(literal) => literal.clone(),
+    };
+
+    for filter in &expr.filters {
+        value = match filter {
+            Filter::Upper => value.to_uppercase(),
+            Filter::Lower => value.to_lowercase(),
+            Filter::Trim => value.trim().to_string(),
+            // Like Jinja, the first line stays where the tag is
+            Filter::Indent(width) => value.replace('\n', &format!("\n{}", " ".repeat(*width))),
+            Filter::JsonEscape => {
+                let quoted = serde_json::Value::String(value).to_string();
+                quoted[1..quoted.len() - 1].to_string()
+            }
+        };
+    }
+    value
+}
+
+fn is_truthy(value: &str) -> bool {
+    !matches!(
+        value.trim().to_lowercase().as_str(),
+        "" | "false" | "no" | "0"
+    )
+}
+
+/// Items of a list value: its lines, or comma-separated items on one line
+fn list_items(value: &str) -> Vec<String> {
+    let separator = if value.contains('\n') { '\n' } else { ',' };
+    value
+        .split(separator)
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template_prompt(description: &str, content: &str) -> Prompt {
+        Prompt::new(
+            description.to_string(),
+            format!("{}\n{}", TEMPLATE_MARKER, content),
+        )
+    }
+
+    #[test]
+    fn test_template_renders_conditions_loops_filters_and_includes() {
+        let rules = Prompt::new("Rules".to_string(), "Be <tone=kind>.".to_string());
+        let footer = template_prompt("Footer", "Thanks, {{ name | upper }}!\n");
+        let main = template_prompt(
+            "Main",
+            concat!(
+                "{% include \"Rules\" %}\n",
+                "{% if strict %}\n",
+                "Strict mode.\n",
+                "{% else %}\n",
+                "Relaxed mode.\n",
+                "{% endif %}\n",
+                "{% for file in files %}\n",
+                "- {{ file | trim }}\n",
+                "{% endfor %}\n",
+                "{\"q\": \"{{ question | json_escape }}\"}\n",
+                "  {{ code | indent(2) }}\n",
+                "{% if lang != \"go\" %}not go{% endif %}\n",
+                "{% include \"Footer\" %}",
+            ),
+        );
+        let prompts = [rules, footer];
+        let mut resolve = |identifier: &str| {
+            Ok(prompts
+                .iter()
+                .find(|p| p.description == identifier)
+                .cloned())
+        };
+
+        let template = Template::for_prompt(&main, &mut resolve).unwrap();
+        let names: Vec<(String, bool)> = template
+            .variables()
+            .into_iter()
+            .map(|v| (v.name.clone(), v.is_required()))
+            .collect();
+        assert_eq!(
+            names,
+            [
+                ("tone", false),
+                ("strict", false),
+                ("files", false),
+                ("question", true),
+                ("code", true),
+                ("lang", false),
+                ("name", true),
+            ]
+            .map(|(n, r)| (n.to_string(), r))
+        );
+
+        let values: HashMap<String, String> = [
+            ("files", "a.rs, b.rs"),
+            ("question", "say \"hi\""),
+            ("code", "fn a() {\n}"),
+            ("name", "ada"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+        assert_eq!(
+            template.render(&values).unwrap(),
+            concat!(
+                "Be kind.\n",
+                "Relaxed mode.\n",
+                "- a.rs\n",
+                "- b.rs\n",
+                "{\"q\": \"say \\\"hi\\\"\"}\n",
+                "  fn a() {\n",
+                "  }\n",
+                "not go\n",
+                "Thanks, ADA!\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_template_errors() {
+        let a = template_prompt("A", "{% include \"B\" %}");
+        let b = template_prompt("B", "{% include \"A\" %}");
+        let prompts = [a.clone(), b];
+        let mut resolve = |identifier: &str| {
+            Ok(prompts
+                .iter()
+                .find(|p| p.description == identifier)
+                .cloned())
+        };
+
+        let err = Template::for_prompt(&a, &mut resolve).unwrap_err();
+        assert!(matches!(&err, AppError::Template(m) if m.contains("include cycle")));
+
+        for content in [
+            "{% if x %}open",
+            "{{ x | shout }}",
+            "{% endfor %}",
+            "{{ x",
+            "{% include \"Missing\" %}",
+        ] {
+            let prompt = template_prompt("Broken", content);
+            assert!(
+                Template::for_prompt(&prompt, &mut resolve).is_err(),
+                "{}",
+                content
+            );
+        }
+    }
+}
@@ -0,0 +1,556 @@
+//! Values for the `<name>` and `<name=default>` placeholders in prompts
+//!
+//! Values come from `--vars name=value`, then a `--vars-file`, then
+//! `PROMPTHEUS_VAR_<NAME>` environment variables. `--vars` values and typed
+//! input may name a source instead, see [`crate::utils::sources`]. Whatever
+//! is still missing is asked for on a terminal; without one, defaults are
+//! used and required variables without a value are reported as an error.
+//!
+//! A placeholder may declare what it accepts, e.g.
+//! `<lang:rust|go "Target language"=rust>`, `<lines?:int>` or
+//! `<ticket:/^[A-Z]+-[0-9]+$/>`. Every value is checked against it, whether
+//! typed in or passed on the command line.
+
+use crate::utils::console::{
+    parse_command_variables, prompt_for_variables, replace_command_variables,
+};
+use crate::utils::error::{AppError, AppResult};
+use crate::utils::output::OutputStyle;
+use crate::utils::sources::ValueSources;
+use crate::utils::template::Template;
+use regex::Regex;
+use std::collections::HashMap;
+use std::fmt;
+use std::io::IsTerminal;
+use std::path::Path;
+
+/// Prefix of environment variables holding variable values
+const ENV_PREFIX: &str = "PROMPTHEUS_VAR_";
+
+#[derive(Debug, Default)]
+pub struct VariableValues {
+    /// `--vars` values, which may name a source such as `@file:path`
+    vars: HashMap<String, String>,
+    /// Values from `--vars-file`, taken literally
+    file_values: HashMap<String, String>,
+    /// `PROMPTHEUS_VAR_*` environment variables
+    env: HashMap<String, String>,
+    sources: ValueSources,
+    /// Whether missing values can be asked for on a terminal
+    interactive: bool,
+}
+
+impl VariableValues {
+    /// Values given on the command line; `--vars` wins over `--vars-file`
+    pub fn from_args(vars: &[String], vars_file: Option<&Path>) -> AppResult<Self> {
+        let file_values = match vars_file {
+            Some(path) => read_vars_file(path)?,
+            None => HashMap::new(),
+        };
+
+        let mut given = HashMap::new();
+        for pair in vars {
+            let (name, value) = pair.split_once('=').ok_or_else(|| {
+                AppError::Usage(format!(
+                    "Invalid --vars value '{}': expected name=value",
+                    pair
+                ))
+            })?;
+            given.insert(name.trim().to_string(), value.to_string());
+        }
+
+        Ok(Self {
+            vars: given,
+            file_values,
+            env: std::env::vars_os()
+                .filter_map(|(key, value)| {
+                    Some((key.into_string().ok()?, value.into_string().ok()?))
+                })
+                .filter(|(key, _)| key.starts_with(ENV_PREFIX))
+                .collect(),
+            sources: ValueSources::default(),
+            interactive: std::io::stdin().is_terminal(),
+        })
+    }
+
+    /// Allow `@cmd:` values, as set by `allow_command_vars`
+    pub fn allow_commands(mut self, allowed: bool) -> Self {
+        self.sources = ValueSources::new(allowed);
+        self
+    }
+
+    /// Whether no value was given, on the command line or in the environment
+    pub fn is_empty(&self) -> bool {
+        self.vars.is_empty() && self.file_values.is_empty() && self.env.is_empty()
+    }
+
+    /// Replace every variable in `content` with its value
+    pub fn render(&self, content: &str) -> AppResult<String> {
+        let values = self.resolve(parse_command_variables(content))?;
+        Ok(replace_command_variables(content, &values))
+    }
+
+    /// Render a template with values for the variables it uses
+    pub fn render_template(&self, template: &Template) -> AppResult<String> {
+        let values = self.resolve(template.variables())?;
+        template.render(&values)
+    }
+
+    /// Values for `variables`, asking for missing ones on a terminal
+    fn resolve(&self, mut variables: Vec<VariableSpec>) -> AppResult<HashMap<String, String>> {
+        let mut seen = Vec::new();
+        variables.retain(|spec| {
+            let first = !seen.contains(&spec.name);
+            seen.push(spec.name.clone());
+            first
+        });
+
+        let mut values = HashMap::new();
+        let mut unresolved = Vec::new();
+        for spec in variables {
+            let given = match self.vars.get(&spec.name) {
+                Some(value) => Some(self.sources.expand(value)?),
+                None => self
+                    .file_values
+                    .get(&spec.name)
+                    .cloned()
+                    .or_else(|| self.env.get(&env_key(&spec.name)).cloned()),
+            };
+            match given {
+                Some(value) => {
+                    let value = spec.validate(&value).map_err(|message| {
+                        AppError::Usage(format!(
+                            "Invalid value for variable '{}': {}",
+                            spec.name, message
+                        ))
+                    })?;
+                    values.insert(spec.name, value);
+                }
+                None => unresolved.push(spec),
+            }
+        }
+
+        if !unresolved.is_empty() {
+            if self.interactive {
+                OutputStyle::print_variables_list(&unresolved);
+                values.extend(prompt_for_variables(unresolved, &self.sources)?);
+            } else {
+                let missing: Vec<&str> = unresolved
+                    .iter()
+                    .filter(|spec| spec.is_required())
+                    .map(|spec| spec.name.as_str())
+                    .collect();
+                if !missing.is_empty() {
+                    return Err(AppError::Usage(format!(
+                        "Missing values for variables: {}. Pass them with --vars name=value, --vars-file or {}<NAME> environment variables",
+                        missing.join(", "),
+                        ENV_PREFIX
+                    )));
+                }
+            }
+        }
+
+        Ok(values)
+    }
+}
+
+/// Kind of value a variable accepts
+#[derive(Debug, Clone, PartialEq)]
+pub enum VariableKind {
+    String,
+    Int,
+    Bool,
+    /// Path to an existing file
+    File,
+    Multiline,
+    Enum(Vec<String>),
+}
+
+impl fmt::Display for VariableKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VariableKind::String => write!(f, "text"),
+            VariableKind::Int => write!(f, "whole number"),
+            VariableKind::Bool => write!(f, "yes/no"),
+            VariableKind::File => write!(f, "file path"),
+            VariableKind::Multiline => write!(f, "multiline text"),
+            VariableKind::Enum(choices) => write!(f, "one of {}", choices.join(", ")),
+        }
+    }
+}
+
+/// A variable as declared by its placeholder
+#[derive(Debug, Clone)]
+pub struct VariableSpec {
+    pub name: String,
+    pub kind: VariableKind,
+    pub description: Option<String>,
+    pub default: Option<String>,
+    /// Declared with `?`: may be left empty
+    pub optional: bool,
+    /// Plain `<name>` placeholder, which accepts an empty answer as before
+    /// typed variables existed
+    pub allow_empty: bool,
+    pub pattern: Option<Regex>,
+}
+
+impl VariableSpec {
+    /// Parse the part of a placeholder before `=`
+    ///
+    /// The syntax is `name[?][:kind][ /regex/][ "description"]`, where kind
+    /// is `string`, `int`, `bool`, `file`, `multiline` or choices separated by
+    /// `|`. Text that does not follow it is a plain variable named after the
+    /// whole text, as before typed variables existed.
+    pub fn parse(raw: &str, default: Option<&str>) -> Self {
+        let default = default.map(str::to_string);
+        Self::parse_typed(raw, default.clone()).unwrap_or(Self {
+            default,
+            allow_empty: true,
+            ..Self::text(raw, false)
+        })
+    }
+
+    /// Free-text variable without a default
+    pub fn text(name: &str, optional: bool) -> Self {
+        Self {
+            name: name.to_string(),
+            kind: VariableKind::String,
+            description: None,
+            default: None,
+            optional,
+            allow_empty: false,
+            pattern: None,
+        }
+    }
+
+    fn parse_typed(raw: &str, default: Option<String>) -> Option<Self> {
+        let (head, spec) = match raw.split_once(':') {
+            Some((head, spec)) => (head, Some(spec.trim())),
+            None => (raw, None),
+        };
+        let (name, optional) = match head.strip_suffix('?') {
+            Some(name) => (name, true),
+            None => (head, false),
+        };
+        let is_identifier = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.'));
+        if !is_identifier || spec.map_or(!optional, str::is_empty) {
+            return None;
+        }
+
+        let mut rest = spec.unwrap_or_default();
+        let mut description = None;
+        if let Some(start) = rest.find('"') {
+            let quoted = rest[start + 1..].strip_suffix('"')?;
+            description = Some(quoted.trim().to_string());
+            rest = rest[..start].trim_end();
+        }
+        let mut pattern = None;
+        if let Some(start) = rest.find('/') {
+            let source = rest[start + 1..].strip_suffix('/')?;
+            pattern = Some(Regex::new(source).ok()?);
+            rest = rest[..start].trim_end();
+        }
+        let kind = match rest {
+            "" | "string" => VariableKind::String,
+            "int" => VariableKind::Int,
+            "bool" => VariableKind::Bool,
+            "file" => VariableKind::File,
+            "multiline" => VariableKind::Multiline,
+            choices if choices.contains('|') => VariableKind::Enum(
+                choices
+                    .split('|')
+                    .map(str::trim)
+                    .filter(|c| !c.is_empty())
+                    .map(str::to_string)
+                    .collect(),
+            ),
+            _ => return None,
+        };
+
+        Some(Self {
+            name: name.to_string(),
+            kind,
+            description,
+            default,
+            optional,
+            allow_empty: false,
+            pattern,
+        })
+    }
+
+    /// Whether a value must be given because there is nothing to fall back on
+    pub fn is_required(&self) -> bool {
+        !self.optional && self.default.is_none()
+    }
+
+    /// Prompt text shown when asking for a value
+    pub fn label(&self) -> String {
+        let mut label = self.name.clone();
+        if let Some(description) = &self.description {
+            label.push_str(&format!(" ({})", description));
+        }
+        match &self.kind {
+            VariableKind::Bool => label.push_str(" [y/n]"),
+            VariableKind::Enum(_) => label.push_str(" [number or value]"),
+            _ => {}
+        }
+        if let Some(default) = &self.default {
+            label.push_str(&format!(" [default: {}]", default));
+        }
+        label
+    }
+
+    /// Check a value, returning it in canonical form or why it was rejected
+    ///
+    /// An empty value stands for the default, or for nothing when the
+    /// variable is optional or a plain `<name>` placeholder.
+    pub fn validate(&self, input: &str) -> Result<String, String> {
+        let input = input.trim_end_matches(['\r', '\n']);
+        if input.trim().is_empty() {
+            return match &self.default {
+                Some(default) => Ok(default.clone()),
+                None if self.optional || self.allow_empty => Ok(String::new()),
+                None => Err("a value is required".to_string()),
+            };
+        }
+
+        let value = match &self.kind {
+            VariableKind::String | VariableKind::Multiline => input.to_string(),
+            VariableKind::Int => {
+                let trimmed = input.trim();
+                trimmed
+                    .parse::<i64>()
+                    .map_err(|_| format!("'{}' is not a whole number", trimmed))?;
+                trimmed.to_string()
+            }
+            VariableKind::Bool => match input.trim().to_lowercase().as_str() {
+                "y" | "yes" | "true" | "1" => "true".to_string(),
+                "n" | "no" | "false" | "0" => "false".to_string(),
+                other => return Err(format!("'{}' is not yes or no", other)),
+            },
+            VariableKind::File => {
+                let trimmed = input.trim();
+                if !Path::new(trimmed).is_file() {
+                    return Err(format!("no such file: {}", trimmed));
+                }
+                trimmed.to_string()
+            }
+            VariableKind::Enum(choices) => {
+                let trimmed = input.trim();
+                let by_number = trimmed
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|n| n.checked_sub(1))
+                    .and_then(|index| choices.get(index));
+                choices
+                    .iter()
+                    .find(|c| c.as_str() == trimmed)
+                    .or_else(|| choices.iter().find(|c| c.eq_ignore_ascii_case(trimmed)))
+                    .or(by_number)
+                    .cloned()
+                    .ok_or_else(|| format!("expected one of: {}", choices.join(", ")))?
+            }
+        };
+
+        if let Some(pattern) = &self.pattern
+            && !pattern.is_match(&value)
+        {
+            return Err(format!("'{}' does not match /{}/", value, pattern));
+        }
+        Ok(value)
+    }
+}
+
+/// Environment variable for `name`: upper case, other characters as `_`
+fn env_key(name: &str) -> String {
+    let suffix: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("{}{}", ENV_PREFIX, suffix)
+}
+
+/// Read a JSON or TOML file holding a flat table of values
+fn read_vars_file(path: &Path) -> AppResult<HashMap<String, String>> {
+    let content = std::fs::read_to_string(path).map_err(|e| {
+        AppError::io(format!(
+            "Failed to read variables file: {}: {}",
+            path.display(),
+            e
+        ))
+        .with_source(e)
+    })?;
+
+    let is_json = path.extension().is_some_and(|ext| ext == "json")
+        || (path.extension().is_none_or(|ext| ext != "toml")
+            && content.trim_start().starts_with('{'));
+    let table: HashMap<String, serde_json::Value> = if is_json {
+        serde_json::from_str(&content).map_err(|e| {
+            let message = e.to_string();
+            AppError::parse(path, Some(e.line()), message, e)
+        })?
+    } else {
+        toml::from_str::<HashMap<String, toml::Value>>(&content)
+            .map_err(|e| AppError::toml_parse(path, &content, e))?
+            .into_iter()
+            .map(|(name, value)| (name, toml_to_json(value)))
+            .collect()
+    };
+
+    table
+        .into_iter()
+        .map(|(name, value)| {
+            // Lists become one item per line, for loops in templates
+            let text = match value {
+                serde_json::Value::Array(items) => items
+                    .into_iter()
+                    .map(scalar_text)
+                    .collect::<Option<Vec<_>>>()
+                    .map(|items| items.join("\n")),
+                value => scalar_text(value),
+            };
+            text.map(|text| (name.clone(), text))
+                .ok_or_else(|| AppError::Parse {
+                    path: path.to_path_buf(),
+                    line: None,
+                    message: format!(
+                        "Variable '{}' must be a string, number, boolean or a list of them",
+                        name
+                    ),
+                    source: None,
+                })
+        })
+        .collect()
+}
+
+fn scalar_text(value: serde_json::Value) -> Option<String> {
+    match value {
+        serde_json::Value::String(text) => Some(text),
+        serde_json::Value::Number(_) | serde_json::Value::Bool(_) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+fn toml_to_json(value: toml::Value) -> serde_json::Value {
+    match value {
+        toml::Value::String(text) => serde_json::Value::String(text),
+        toml::Value::Integer(number) => number.into(),
+        toml::Value::Float(number) => number.into(),
+        toml::Value::Boolean(flag) => flag.into(),
+        toml::Value::Datetime(datetime) => serde_json::Value::String(datetime.to_string()),
+        toml::Value::Array(items) => items.into_iter().map(toml_to_json).collect(),
+        // Tables are rejected by the caller
+        toml::Value::Table(_) => serde_json::Value::Null,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_values_from_args_file_and_environment() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("vars.toml");
+        std::fs::write(&file, "lang = \"Rust\"\nlines = 20\nfiles = [\"a\", 1]\n").unwrap();
+
+        let mut values =
+            VariableValues::from_args(&["lang=Go=lang".to_string()], Some(&file)).unwrap();
+        values.env =
+            HashMap::from([("PROMPTHEUS_VAR_CODE_STYLE".to_string(), "terse".to_string())]);
+        values.interactive = false;
+        let rendered = values
+            .render("<lang> <lines> <code-style> <tone=friendly> <lang> <files>")
+            .unwrap();
+        assert_eq!(rendered, "Go=lang 20 terse friendly Go=lang a\n1");
+
+        let json = dir.path().join("vars.json");
+        std::fs::write(&json, r#"{"lang": {"nested": 1}}"#).unwrap();
+        assert!(matches!(
+            VariableValues::from_args(&[], Some(&json)),
+            Err(AppError::Parse { .. })
+        ));
+        assert!(matches!(
+            VariableValues::from_args(&["novalue".to_string()], None),
+            Err(AppError::Usage(_))
+        ));
+    }
+
+    #[test]
+    fn test_typed_variables_are_parsed_and_validated() {
+        let lang = VariableSpec::parse("lang:rust|go \"Target language\"", Some("rust"));
+        assert_eq!(lang.name, "lang");
+        assert_eq!(
+            lang.kind,
+            VariableKind::Enum(vec!["rust".to_string(), "go".to_string()])
+        );
+        assert_eq!(lang.description.as_deref(), Some("Target language"));
+        assert_eq!(lang.validate("2"), Ok("go".to_string()));
+        assert_eq!(lang.validate("Go"), Ok("go".to_string()));
+        assert_eq!(lang.validate(""), Ok("rust".to_string()));
+        assert!(lang.validate("java").is_err());
+
+        let lines = VariableSpec::parse("lines?:int", None);
+        assert!(lines.optional && !lines.is_required());
+        assert_eq!(lines.validate(""), Ok(String::new()));
+        assert!(lines.validate("many").is_err());
+
+        let ticket = VariableSpec::parse("ticket:/^[A-Z]+-[0-9]+$/", None);
+        assert_eq!(ticket.validate("AB-12"), Ok("AB-12".to_string()));
+        assert!(ticket.validate("ab-12").is_err());
+        assert!(ticket.validate("").is_err());
+        assert!(
+            VariableSpec::parse("name:string", None)
+                .validate("")
+                .is_err()
+        );
+        assert_eq!(
+            VariableSpec::parse("ok:bool", None).validate("Yes"),
+            Ok("true".to_string())
+        );
+
+        // Placeholders outside the syntax stay plain variables
+        for raw in ["file name", "a:unknown", "b:", "c:/[/"] {
+            let plain = VariableSpec::parse(raw, None);
+            assert_eq!(plain.name, raw);
+            assert_eq!(plain.kind, VariableKind::String);
+        }
+        // Plain placeholders accept an empty answer, as they always did
+        assert_eq!(
+            VariableSpec::parse("name", None).validate(""),
+            Ok(String::new())
+        );
+
+        let values = VariableValues {
+            vars: HashMap::from([("n".to_string(), "x".to_string())]),
+            ..Default::default()
+        };
+        assert!(matches!(
+            values.render("<n:int>"),
+            Err(AppError::Usage(message)) if message.contains("'n'")
+        ));
+        let values = VariableValues {
+            vars: HashMap::from([("n".to_string(), "3".to_string())]),
+            ..Default::default()
+        };
+        assert_eq!(values.render("<n:int> <m?:bool>").unwrap(), "3 ");
+    }
+
+    #[test]
+    fn test_missing_values_without_terminal_are_reported() {
+        let err = VariableValues::default()
+            .render("Review <file> in <language> <tone=calm>")
+            .unwrap_err();
+        assert!(matches!(err, AppError::Usage(_)));
+        assert!(err.to_string().contains("file, language"));
+    }
+}